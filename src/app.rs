@@ -3,7 +3,7 @@ use gix::Repository;
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio_util::sync::CancellationToken;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -14,32 +14,249 @@ pub enum PanelFocus {
 }
 
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CommitInfo {
     pub hash: String,
     pub short_hash: String,
     pub author: String,
+    /// Author date formatted in the local timezone for the history list
     pub date: String,
+    /// First line of the commit message
     pub subject: String,
+    /// Path of the file at this commit, which differs from the selected path before a rename
+    #[serde(default)]
+    pub file_path: Option<String>,
+    #[serde(default)]
+    pub author_email: String,
+    /// Author timestamp (seconds since the Unix epoch)
+    #[serde(default)]
+    pub author_time: i64,
+    /// Offset of the author's timezone from UTC in seconds
+    #[serde(default)]
+    pub author_utc_offset: i32,
+    #[serde(default)]
+    pub committer: String,
+    #[serde(default)]
+    pub committer_email: String,
+    /// Committer timestamp (seconds since the Unix epoch)
+    #[serde(default)]
+    pub committer_time: i64,
+    /// Offset of the committer's timezone from UTC in seconds
+    #[serde(default)]
+    pub committer_utc_offset: i32,
+    #[serde(default)]
+    pub parent_ids: Vec<String>,
+    /// Message after the subject line, without the trailer block
+    #[serde(default)]
+    pub body: String,
+    /// Trailers such as `Signed-off-by` from the last paragraph of the message, in order
+    #[serde(default)]
+    pub trailers: Vec<(String, String)>,
+    /// Commits of the same history whose nearest listed ancestor is this commit, each with the
+    /// index of the parent it was reached through; the history graph is drawn from these
+    #[serde(default)]
+    pub graph_children: Vec<(String, usize)>,
+    /// In the history of a directory, the number of files below it the commit changed
+    #[serde(default)]
+    pub files_changed: Option<usize>,
 }
 
+/// How a commit changed a file relative to its first parent
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    /// Renamed from the given path
+    Renamed(String),
+}
+
+/// A file touched by a commit, shown in the commit detail pane
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedFile {
+    pub path: String,
+    pub kind: FileChangeKind,
+}
+
+/// Kind of reference offered by the ref picker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RefKind {
+    Head,
+    Branch,
+    RemoteBranch,
+    Tag,
+}
+
+/// A reference the file tree and histories can be rooted at
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefInfo {
+    /// Short name such as `main`, `origin/main` or `v1.0`
+    pub name: String,
+    /// Full name such as `refs/heads/main`, which stays unambiguous when a branch and a tag
+    /// share a short name
+    #[serde(default)]
+    pub full_name: String,
+    pub kind: RefKind,
+    /// Commit the reference points at, with tags peeled
+    pub commit_hash: String,
+    /// Committer timestamp of the tip commit (seconds since the Unix epoch)
+    pub commit_time: i64,
+    /// Tip commit date formatted in the local timezone
+    pub date: String,
+}
+
+/// Branches and tags whose history contains a commit, like `git tag --contains`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainingRefs {
+    /// Local and remote-tracking branches, in ref picker order
+    pub branches: Vec<RefInfo>,
+    /// Tags ordered from the oldest tip commit to the newest
+    pub tags: Vec<RefInfo>,
+}
+
+impl ContainingRefs {
+    /// The first release that shipped the commit
+    pub fn earliest_tag(&self) -> Option<&RefInfo> {
+        self.tags.first()
+    }
+}
+
+/// Short name of a revision the tree is rooted at (HEAD if None): full ref names lose their
+/// `refs/...` prefix and commit ids picked in the history are abbreviated
+pub fn revision_label(revision: Option<&str>) -> &str {
+    let Some(revision) = revision else {
+        return "HEAD";
+    };
+    let is_commit_id = revision.len() == 40 && revision.chars().all(|c| c.is_ascii_hexdigit());
+    if is_commit_id {
+        return &revision[..8];
+    }
+    ["refs/heads/", "refs/remotes/", "refs/tags/"]
+        .iter()
+        .find_map(|prefix| revision.strip_prefix(prefix))
+        .unwrap_or(revision)
+}
+
+/// State of the ref picker popup
+#[derive(Debug, Default)]
+pub struct RefPickerState {
+    /// Filter typed by the user; used as a revspec when no reference matches it
+    pub query: String,
+    /// None until the references have been loaded
+    pub refs: Option<Vec<RefInfo>>,
+    /// Index into the filtered references
+    pub selected: usize,
+    /// Set once a reference was picked with the arrow keys, which then wins over the query
+    pub selection_moved: bool,
+    /// Commit the query resolves to as a revspec (such as an abbreviated hash), if any
+    pub query_commit: Option<String>,
+}
+
+impl RefPickerState {
+    /// References matching the query, best fuzzy match first
+    pub fn filtered_refs(&self) -> Vec<&RefInfo> {
+        use fuzzy_matcher::skim::SkimMatcherV2;
+        use fuzzy_matcher::FuzzyMatcher;
+
+        let Some(refs) = &self.refs else {
+            return Vec::new();
+        };
+        if self.query.is_empty() {
+            return refs.iter().collect();
+        }
+
+        let matcher = SkimMatcherV2::default();
+        let mut scored: Vec<(i64, &RefInfo)> = refs
+            .iter()
+            .filter_map(|r| matcher.fuzzy_match(&r.name, &self.query).map(|score| (score, r)))
+            .collect();
+        // Stable sort keeps the branch/remote/tag order among equal scores
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        scored.into_iter().map(|(_, r)| r).collect()
+    }
+
+    pub fn selected_ref(&self) -> Option<&RefInfo> {
+        self.filtered_refs().get(self.selected).copied()
+    }
+
+    /// Whether Enter takes the query as a revspec rather than the selected reference: a query
+    /// that resolves wins over fuzzy matches unless a reference was picked explicitly
+    pub fn uses_query(&self) -> bool {
+        self.query_commit.is_some() && !self.selection_moved
+    }
+}
+
+/// Blame information for a single line of a file at a given commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlameLine {
+    /// Commit that last modified this line
+    pub commit_hash: String,
+    pub short_hash: String,
+    pub author: String,
+    /// Author timestamp of the commit (seconds since the Unix epoch)
+    pub timestamp: i64,
+    /// Line number (0-based) of this line in the blamed commit's version of the file
+    pub original_line_number: usize,
+    /// Path of the file in the blamed commit
+    pub original_path: String,
+}
+
+/// A jump to a commit that has not been streamed into the history list yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingJump {
+    pub commit_hash: String,
+    /// Line (0-based) to place the cursor on once the commit is selected,
+    /// or `None` to follow the current line via smart cursor positioning
+    pub line_number: Option<usize>,
+}
+
+/// A range of lines whose history is shown instead of the whole file's
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineRange {
+    /// Commit in whose version of the file the range was selected
+    pub commit_hash: String,
+    /// Path of the file at that commit
+    pub file_path: String,
+    /// First line (0-based) of the range
+    pub start_line: usize,
+    /// Last line (0-based, inclusive) of the range
+    pub end_line: usize,
+}
 
 #[derive(Debug)]
 pub struct HistoryState {
     pub commit_list: Vec<CommitInfo>,
+    /// Graph column for `commit_list`, laid out again on the first draw after the list changes
+    pub graph: Option<Vec<String>>,
     pub selected_commit_index: Option<usize>,
     pub selected_commit_hash: Option<String>,
     pub is_loading_more: bool,
     pub history_complete: bool,
     pub next_chunk_offset: usize,
     pub streaming_cancellation_token: Option<CancellationToken>,
+    pub pending_jump: Option<PendingJump>,
+    /// Set while the history is filtered to the commits that touched a line range
+    pub line_range: Option<LineRange>,
+    /// Show the full message and changed files of the selected commit below the list
+    pub show_commit_details: bool,
+    /// How merges are simplified when listing the history
+    pub mode: HistoryMode,
+    /// Files changed by `changed_files_commit`, once loaded
+    pub changed_files: Option<Vec<ChangedFile>>,
+    /// Commit the changed files were requested for
+    pub changed_files_commit: Option<String>,
+    /// Branches and tags containing `containing_refs_commit`, once loaded
+    pub containing_refs: Option<ContainingRefs>,
+    /// Commit the containing refs were requested for
+    pub containing_refs_commit: Option<String>,
+    /// Cancels the search for refs containing `containing_refs_commit`
+    pub containing_refs_cancellation_token: Option<CancellationToken>,
 }
 
 #[derive(Debug, Clone)]
 pub enum DiffLineType {
     Added,
     Removed,
-    Modified,
     Unchanged,
 }
 
@@ -49,20 +266,183 @@ pub struct DiffLine {
     pub old_line_num: Option<usize>,
     pub new_line_num: Option<usize>,
     pub content: String,
+    /// Byte ranges of the words that changed relative to the paired line on the other side
+    pub changed_ranges: Vec<std::ops::Range<usize>>,
+}
+
+/// What the selected commit is compared against in the diff view
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffBase {
+    /// The n-th parent of the selected commit (0 is the first parent)
+    Parent(usize),
+    /// A commit marked in the history list
+    Commit(String),
+    /// The staged version of the file; shown as the newer side
+    Index,
+    /// The file as it is in the working tree; shown as the newer side
+    WorkingTree,
+}
+
+impl Default for DiffBase {
+    fn default() -> Self {
+        DiffBase::Parent(0)
+    }
+}
+
+impl DiffBase {
+    /// Short description for titles and status messages
+    pub fn describe(&self) -> String {
+        match self {
+            DiffBase::Parent(0) => "parent".to_string(),
+            DiffBase::Parent(n) => format!("parent {}", n + 1),
+            DiffBase::Commit(hash) => hash.chars().take(8).collect(),
+            DiffBase::Index => "index".to_string(),
+            DiffBase::WorkingTree => "working tree".to_string(),
+        }
+    }
+}
+
+/// How merges are treated when listing a file's history, mirroring `git log` history simplification
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryMode {
+    /// Git's default: a merge that matches one of its parents is skipped and only that parent is followed
+    #[default]
+    Simplified,
+    /// Like `--full-history`: follow every parent and list merges that differ from any of them
+    FullHistory,
+    /// Like `--first-parent`: follow only the mainline and compare each commit with its first parent
+    FirstParent,
+}
+
+impl HistoryMode {
+    pub fn next(self) -> Self {
+        match self {
+            HistoryMode::Simplified => HistoryMode::FirstParent,
+            HistoryMode::FirstParent => HistoryMode::FullHistory,
+            HistoryMode::FullHistory => HistoryMode::Simplified,
+        }
+    }
+
+    /// Short description for titles and status messages
+    pub fn describe(self) -> &'static str {
+        match self {
+            HistoryMode::Simplified => "simplified",
+            HistoryMode::FullHistory => "full history",
+            HistoryMode::FirstParent => "first parent",
+        }
+    }
+}
+
+/// A row of the side-by-side diff, as indices into the diff lines shown on each side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SideBySideRow {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+}
+
+/// Align a unified diff into side-by-side rows.
+///
+/// Unchanged lines appear on both sides. Within a hunk, removed lines are paired with the added
+/// lines that replace them, and the shorter side is padded with empty rows.
+pub fn side_by_side_rows(diff_lines: &[DiffLine]) -> Vec<SideBySideRow> {
+    let mut rows = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+
+    let flush = |rows: &mut Vec<SideBySideRow>, removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+        for i in 0..removed.len().max(added.len()) {
+            rows.push(SideBySideRow {
+                left: removed.get(i).copied(),
+                right: added.get(i).copied(),
+            });
+        }
+        removed.clear();
+        added.clear();
+    };
+
+    for (idx, diff_line) in diff_lines.iter().enumerate() {
+        match diff_line.line_type {
+            DiffLineType::Removed => removed.push(idx),
+            DiffLineType::Added => added.push(idx),
+            DiffLineType::Unchanged => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push(SideBySideRow {
+                    left: Some(idx),
+                    right: Some(idx),
+                });
+            }
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+
+    rows
+}
+
+/// The old and new file of a diff, rebuilt from its lines, with their highlighting keys
+#[derive(Debug)]
+pub struct DiffSides {
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+    pub old_key: crate::syntax::HighlightKey,
+    pub new_key: crate::syntax::HighlightKey,
+}
+
+impl DiffSides {
+    /// Split the diff of `file_path` into the files on each side
+    pub fn new(file_path: Option<&Path>, diff_lines: &[DiffLine]) -> Self {
+        let mut old = Vec::new();
+        let mut new = Vec::new();
+        for diff_line in diff_lines {
+            let content = diff_line.content.trim_end_matches('\n');
+            if diff_line.old_line_num.is_some() {
+                old.push(content.to_string());
+            }
+            if diff_line.new_line_num.is_some() {
+                new.push(content.to_string());
+            }
+        }
+        Self {
+            old_key: crate::syntax::HighlightKey::new(file_path, &old),
+            new_key: crate::syntax::HighlightKey::new(file_path, &new),
+            old,
+            new,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct InspectorState {
     pub current_content: Vec<String>,
-    pub current_blame: Option<String>,
+    pub current_blame: Option<Vec<BlameLine>>,
+    /// Commit the current blame was requested for (None if no blame has been requested)
+    pub blame_commit_hash: Option<String>,
+    /// Blame jump waiting for the blame of its commit to load, from the line it was asked for
+    pub pending_blame_jump: Option<PendingJump>,
+    pub show_blame: bool,
     pub scroll_vertical: u16,
     pub scroll_horizontal: u16,
     pub visible_height: usize,
     pub cursor_line: usize,
     pub cursor_column: usize,
     pub show_diff_view: bool,
+    /// Show the diff as parent and commit side by side instead of inline
+    pub diff_side_by_side: bool,
+    /// What the diff view compares the selected commit against
+    pub diff_base: DiffBase,
     pub diff_lines: Option<Vec<DiffLine>>,
-    pub parent_commit_hash: Option<String>,
+    /// Commit the loaded diff was computed against (None for the index and working tree)
+    pub base_commit_hash: Option<String>,
+    /// Line where the current line selection started (the cursor is the other end)
+    pub selection_anchor: Option<usize>,
+    /// Syntax highlighting of displayed content, reused while scrolling
+    pub highlight_cache: crate::syntax::HighlightCache,
+    /// Highlighting key of `current_content`, built when it is first drawn after loading
+    pub content_highlight_key: Option<crate::syntax::HighlightKey>,
+    /// Sides of `diff_lines` for highlighting, built when the diff is first drawn after loading
+    pub diff_sides: Option<DiffSides>,
+    /// In the history of a directory, the files below it changed by the selected commit,
+    /// listed in place of file content
+    pub directory_changes: Vec<ChangedFile>,
 }
 
 #[derive(Debug)]
@@ -71,10 +451,25 @@ pub struct UIState {
     pub status_message: String,
     pub is_loading: bool,
     pub force_redraw: bool,
+    /// Ref picker popup, open while Some
+    pub ref_picker: Option<RefPickerState>,
 }
 
 pub struct App {
     pub repo: Repository,
+    /// Revision the file tree and all histories are rooted at (None for HEAD)
+    pub revision: Option<String>,
+    /// While browsing the tree of a commit picked in the history, the revision to return to
+    pub revision_before_commit_tree: Option<Option<String>>,
+    /// References by the commit they point at, used to decorate the history
+    pub refs_by_commit: HashMap<String, Vec<RefInfo>>,
+    /// Changes in the index and working tree once loaded, shown in the tree while it is rooted at HEAD
+    pub worktree_status: Option<crate::git_utils::WorktreeStatus>,
+    /// Files deleted in the history of the revision; Some once they have been asked for,
+    /// empty until they are loaded
+    pub deleted_files: Option<Vec<crate::git_utils::DeletedFile>>,
+    /// Cancels the search for deleted files when the revision changes
+    pub deleted_files_cancellation_token: Option<CancellationToken>,
     pub should_quit: bool,
 
     // Position Tracking for Same-Line Feature
@@ -96,6 +491,12 @@ impl App {
     pub fn new(repo: Repository) -> Self {
         let app = Self {
             repo,
+            revision: None,
+            revision_before_commit_tree: None,
+            refs_by_commit: HashMap::new(),
+            worktree_status: None,
+            deleted_files: None,
+            deleted_files_cancellation_token: None,
             should_quit: false,
             per_commit_cursor_positions: HashMap::new(),
             last_commit_for_mapping: None,
@@ -109,6 +510,17 @@ impl App {
         app
     }
 
+    /// Replace the references used for decorations, keeping their order per commit
+    pub fn set_refs(&mut self, refs: &[RefInfo]) {
+        self.refs_by_commit.clear();
+        for r in refs {
+            self.refs_by_commit
+                .entry(r.commit_hash.clone())
+                .or_default()
+                .push(r.clone());
+        }
+    }
+
     pub fn next_panel(&mut self) {
         self.ui.active_panel = match self.ui.active_panel {
             PanelFocus::Navigator => PanelFocus::History,
@@ -167,6 +579,31 @@ impl App {
         }
     }
 
+    /// Get the selected directory, whose history covers all files below it
+    pub fn get_history_directory(&self) -> Option<PathBuf> {
+        let path = self.navigator.get_selection()?;
+        let is_deleted_files = path == Path::new(crate::tree::DELETED_FILES_PATH);
+        (self.navigator.is_path_directory(&path) && !is_deleted_files).then_some(path)
+    }
+
+    /// Get the path whose history is shown: the active file or the selected directory
+    pub fn get_history_path(&self) -> Option<PathBuf> {
+        self.get_active_file().or_else(|| self.get_history_directory())
+    }
+
+    /// Get the path the active file had at the given commit, following renames recorded in the history
+    pub fn get_file_path_at_commit(&self, commit_hash: &str) -> Option<PathBuf> {
+        let active_file = self.get_active_file()?;
+        let historical_path = self
+            .history
+            .commit_list
+            .iter()
+            .find(|commit| commit.hash == commit_hash)
+            .and_then(|commit| commit.file_path.as_ref())
+            .map(PathBuf::from);
+        Some(historical_path.unwrap_or(active_file))
+    }
+
 
 
     /// Get navigator search query
@@ -208,6 +645,10 @@ impl App {
                 return Ok(());
             }
         };
+        let file_path = self
+            .get_file_path_at_commit(&commit_hash)
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or(file_path);
 
         // Load file content at the selected commit
         self.ui.is_loading = true;
@@ -216,7 +657,7 @@ impl App {
 
         match crate::git_utils::get_file_content_at_commit(&self.repo, &file_path, &commit_hash) {
             Ok(content) => {
-                self.inspector.current_content = content;
+                self.inspector.set_content(content);
                 self.inspector.scroll_horizontal = 0;
                 self.inspector.cursor_line = 0;
                 self.ensure_inspector_cursor_visible(); // Use unified scroll management
@@ -285,7 +726,7 @@ impl App {
         let file_path = match self.get_active_file() {
             Some(path) => path.to_string_lossy().to_string(),
             None => {
-                self.history.commit_list.clear();
+                self.history.clear_commits();
                 self.history.selected_commit_index = None;
                 self.history.selected_commit_hash = None;
                 self.inspector.current_content.clear();
@@ -301,9 +742,9 @@ impl App {
         self.ui.is_loading = true;
         self.ui.status_message = format!("Loading commit history for {}...", file_path);
 
-        match crate::git_utils::get_commit_history_for_file(&self.repo, &file_path) {
+        match crate::git_utils::get_commit_history_for_file(&self.repo, &file_path, self.revision.as_deref(), self.history.mode) {
             Ok(commits) => {
-                self.history.commit_list = commits;
+                self.history.set_commits(commits);
                 if !self.history.commit_list.is_empty() {
                     // Auto-select the first (most recent) commit
                     self.history.selected_commit_index = Some(0);
@@ -325,7 +766,7 @@ impl App {
                 }
             }
             Err(e) => {
-                self.history.commit_list.clear();
+                self.history.clear_commits();
                 self.history.selected_commit_index = None;
                 self.history.selected_commit_hash = None;
                 self.inspector.current_content.clear();
@@ -340,57 +781,64 @@ impl App {
     pub fn from_test_config(config: &crate::test_config::TestConfig, repo: Repository) -> Self {
         let mut app = Self {
             repo,
+            revision: config.revision.clone(),
+            revision_before_commit_tree: None,
+            refs_by_commit: HashMap::new(),
+            worktree_status: None,
+            deleted_files: None,
+            deleted_files_cancellation_token: None,
             should_quit: false,
             per_commit_cursor_positions: HashMap::new(),
             last_commit_for_mapping: None,
             active_background_tasks: 0,
-            navigator: {
-                let mut navigator = NavigatorState::new(config.file_tree.clone());
-                
-                // Set search mode if specified in test config
-                if config.in_search_mode {
-                    let _ = navigator.handle_event(NavigatorEvent::StartSearch);
-                    if !config.search_query.is_empty() {
-                        let _ = navigator.handle_event(NavigatorEvent::UpdateSearchQuery(config.search_query.clone()));
-                    }
-                }
-                
-                // Set initial selection if we have a selected index
-                if let Some(index) = config.selected_file_navigator_index {
-                    // Navigate down to the selected index
-                    for _ in 0..index {
-                        let _ = navigator.handle_event(NavigatorEvent::NavigateDown);
-                    }
-                }
-                
-                navigator
-            },
+            navigator: config.navigator(),
             history: HistoryState {
                 commit_list: config.commit_list.clone(),
+                graph: None,
                 selected_commit_index: None, // Will be set below based on valid commit index
                 selected_commit_hash: None, // Will be set below based on valid commit index
                 is_loading_more: false,
                 history_complete: false,
                 next_chunk_offset: 0,
                 streaming_cancellation_token: None,
+                pending_jump: None,
+                line_range: config.line_range.clone(),
+                show_commit_details: config.show_commit_details,
+                mode: config.history_mode,
+                changed_files: config.changed_files.clone(),
+                changed_files_commit: None,
+                containing_refs: config.containing_refs.clone(),
+                containing_refs_commit: None,
+                containing_refs_cancellation_token: None,
             },
             inspector: InspectorState {
                 current_content: config.current_content.clone(),
-                current_blame: None,
+                current_blame: config.current_blame.clone(),
+                blame_commit_hash: None,
+                pending_blame_jump: None,
+                show_blame: config.show_blame,
                 scroll_vertical: config.inspector_scroll_vertical,
                 scroll_horizontal: config.inspector_scroll_horizontal,
                 visible_height: 20, // Default reasonable value
                 cursor_line: config.cursor_line,
                 cursor_column: config.cursor_column,
                 show_diff_view: config.show_diff_view,
+                diff_side_by_side: config.diff_side_by_side,
+                diff_base: config.diff_base.clone(),
                 diff_lines: None,
-                parent_commit_hash: None,
+                base_commit_hash: None,
+                selection_anchor: config.selection_anchor,
+                highlight_cache: crate::syntax::HighlightCache::new(),
+                content_highlight_key: None,
+                diff_sides: None,
+                directory_changes: Vec::new(),
             },
             ui: UIState {
                 active_panel: config.active_panel,
                 status_message: config.status_message.clone(),
                 is_loading: config.is_loading,
                 force_redraw: false,
+                ref_picker: None,
             },
         };
 
@@ -403,6 +851,12 @@ impl App {
             }
         }
 
+        // Blame provided by the config belongs to the selected commit
+        if app.inspector.current_blame.is_some() {
+            app.inspector.blame_commit_hash = app.history.selected_commit_hash.clone();
+        }
+
+        app.set_refs(&config.refs);
 
         app
     }
@@ -730,6 +1184,8 @@ mod tests {
                 author: "Alice Developer".to_string(),
                 date: "2023-01-01".to_string(),
                 subject: "Initial commit".to_string(),
+                file_path: None,
+                ..Default::default()
             },
             CommitInfo {
                 hash: "def456ghi789".to_string(),
@@ -737,6 +1193,8 @@ mod tests {
                 author: "Bob Coder".to_string(),
                 date: "2023-01-02".to_string(),
                 subject: "Add feature".to_string(),
+                file_path: None,
+                ..Default::default()
             },
         ]
     }
@@ -1003,6 +1461,37 @@ mod tests {
             // Initially no selection
             assert_eq!(path, None);
         }
+
+        #[test]
+        fn test_get_file_path_at_commit_follows_renames() {
+            let repo = create_test_repo();
+            let mut app = App::new(repo);
+            let mut tree = FileTree::new();
+            tree.root.push(TreeNode::new_file(
+                "new.rs".to_string(),
+                PathBuf::from("src/new.rs"),
+            ));
+            app.navigator = crate::navigator::NavigatorState::new(tree);
+            app.navigator
+                .handle_event(crate::navigator::NavigatorEvent::SelectFile(PathBuf::from(
+                    "src/new.rs",
+                )))
+                .unwrap();
+
+            let mut commits = create_test_commits();
+            commits[0].file_path = Some("src/old.rs".to_string());
+            app.history.commit_list = commits;
+
+            assert_eq!(
+                app.get_file_path_at_commit("abc123def456"),
+                Some(PathBuf::from("src/old.rs"))
+            );
+            // Commits without a recorded path fall back to the selected file
+            assert_eq!(
+                app.get_file_path_at_commit("def456ghi789"),
+                Some(PathBuf::from("src/new.rs"))
+            );
+        }
     }
 
 
@@ -1307,6 +1796,229 @@ mod tests {
             assert!(mapped_line2 < app.inspector.current_content.len());
         }
     }
+    mod side_by_side_diff {
+        use super::*;
+
+        fn diff_line(line_type: DiffLineType, old: Option<usize>, new: Option<usize>, content: &str) -> DiffLine {
+            DiffLine {
+                line_type,
+                old_line_num: old,
+                new_line_num: new,
+                content: format!("{}\n", content),
+                changed_ranges: Vec::new(),
+            }
+        }
+
+        fn sample_diff() -> Vec<DiffLine> {
+            vec![
+                diff_line(DiffLineType::Unchanged, Some(1), Some(1), "a"),
+                diff_line(DiffLineType::Removed, Some(2), None, "b"),
+                diff_line(DiffLineType::Removed, Some(3), None, "c"),
+                diff_line(DiffLineType::Added, None, Some(2), "B"),
+                diff_line(DiffLineType::Unchanged, Some(4), Some(3), "d"),
+                diff_line(DiffLineType::Added, None, Some(4), "e"),
+            ]
+        }
+
+        #[test]
+        fn test_side_by_side_rows_align_hunks() {
+            let rows = side_by_side_rows(&sample_diff());
+
+            assert_eq!(
+                rows,
+                vec![
+                    SideBySideRow { left: Some(0), right: Some(0) },
+                    SideBySideRow { left: Some(1), right: Some(3) },
+                    SideBySideRow { left: Some(2), right: None },
+                    SideBySideRow { left: Some(4), right: Some(4) },
+                    SideBySideRow { left: None, right: Some(5) },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_side_by_side_rows_empty_diff() {
+            assert!(side_by_side_rows(&[]).is_empty());
+        }
+
+        #[test]
+        fn test_line_count_follows_view() {
+            let mut inspector = InspectorState::new();
+            inspector.current_content = vec!["a".to_string(), "B".to_string(), "d".to_string(), "e".to_string()];
+            inspector.diff_lines = Some(sample_diff());
+            assert_eq!(inspector.line_count(), 4);
+
+            inspector.show_diff_view = true;
+            assert_eq!(inspector.line_count(), 6);
+
+            inspector.diff_side_by_side = true;
+            assert_eq!(inspector.line_count(), 5);
+        }
+
+        #[test]
+        fn test_toggle_side_by_side_keeps_cursor_on_line() {
+            let mut inspector = InspectorState::new();
+            inspector.diff_lines = Some(sample_diff());
+            inspector.show_diff_view = true;
+
+            // The added line "B" is diff line 3 and shares row 1 with the removed "b"
+            inspector.cursor_line = 3;
+            inspector.toggle_diff_side_by_side();
+            assert!(inspector.diff_side_by_side);
+            assert_eq!(inspector.cursor_line, 1);
+
+            // Going back prefers the commit's side of the row
+            inspector.toggle_diff_side_by_side();
+            assert!(!inspector.diff_side_by_side);
+            assert_eq!(inspector.cursor_line, 3);
+
+            // A removed-only row maps back to its removed line
+            inspector.diff_side_by_side = true;
+            inspector.cursor_line = 2;
+            inspector.toggle_diff_side_by_side();
+            assert_eq!(inspector.cursor_line, 2);
+        }
+
+        #[test]
+        fn test_diff_view_maps_cursor_to_file_lines() {
+            let mut inspector = InspectorState::new();
+            inspector.current_content = vec!["a".to_string(), "B".to_string(), "d".to_string(), "e".to_string()];
+            inspector.diff_lines = Some(sample_diff());
+
+            // File line "d" is diff line 4
+            inspector.cursor_line = 2;
+            inspector.set_show_diff_view(true);
+            assert_eq!(inspector.cursor_line, 4);
+            inspector.set_show_diff_view(false);
+            assert_eq!(inspector.cursor_line, 2);
+
+            // A removed line is not in the file, the line before it is
+            inspector.set_show_diff_view(true);
+            inspector.cursor_line = 2;
+            inspector.set_show_diff_view(false);
+            assert_eq!(inspector.cursor_line, 0);
+
+            // Side by side, "e" is on the last row
+            inspector.diff_side_by_side = true;
+            inspector.cursor_line = 3;
+            inspector.set_show_diff_view(true);
+            assert_eq!(inspector.cursor_line, 4);
+            inspector.set_show_diff_view(false);
+            assert_eq!(inspector.cursor_line, 3);
+        }
+
+        #[test]
+        fn test_diff_against_working_tree_maps_to_old_side() {
+            let mut inspector = InspectorState::new();
+            inspector.diff_base = DiffBase::WorkingTree;
+            inspector.show_diff_view = true;
+
+            // The commit is the old side: its line "c" is diff line 2
+            inspector.cursor_line = 2;
+            inspector.set_diff_lines(Some(sample_diff()));
+            assert_eq!(inspector.cursor_line, 2);
+            inspector.set_diff_lines(None);
+            assert_eq!(inspector.cursor_line, 2);
+        }
+
+        #[test]
+        fn test_toggle_side_by_side_without_diff_keeps_cursor() {
+            let mut inspector = InspectorState::new();
+            inspector.cursor_line = 7;
+            inspector.toggle_diff_side_by_side();
+
+            assert!(inspector.diff_side_by_side);
+            assert_eq!(inspector.cursor_line, 7);
+        }
+    }
+
+    mod ref_picker_tests {
+        use super::*;
+
+        fn ref_info(name: &str, kind: RefKind) -> RefInfo {
+            RefInfo {
+                name: name.to_string(),
+                full_name: format!("refs/{}", name),
+                kind,
+                commit_hash: "0123456789abcdef".to_string(),
+                commit_time: 0,
+                date: String::new(),
+            }
+        }
+
+        fn picker(query: &str) -> RefPickerState {
+            RefPickerState {
+                query: query.to_string(),
+                refs: Some(vec![
+                    ref_info("HEAD", RefKind::Head),
+                    ref_info("main", RefKind::Branch),
+                    ref_info("feature/parser", RefKind::Branch),
+                    ref_info("origin/main", RefKind::RemoteBranch),
+                    ref_info("v1.0", RefKind::Tag),
+                ]),
+                selected: 0,
+                selection_moved: false,
+                query_commit: None,
+            }
+        }
+
+        fn names(picker: &RefPickerState) -> Vec<&str> {
+            picker.filtered_refs().iter().map(|r| r.name.as_str()).collect()
+        }
+
+        #[test]
+        fn test_empty_query_lists_all_refs_in_order() {
+            assert_eq!(
+                names(&picker("")),
+                vec!["HEAD", "main", "feature/parser", "origin/main", "v1.0"]
+            );
+        }
+
+        #[test]
+        fn test_query_filters_fuzzily() {
+            assert_eq!(names(&picker("fp")), vec!["feature/parser"]);
+            assert_eq!(names(&picker("v10")), vec!["v1.0"]);
+
+            let main_picker = picker("main");
+            let main = names(&main_picker);
+            assert_eq!(main.len(), 2);
+            assert!(main.contains(&"main") && main.contains(&"origin/main"));
+
+            assert!(names(&picker("zzz")).is_empty());
+        }
+
+        #[test]
+        fn test_selected_ref_follows_filter() {
+            let mut picker = picker("v1");
+            assert_eq!(picker.selected_ref().map(|r| r.kind), Some(RefKind::Tag));
+
+            picker.selected = 5;
+            assert!(picker.selected_ref().is_none());
+
+            // Nothing is selectable while the refs are loading
+            let loading = RefPickerState::default();
+            assert!(loading.filtered_refs().is_empty());
+            assert!(loading.selected_ref().is_none());
+        }
+
+        #[test]
+        fn test_set_refs_groups_by_commit() {
+            let mut app = App::new(create_test_repo());
+            let mut tag = ref_info("v1.0", RefKind::Tag);
+            tag.commit_hash = "fedcba9876543210".to_string();
+            app.set_refs(&[ref_info("HEAD", RefKind::Head), ref_info("main", RefKind::Branch), tag]);
+
+            let names = |hash: &str| -> Vec<String> {
+                app.refs_by_commit[hash].iter().map(|r| r.name.clone()).collect()
+            };
+            assert_eq!(names("0123456789abcdef"), vec!["HEAD", "main"]);
+            assert_eq!(names("fedcba9876543210"), vec!["v1.0"]);
+
+            // Reloading replaces the old decorations
+            app.set_refs(&[ref_info("main", RefKind::Branch)]);
+            assert_eq!(app.refs_by_commit.len(), 1);
+        }
+    }
 }
 
 
@@ -1314,14 +2026,38 @@ impl HistoryState {
     pub fn new() -> Self {
         Self {
             commit_list: Vec::new(),
+            graph: None,
             selected_commit_index: None,
             selected_commit_hash: None,
             is_loading_more: false,
             history_complete: false,
             next_chunk_offset: 0,
             streaming_cancellation_token: None,
+            pending_jump: None,
+            line_range: None,
+            show_commit_details: false,
+            mode: HistoryMode::default(),
+            changed_files: None,
+            changed_files_commit: None,
+            containing_refs: None,
+            containing_refs_commit: None,
+            containing_refs_cancellation_token: None,
         }
     }
+
+    pub fn set_commits(&mut self, commits: Vec<CommitInfo>) {
+        self.commit_list = commits;
+        self.graph = None;
+    }
+
+    pub fn append_commits(&mut self, commits: impl IntoIterator<Item = CommitInfo>) {
+        self.commit_list.extend(commits);
+        self.graph = None;
+    }
+
+    pub fn clear_commits(&mut self) {
+        self.set_commits(Vec::new());
+    }
     
     pub fn reset_for_new_file(&mut self) {
         // Cancel any existing streaming task
@@ -1329,13 +2065,22 @@ impl HistoryState {
             token.cancel();
         }
         
-        self.commit_list.clear();
+        self.clear_commits();
         self.selected_commit_index = None;
         self.selected_commit_hash = None;
         self.is_loading_more = false;
         self.history_complete = false;
         self.next_chunk_offset = 0;
         self.streaming_cancellation_token = None;
+        self.pending_jump = None;
+        self.line_range = None;
+        self.changed_files = None;
+        self.changed_files_commit = None;
+        self.containing_refs = None;
+        self.containing_refs_commit = None;
+        if let Some(token) = self.containing_refs_cancellation_token.take() {
+            token.cancel();
+        }
     }
 }
 
@@ -1344,15 +2089,150 @@ impl InspectorState {
         Self {
             current_content: Vec::new(),
             current_blame: None,
+            blame_commit_hash: None,
+            pending_blame_jump: None,
+            show_blame: false,
             scroll_vertical: 0,
             scroll_horizontal: 0,
             visible_height: 20, // Default reasonable value
             cursor_line: 0,
             cursor_column: 0,
             show_diff_view: false,
+            diff_side_by_side: false,
+            diff_base: DiffBase::default(),
             diff_lines: None,
-            parent_commit_hash: None,
+            base_commit_hash: None,
+            selection_anchor: None,
+            highlight_cache: crate::syntax::HighlightCache::new(),
+            content_highlight_key: None,
+            diff_sides: None,
+            directory_changes: Vec::new(),
+        }
+    }
+
+    /// Replace the displayed file content
+    pub fn set_content(&mut self, content: Vec<String>) {
+        self.current_content = content;
+        self.content_highlight_key = None;
+    }
+
+    /// Replace the loaded diff, keeping the cursor on the same line of the file
+    pub fn set_diff_lines(&mut self, diff_lines: Option<Vec<DiffLine>>) {
+        let file_line = self.cursor_file_line();
+        self.diff_lines = diff_lines;
+        self.diff_sides = None;
+        self.cursor_line = self.row_of_file_line(file_line);
+    }
+
+    /// Show or hide the diff view, keeping the cursor on the same line of the file
+    pub fn set_show_diff_view(&mut self, show_diff_view: bool) {
+        let file_line = self.cursor_file_line();
+        self.show_diff_view = show_diff_view;
+        self.cursor_line = self.row_of_file_line(file_line);
+    }
+
+    /// The shown diff, if the cursor moves through its rows rather than the file content
+    fn shown_diff(&self) -> Option<&[DiffLine]> {
+        self.diff_lines.as_deref().filter(|_| self.show_diff_view)
+    }
+
+    /// Line number of a diff line in the selected commit's file; the commit is the new side
+    /// unless it is compared against the index or working tree
+    fn commit_line_num(&self, diff_line: &DiffLine) -> Option<usize> {
+        match self.diff_base {
+            DiffBase::Index | DiffBase::WorkingTree => diff_line.old_line_num,
+            _ => diff_line.new_line_num,
+        }
+    }
+
+    /// The file line (0-based) under the cursor. On a diff line missing from the selected
+    /// commit's file this is the closest line before it, or after it at the start of the file.
+    fn cursor_file_line(&self) -> usize {
+        let Some(diff_lines) = self.shown_diff() else {
+            return self.cursor_line;
+        };
+        let index = if self.diff_side_by_side {
+            side_by_side_rows(diff_lines)
+                .get(self.cursor_line)
+                .and_then(|row| row.right.or(row.left))
+                .unwrap_or(self.cursor_line)
+        } else {
+            self.cursor_line
+        };
+        let (before, after) = diff_lines.split_at((index + 1).min(diff_lines.len()));
+
+        before
+            .iter()
+            .rev()
+            .chain(after)
+            .find_map(|diff_line| self.commit_line_num(diff_line))
+            .map_or(0, |num| num - 1)
+    }
+
+    /// The cursor row showing a file line (0-based) in the current view
+    fn row_of_file_line(&self, file_line: usize) -> usize {
+        let Some(diff_lines) = self.shown_diff() else {
+            return file_line;
+        };
+        let Some(index) = diff_lines
+            .iter()
+            .position(|diff_line| self.commit_line_num(diff_line) == Some(file_line + 1))
+        else {
+            return file_line.min(self.line_count().saturating_sub(1));
+        };
+        if self.diff_side_by_side {
+            side_by_side_rows(diff_lines)
+                .iter()
+                .position(|row| row.left == Some(index) || row.right == Some(index))
+                .unwrap_or(index)
+        } else {
+            index
+        }
+    }
+
+    /// Drop any loaded or requested blame so it is fetched again when needed
+    pub fn clear_blame(&mut self) {
+        self.current_blame = None;
+        self.blame_commit_hash = None;
+        self.pending_blame_jump = None;
+    }
+
+    /// The selected lines (0-based, inclusive) between the selection anchor and the cursor
+    pub fn selected_range(&self) -> Option<(usize, usize)> {
+        self.selection_anchor
+            .map(|anchor| (anchor.min(self.cursor_line), anchor.max(self.cursor_line)))
+    }
+
+    /// Number of rows the cursor can move through in the current view
+    pub fn line_count(&self) -> usize {
+        match &self.diff_lines {
+            Some(diff_lines) if self.show_diff_view => {
+                if self.diff_side_by_side {
+                    side_by_side_rows(diff_lines).len()
+                } else {
+                    diff_lines.len()
+                }
+            }
+            _ if !self.directory_changes.is_empty() => self.directory_changes.len(),
+            _ => self.current_content.len(),
+        }
+    }
+
+    /// Switch the diff between inline and side-by-side, keeping the cursor on the same line
+    pub fn toggle_diff_side_by_side(&mut self) {
+        if let Some(diff_lines) = self.diff_lines.as_ref().filter(|_| self.show_diff_view) {
+            let rows = side_by_side_rows(diff_lines);
+            self.cursor_line = if self.diff_side_by_side {
+                rows.get(self.cursor_line)
+                    .and_then(|row| row.right.or(row.left))
+                    .unwrap_or(self.cursor_line)
+            } else {
+                rows.iter()
+                    .position(|row| row.left == Some(self.cursor_line) || row.right == Some(self.cursor_line))
+                    .unwrap_or(self.cursor_line)
+            };
         }
+        self.diff_side_by_side = !self.diff_side_by_side;
     }
 }
 
@@ -1363,6 +2243,7 @@ impl UIState {
             status_message: "Ready".to_string(),
             is_loading: false,
             force_redraw: false,
+            ref_picker: None,
         }
     }
 }
@@ -0,0 +1,200 @@
+use gix::Repository;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bumped whenever the layout of a cached value changes, which turns old entries into misses
+const CACHE_VERSION: u32 = 1;
+
+/// Entries kept per kind before the least recently written ones are removed
+const MAX_ENTRIES_PER_KIND: usize = 2000;
+
+/// Entries written to a kind between looking for entries to remove
+const PRUNE_INTERVAL: usize = 100;
+
+/// Entries written per kind since startup; the first write of a kind prunes its directory
+static WRITES: [AtomicUsize; 3] = [AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0)];
+
+/// Whether the cache is used in repositories that do not set `lineage.cache`. Unit tests open the
+/// repository they are run from, so there the cache is only used where a test turns it on.
+const ENABLED_BY_DEFAULT: bool = !cfg!(test);
+
+/// What a cache entry holds; each kind lives in its own directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    /// A file's history from a revision, valid while the revision points at the same commit
+    History,
+    /// The line mapping between two blobs
+    LineMapping,
+    /// The blame of a file at a commit
+    Blame,
+}
+
+impl CacheKind {
+    fn dir_name(self) -> &'static str {
+        match self {
+            CacheKind::History => "history",
+            CacheKind::LineMapping => "line-mappings",
+            CacheKind::Blame => "blame",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    version: u32,
+    /// The full key, as file names are only its hash
+    key: String,
+    value: T,
+}
+
+/// Directory of the on-disk cache, or None if it is turned off with `git config lineage.cache false`
+fn cache_dir(repo: &Repository, kind: CacheKind) -> Option<PathBuf> {
+    if !repo.config_snapshot().boolean("lineage.cache").unwrap_or(ENABLED_BY_DEFAULT) {
+        return None;
+    }
+    Some(repo.git_dir().join("lineage-cache").join(kind.dir_name()))
+}
+
+fn entry_path(repo: &Repository, kind: CacheKind, key: &str) -> Option<PathBuf> {
+    let hash = gix::objs::compute_hash(gix::hash::Kind::Sha1, gix::objs::Kind::Blob, key.as_bytes()).ok()?;
+    Some(cache_dir(repo, kind)?.join(format!("{}.json", hash)))
+}
+
+/// Read a cached value; any problem with the entry counts as a miss
+pub fn load<T: DeserializeOwned>(repo: &Repository, kind: CacheKind, key: &str) -> Option<T> {
+    let path = entry_path(repo, kind, key)?;
+    let data = std::fs::read(&path).ok()?;
+    match serde_json::from_slice::<Entry<T>>(&data) {
+        Ok(entry) if entry.version == CACHE_VERSION && entry.key == key => {
+            log::debug!("Cache hit for {:?} '{}'", kind, key);
+            Some(entry.value)
+        }
+        Ok(_) => None,
+        Err(e) => {
+            log::debug!("Ignoring unreadable cache entry {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Write a value to the cache; failures are logged and otherwise ignored
+pub fn store<T: Serialize>(repo: &Repository, kind: CacheKind, key: &str, value: &T) {
+    let Some(path) = entry_path(repo, kind, key) else {
+        return;
+    };
+    let entry = Entry {
+        version: CACHE_VERSION,
+        key: key.to_string(),
+        value,
+    };
+    if let Err(e) = write_entry(&path, &entry) {
+        log::warn!("Failed to write cache entry {}: {}", path.display(), e);
+        return;
+    }
+
+    // Listing the directory is slow once it is full, so it is not done for every entry
+    if WRITES[kind as usize]
+        .fetch_add(1, Ordering::Relaxed)
+        .is_multiple_of(PRUNE_INTERVAL)
+    {
+        let dir = path.parent().expect("Cache entries live in a directory");
+        if let Err(e) = prune(dir, MAX_ENTRIES_PER_KIND) {
+            log::warn!("Failed to prune cache directory {}: {}", dir.display(), e);
+        }
+    }
+}
+
+fn write_entry<T: Serialize>(path: &std::path::Path, entry: &Entry<T>) -> std::io::Result<()> {
+    std::fs::create_dir_all(path.parent().expect("Cache entries live in a directory"))?;
+
+    // Write to a temporary file first so concurrent readers never see half an entry
+    let temp_path = path.with_extension(format!("tmp{}", std::process::id()));
+    std::fs::write(&temp_path, serde_json::to_vec(entry)?)?;
+    std::fs::rename(&temp_path, path)
+}
+
+/// Remove the oldest entries of a directory until at most `max_entries` remain
+fn prune(dir: &std::path::Path, max_entries: usize) -> std::io::Result<()> {
+    let mut entries: Vec<(std::time::SystemTime, PathBuf)> = std::fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect();
+    if entries.len() <= max_entries {
+        return Ok(());
+    }
+
+    entries.sort();
+    for (_, path) in &entries[..entries.len() - max_entries] {
+        // Another instance may have removed it already
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repo(temp_dir: &tempfile::TempDir) -> Repository {
+        for args in [&["init", "-q"][..], &["config", "lineage.cache", "true"]] {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(temp_dir.path())
+                .status()
+                .expect("Failed to run git");
+        }
+        crate::git_utils::open_repository(temp_dir.path()).expect("Should open test repository")
+    }
+
+    #[test]
+    fn test_store_and_load() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo = init_repo(&temp_dir);
+
+        assert_eq!(load::<Vec<usize>>(&repo, CacheKind::Blame, "key"), None);
+        store(&repo, CacheKind::Blame, "key", &vec![1usize, 2, 3]);
+        assert_eq!(load::<Vec<usize>>(&repo, CacheKind::Blame, "key"), Some(vec![1, 2, 3]));
+
+        // Kinds do not share entries, and values of another shape are misses
+        assert_eq!(load::<Vec<usize>>(&repo, CacheKind::History, "key"), None);
+        assert_eq!(load::<String>(&repo, CacheKind::Blame, "key"), None);
+    }
+
+    #[test]
+    fn test_cache_can_be_turned_off() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        init_repo(&temp_dir);
+        std::process::Command::new("git")
+            .args(["config", "lineage.cache", "false"])
+            .current_dir(temp_dir.path())
+            .status()
+            .expect("Failed to run git");
+        let repo = crate::git_utils::open_repository(temp_dir.path()).unwrap();
+
+        store(&repo, CacheKind::Blame, "key", &1usize);
+        assert_eq!(load::<usize>(&repo, CacheKind::Blame, "key"), None);
+        assert!(!temp_dir.path().join(".git/lineage-cache").exists());
+    }
+
+    #[test]
+    fn test_prune_keeps_newest_entries() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for index in 0..5 {
+            let path = temp_dir.path().join(format!("{}.json", index));
+            std::fs::write(&path, "{}").unwrap();
+            let time = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000 + index);
+            std::fs::File::options().write(true).open(&path).unwrap().set_modified(time).unwrap();
+        }
+
+        prune(temp_dir.path(), 3).unwrap();
+
+        let mut names: Vec<String> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["2.json", "3.json", "4.json"]);
+    }
+}
@@ -6,6 +6,9 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
+    /// Explore the repository as of this revision instead of HEAD (branch, tag, commit or any revspec)
+    #[arg(long, value_name = "REV")]
+    pub rev: Option<String>,
 }
 
 #[derive(Subcommand, Debug, PartialEq)]
@@ -54,6 +57,11 @@ pub enum Commands {
         #[arg(short, long)]
         output: Option<String>,
     },
+    /// List the tags and branches containing a commit, starting with the earliest release
+    Contains {
+        /// Commit to look up (hash, tag, branch or any revspec)
+        revision: String,
+    },
     /// Run headless tests from a test script
     Test {
         /// Path to the test script file
@@ -38,6 +38,16 @@ pub enum Command {
     PreviousChange,
     NextChange,
     ToggleDiff,
+    ToggleSideBySide,
+    MarkDiffBase,
+    ToggleCommitDetails,
+    CycleHistoryMode,
+    CycleWorktreeDiff,
+    NextDiffParent,
+    ToggleBlame,
+    JumpToBlameCommit,
+    ToggleSelection,
+    LineRangeHistory,
 
     // Multi-step commands for testing
     Sequence(Vec<Command>),
@@ -77,6 +87,16 @@ impl Command {
             "previous_change" | "p" => Ok(Command::PreviousChange),
             "next_change" | "n" => Ok(Command::NextChange),
             "toggle_diff" | "d" => Ok(Command::ToggleDiff),
+            "toggle_side_by_side" | "s" => Ok(Command::ToggleSideBySide),
+            "mark_diff_base" | "m" => Ok(Command::MarkDiffBase),
+            "toggle_commit_details" | "i" => Ok(Command::ToggleCommitDetails),
+            "cycle_history_mode" | "f" => Ok(Command::CycleHistoryMode),
+            "diff_worktree" | "w" => Ok(Command::CycleWorktreeDiff),
+            "next_diff_parent" | "shift_p" => Ok(Command::NextDiffParent),
+            "toggle_blame" | "b" => Ok(Command::ToggleBlame),
+            "jump_to_blame_commit" | "shift_b" => Ok(Command::JumpToBlameCommit),
+            "toggle_selection" | "v" => Ok(Command::ToggleSelection),
+            "line_range_history" | "shift_l" => Ok(Command::LineRangeHistory),
 
             _ => {
                 if s.starts_with("search:") {
@@ -152,6 +172,16 @@ impl Command {
             Command::PreviousChange => "previous_change".to_string(),
             Command::NextChange => "next_change".to_string(),
             Command::ToggleDiff => "toggle_diff".to_string(),
+            Command::ToggleSideBySide => "toggle_side_by_side".to_string(),
+            Command::MarkDiffBase => "mark_diff_base".to_string(),
+            Command::ToggleCommitDetails => "toggle_commit_details".to_string(),
+            Command::CycleHistoryMode => "cycle_history_mode".to_string(),
+            Command::CycleWorktreeDiff => "diff_worktree".to_string(),
+            Command::NextDiffParent => "next_diff_parent".to_string(),
+            Command::ToggleBlame => "toggle_blame".to_string(),
+            Command::JumpToBlameCommit => "jump_to_blame_commit".to_string(),
+            Command::ToggleSelection => "toggle_selection".to_string(),
+            Command::LineRangeHistory => "line_range_history".to_string(),
 
             Command::Sequence(commands) => {
                 format!(
@@ -179,6 +209,25 @@ mod tests {
         );
         assert_eq!(Command::from_string("tab").unwrap(), Command::NextPanel);
         assert_eq!(Command::from_string("up").unwrap(), Command::NavigateUp);
+        assert_eq!(Command::from_string("b").unwrap(), Command::ToggleBlame);
+        assert_eq!(Command::from_string("s").unwrap(), Command::ToggleSideBySide);
+        assert_eq!(Command::from_string("m").unwrap(), Command::MarkDiffBase);
+        assert_eq!(Command::from_string("i").unwrap(), Command::ToggleCommitDetails);
+        assert_eq!(Command::from_string("f").unwrap(), Command::CycleHistoryMode);
+        assert_eq!(Command::from_string("w").unwrap(), Command::CycleWorktreeDiff);
+        assert_eq!(
+            Command::from_string("shift_p").unwrap(),
+            Command::NextDiffParent
+        );
+        assert_eq!(
+            Command::from_string("shift_b").unwrap(),
+            Command::JumpToBlameCommit
+        );
+        assert_eq!(Command::from_string("v").unwrap(), Command::ToggleSelection);
+        assert_eq!(
+            Command::from_string("shift_l").unwrap(),
+            Command::LineRangeHistory
+        );
         assert_eq!(
             Command::from_string("search:a").unwrap(),
             Command::SearchInput('a')
@@ -194,5 +243,11 @@ mod tests {
         assert_eq!(Command::NextPanel.to_string(), "next_panel");
         assert_eq!(Command::SearchInput('x').to_string(), "search:x");
         assert_eq!(Command::ToggleDiff.to_string(), "toggle_diff");
+        assert_eq!(Command::ToggleSideBySide.to_string(), "toggle_side_by_side");
+        assert_eq!(Command::ToggleBlame.to_string(), "toggle_blame");
+        assert_eq!(
+            Command::JumpToBlameCommit.to_string(),
+            "jump_to_blame_commit"
+        );
     }
 }
@@ -0,0 +1,289 @@
+use gix::commitgraph::{Graph, Position};
+use gix::{ObjectId, Repository};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Seeds of the two hashes that git's changed-path Bloom filters derive all their hashes from
+const BLOOM_SEEDS: (u32, u32) = (0x293a_e76f, 0x7e64_6e2c);
+
+/// The repository's commit-graph: parents, root trees and dates of the commits it covers,
+/// readable without decoding the commit objects, plus the changed-path Bloom filters written
+/// by `git commit-graph write --changed-paths`, which gix does not read itself.
+pub struct CommitGraph {
+    graph: Graph,
+    /// Bloom filters of each commit-graph file in chain order, with the position of its first commit
+    bloom_filters: Vec<(u32, Option<BloomFilters>)>,
+}
+
+/// What a history walk needs to know about a commit
+#[derive(Debug, Clone)]
+pub struct CommitData {
+    pub tree_id: ObjectId,
+    pub parent_ids: Vec<ObjectId>,
+    /// Committer timestamp (seconds since the Unix epoch)
+    pub time: i64,
+    /// Position of the commit in the commit-graph, if it is covered by it
+    pub graph_position: Option<Position>,
+}
+
+impl CommitGraph {
+    /// Open the repository's commit-graph; None if there is none or `core.commitGraph` is off
+    pub fn open(repo: &Repository) -> Option<Self> {
+        let graph = match repo.commit_graph_if_enabled() {
+            Ok(graph) => graph?,
+            Err(e) => {
+                log::warn!("Ignoring unreadable commit-graph: {}", e);
+                return None;
+            }
+        };
+
+        let info_dir = repo.objects.store_ref().path().join("info");
+        let mut bloom_filters = Vec::new();
+        let mut first_position = 0;
+        for path in graph_file_paths(&info_dir) {
+            let Some((num_commits, filters)) = std::fs::read(&path).ok().and_then(parse_graph_file) else {
+                log::warn!("Not using Bloom filters, cannot read '{}'", path.display());
+                bloom_filters.clear();
+                break;
+            };
+            bloom_filters.push((first_position, filters));
+            first_position += num_commits;
+        }
+        if first_position != graph.num_commits() {
+            // The files changed between reading them twice
+            bloom_filters.clear();
+        }
+
+        log::debug!(
+            "Opened commit-graph with {} commits, Bloom filters: {}",
+            graph.num_commits(),
+            bloom_filters.iter().any(|(_, filters)| filters.is_some())
+        );
+        Some(Self { graph, bloom_filters })
+    }
+
+    /// Look up a commit; None if it is newer than the commit-graph
+    pub fn commit(&self, id: &gix::oid) -> Option<CommitData> {
+        let position = self.graph.lookup(id)?;
+        let commit = self.graph.commit_at(position);
+        let parent_ids = commit
+            .iter_parents()
+            .map(|parent| parent.map(|position| self.graph.id_at(position).to_owned()))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        Some(CommitData {
+            tree_id: commit.root_tree_id().to_owned(),
+            parent_ids,
+            time: commit.committer_timestamp() as i64,
+            graph_position: Some(position),
+        })
+    }
+
+    /// Generation number of a commit: one more than the highest of its parents, so an ancestor's
+    /// is always lower. None if the commit is newer than the commit-graph; 0 if the commit-graph
+    /// was written without generation numbers.
+    pub fn generation(&self, id: &gix::oid) -> Option<u32> {
+        let position = self.graph.lookup(id)?;
+        Some(self.graph.commit_at(position).generation())
+    }
+
+    /// Whether the commit at `position` may have changed `path` compared to its first parent.
+    /// Some(false) is certain; None means the commit has no usable Bloom filter.
+    pub fn maybe_changed(&self, position: Position, path: &str) -> Option<bool> {
+        let (first_position, filters) = self
+            .bloom_filters
+            .iter()
+            .rev()
+            .find(|(first_position, _)| *first_position <= position.0)?;
+        let filters = filters.as_ref()?;
+        let filter = filters.filter(position.0 - first_position)?;
+        if filter.is_empty() {
+            return None;
+        }
+
+        // Git adds the leading directories of every changed path as well, so all must be present
+        let mut key = path;
+        loop {
+            if !filters.contains(filter, key) {
+                return Some(false);
+            }
+            match key.rfind('/') {
+                Some(index) => key = &key[..index],
+                None => return Some(true),
+            }
+        }
+    }
+}
+
+/// Look up a commit in the commit-graph, falling back to decoding the commit object
+pub fn commit_data(
+    repo: &Repository,
+    graph: Option<&CommitGraph>,
+    id: ObjectId,
+) -> Result<CommitData, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(data) = graph.and_then(|graph| graph.commit(&id)) {
+        return Ok(data);
+    }
+    let commit = repo.find_object(id)?.try_into_commit()?;
+    Ok(CommitData {
+        tree_id: commit.tree_id()?.detach(),
+        parent_ids: commit.parent_ids().map(|id| id.detach()).collect(),
+        time: commit.time()?.seconds,
+        graph_position: None,
+    })
+}
+
+/// The commit-graph files in the order gix reads them: the single file if there is one,
+/// otherwise the files of the split commit-graph chain, base first
+fn graph_file_paths(info_dir: &Path) -> Vec<PathBuf> {
+    let single = info_dir.join("commit-graph");
+    if single.is_file() {
+        return vec![single];
+    }
+    let graphs_dir = info_dir.join("commit-graphs");
+    std::fs::read_to_string(graphs_dir.join("commit-graph-chain"))
+        .map(|chain| {
+            chain
+                .lines()
+                .map(|hash| graphs_dir.join(format!("graph-{}.graph", hash.trim())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The changed-path Bloom filters of one commit-graph file
+struct BloomFilters {
+    data: Vec<u8>,
+    /// The BIDX chunk: for each commit, the end offset of its filter in `filters`
+    index: Range<usize>,
+    /// The filters in the BDAT chunk, after its header
+    filters: Range<usize>,
+    version: u32,
+    num_hashes: u32,
+}
+
+impl BloomFilters {
+    /// The filter of the commit at `position` within the file
+    fn filter(&self, position: u32) -> Option<&[u8]> {
+        let entry = self.index.start + position as usize * 4;
+        if entry + 4 > self.index.end {
+            return None;
+        }
+        let end = read_u32(&self.data, entry)? as usize;
+        let start = match position {
+            0 => 0,
+            _ => read_u32(&self.data, entry - 4)? as usize,
+        };
+        let range = self.filters.start + start..self.filters.start + end;
+        (start <= end && range.end <= self.filters.end).then(|| &self.data[range])
+    }
+
+    fn contains(&self, filter: &[u8], key: &str) -> bool {
+        // Version 1 filters were written with git's murmur3 treating bytes as signed
+        let signed_bytes = self.version == 1;
+        let hash0 = murmur3(BLOOM_SEEDS.0, key.as_bytes(), signed_bytes);
+        let hash1 = murmur3(BLOOM_SEEDS.1, key.as_bytes(), signed_bytes);
+        let bits = filter.len() as u64 * 8;
+        (0..self.num_hashes).all(|i| {
+            let bit = u64::from(hash0.wrapping_add(i.wrapping_mul(hash1))) % bits;
+            filter[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+        })
+    }
+}
+
+/// Read the number of commits and, if present, the Bloom filters of a commit-graph file
+fn parse_graph_file(data: Vec<u8>) -> Option<(u32, Option<BloomFilters>)> {
+    if data.get(..4)? != b"CGPH" || *data.get(4)? != 1 {
+        return None;
+    }
+    let num_chunks = *data.get(6)? as usize;
+
+    let mut chunks = Vec::with_capacity(num_chunks);
+    for i in 0..num_chunks {
+        let entry = 8 + i * 12;
+        let id: [u8; 4] = data.get(entry..entry + 4)?.try_into().ok()?;
+        let start = read_u64(&data, entry + 4)? as usize;
+        let end = read_u64(&data, entry + 16)? as usize;
+        if start > end || end > data.len() {
+            return None;
+        }
+        chunks.push((id, start..end));
+    }
+    let chunk = |id: &[u8; 4]| chunks.iter().find(|(chunk_id, _)| chunk_id == id).map(|(_, range)| range.clone());
+
+    // The last entry of the fan-out table counts all commits in the file
+    let num_commits = read_u32(&data, chunk(b"OIDF")?.start + 255 * 4)?;
+
+    let filters = match (chunk(b"BIDX"), chunk(b"BDAT")) {
+        (Some(index), Some(bdat)) if bdat.len() >= 12 => {
+            let version = read_u32(&data, bdat.start)?;
+            let num_hashes = read_u32(&data, bdat.start + 4)?;
+            (matches!(version, 1 | 2) && index.len() >= num_commits as usize * 4).then(|| BloomFilters {
+                index,
+                filters: bdat.start + 12..bdat.end,
+                version,
+                num_hashes,
+                data,
+            })
+        }
+        _ => None,
+    };
+    Some((num_commits, filters))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(data.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+/// The 32-bit murmur3 hash, as git computes it for Bloom filter keys
+fn murmur3(seed: u32, data: &[u8], signed_bytes: bool) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    let byte = |b: u8| if signed_bytes { b as i8 as u32 } else { u32::from(b) };
+
+    let mut hash = seed;
+    let blocks = data.chunks_exact(4);
+    let tail = blocks.remainder();
+    for block in blocks {
+        let k = byte(block[0]) | byte(block[1]) << 8 | byte(block[2]) << 16 | byte(block[3]) << 24;
+        hash ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+        hash = hash.rotate_left(13).wrapping_mul(5).wrapping_add(0xe654_6b64);
+    }
+    if !tail.is_empty() {
+        let k = tail
+            .iter()
+            .enumerate()
+            .fold(0, |k, (i, b)| k ^ byte(*b) << (8 * i));
+        hash ^= k.wrapping_mul(C1).rotate_left(15).wrapping_mul(C2);
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^ (hash >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_murmur3_matches_git() {
+        // Values from git's t0095-bloom.sh
+        assert_eq!(murmur3(0, b"", false), 0x0000_0000);
+        assert_eq!(murmur3(0, b"Hello world!", false), 0x627b_0c2c);
+        assert_eq!(
+            murmur3(0, b"The quick brown fox jumps over the lazy dog", false),
+            0x2e4f_f723
+        );
+        // The version 1 hash only differs for bytes with the high bit set
+        assert_eq!(murmur3(0, b"Hello world!", true), 0x627b_0c2c);
+        assert_ne!(murmur3(0, "\u{e9}".as_bytes(), true), murmur3(0, "\u{e9}".as_bytes(), false));
+    }
+}
@@ -0,0 +1,317 @@
+use crate::app::{DiffLine, DiffLineType};
+use similar::{ChangeTag, TextDiff};
+use std::ops::Range;
+
+/// Paired lines that share less than this fraction of their words are shown as plain
+/// removed/added lines; highlighting nearly everything in them would only add noise
+const MIN_SIMILARITY_FOR_WORD_DIFF: f32 = 0.3;
+
+/// Compute the line diff between a file in the parent commit and in the selected commit.
+///
+/// Removed and added lines within a hunk are paired up in order, and the words that differ
+/// between each pair are recorded in `changed_ranges` so the view can emphasize them.
+pub fn compute_diff_lines(parent_content: &[String], current_content: &[String]) -> Vec<DiffLine> {
+    // Handle special case where file is new (no parent content)
+    if parent_content.is_empty() && !current_content.is_empty() {
+        return current_content
+            .iter()
+            .enumerate()
+            .map(|(idx, line)| DiffLine {
+                line_type: DiffLineType::Added,
+                old_line_num: None,
+                new_line_num: Some(idx + 1),
+                content: line.clone() + "\n",
+                changed_ranges: Vec::new(),
+            })
+            .collect();
+    }
+
+    // Terminate every line so the last lines compare equal when only earlier lines changed
+    let parent_text: String = parent_content.iter().map(|line| format!("{}\n", line)).collect();
+    let current_text: String = current_content.iter().map(|line| format!("{}\n", line)).collect();
+    let diff = TextDiff::from_lines(&parent_text, &current_text);
+
+    let mut diff_lines = Vec::new();
+    let mut old_line_num = 1;
+    let mut new_line_num = 1;
+
+    for change in diff.iter_all_changes() {
+        let content = change.value().to_string();
+
+        match change.tag() {
+            ChangeTag::Delete => {
+                diff_lines.push(DiffLine {
+                    line_type: DiffLineType::Removed,
+                    old_line_num: Some(old_line_num),
+                    new_line_num: None,
+                    content,
+                    changed_ranges: Vec::new(),
+                });
+                old_line_num += 1;
+            }
+            ChangeTag::Insert => {
+                diff_lines.push(DiffLine {
+                    line_type: DiffLineType::Added,
+                    old_line_num: None,
+                    new_line_num: Some(new_line_num),
+                    content,
+                    changed_ranges: Vec::new(),
+                });
+                new_line_num += 1;
+            }
+            ChangeTag::Equal => {
+                diff_lines.push(DiffLine {
+                    line_type: DiffLineType::Unchanged,
+                    old_line_num: Some(old_line_num),
+                    new_line_num: Some(new_line_num),
+                    content,
+                    changed_ranges: Vec::new(),
+                });
+                old_line_num += 1;
+                new_line_num += 1;
+            }
+        }
+    }
+
+    mark_changed_words(&mut diff_lines);
+    diff_lines
+}
+
+/// Pair the removed and added lines of each hunk and record which words changed in each pair
+fn mark_changed_words(diff_lines: &mut [DiffLine]) {
+    let mut hunk_start = 0;
+    while hunk_start < diff_lines.len() {
+        if matches!(diff_lines[hunk_start].line_type, DiffLineType::Unchanged) {
+            hunk_start += 1;
+            continue;
+        }
+
+        let hunk_end = diff_lines[hunk_start..]
+            .iter()
+            .position(|line| matches!(line.line_type, DiffLineType::Unchanged))
+            .map_or(diff_lines.len(), |offset| hunk_start + offset);
+
+        let hunk = &diff_lines[hunk_start..hunk_end];
+        let removed: Vec<usize> = (hunk_start..hunk_end)
+            .filter(|&idx| matches!(hunk[idx - hunk_start].line_type, DiffLineType::Removed))
+            .collect();
+        let added: Vec<usize> = (hunk_start..hunk_end)
+            .filter(|&idx| matches!(hunk[idx - hunk_start].line_type, DiffLineType::Added))
+            .collect();
+
+        for (&old_idx, &new_idx) in removed.iter().zip(&added) {
+            if let Some((old_ranges, new_ranges)) = changed_word_ranges(
+                diff_lines[old_idx].content.trim_end_matches('\n'),
+                diff_lines[new_idx].content.trim_end_matches('\n'),
+            ) {
+                diff_lines[old_idx].changed_ranges = old_ranges;
+                diff_lines[new_idx].changed_ranges = new_ranges;
+            }
+        }
+
+        hunk_start = hunk_end;
+    }
+}
+
+/// Changed byte ranges in the old and in the new version of a line
+pub type ChangedRanges = (Vec<Range<usize>>, Vec<Range<usize>>);
+
+/// Byte ranges of the words that differ between an old and a new version of a line, or None
+/// if the lines are too different for a word diff to be useful
+pub fn changed_word_ranges(old: &str, new: &str) -> Option<ChangedRanges> {
+    let old_words = split_words(old);
+    let new_words = split_words(new);
+    let diff = TextDiff::from_slices(&old_words, &new_words);
+    if diff.ratio() < MIN_SIMILARITY_FOR_WORD_DIFF {
+        return None;
+    }
+
+    let mut old_ranges = Vec::new();
+    let mut new_ranges = Vec::new();
+    let mut old_pos = 0;
+    let mut new_pos = 0;
+
+    for change in diff.iter_all_changes() {
+        let len = change.value().len();
+
+        match change.tag() {
+            ChangeTag::Delete => {
+                push_range(&mut old_ranges, old_pos..old_pos + len);
+                old_pos += len;
+            }
+            ChangeTag::Insert => {
+                push_range(&mut new_ranges, new_pos..new_pos + len);
+                new_pos += len;
+            }
+            ChangeTag::Equal => {
+                old_pos += len;
+                new_pos += len;
+            }
+        }
+    }
+
+    Some((trim_ranges(old, old_ranges), trim_ranges(new, new_ranges)))
+}
+
+/// Strip surrounding whitespace from the ranges; whitespace-only changes would highlight
+/// invisible text, so they are dropped
+fn trim_ranges(line: &str, ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges
+        .into_iter()
+        .filter_map(|range| {
+            let text = &line[range.clone()];
+            let start = range.start + (text.len() - text.trim_start().len());
+            let end = range.start + text.trim_end().len();
+            (start < end).then_some(start..end)
+        })
+        .collect()
+}
+
+/// Split a line into identifiers, runs of whitespace and single punctuation characters, so
+/// that e.g. changing `1;` to `10;` marks only the number
+fn split_words(line: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        let same_word = |next: char| {
+            (is_word_char(c) && is_word_char(next)) || (c.is_whitespace() && next.is_whitespace())
+        };
+        match chars.peek() {
+            Some(&(_, next)) if same_word(next) => {}
+            Some(&(next_idx, _)) => {
+                words.push(&line[start..next_idx]);
+                start = next_idx;
+            }
+            None => words.push(&line[start..idx + c.len_utf8()]),
+        }
+    }
+
+    words
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Add a range, merging it into the previous one if they touch
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &[&str]) -> Vec<String> {
+        text.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn changed_text(diff_line: &DiffLine) -> Vec<&str> {
+        diff_line
+            .changed_ranges
+            .iter()
+            .map(|range| &diff_line.content[range.clone()])
+            .collect()
+    }
+
+    #[test]
+    fn test_changed_word_ranges_marks_only_changed_words() {
+        let (old_ranges, new_ranges) =
+            changed_word_ranges("let total = price * count;", "let total = cost * count;").unwrap();
+
+        assert_eq!(old_ranges, vec![12..17]);
+        assert_eq!(new_ranges, vec![12..16]);
+    }
+
+    #[test]
+    fn test_changed_word_ranges_merges_adjacent_words() {
+        let (old_ranges, new_ranges) =
+            changed_word_ranges("call(a, b, c)", "call(x y, b, c)").unwrap();
+
+        assert_eq!(old_ranges.len(), 1);
+        assert_eq!(&"call(a, b, c)"[old_ranges[0].clone()], "a");
+        assert_eq!(&"call(x y, b, c)"[new_ranges[0].clone()], "x y");
+        assert_eq!(new_ranges.len(), 1);
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words("  let x_1 = f(a, \"é\");"),
+            vec!["  ", "let", " ", "x_1", " ", "=", " ", "f", "(", "a", ",", " ", "\"", "é", "\"", ")", ";"]
+        );
+        assert!(split_words("").is_empty());
+    }
+
+    #[test]
+    fn test_changed_word_ranges_ignores_whitespace_changes() {
+        let (old_ranges, new_ranges) = changed_word_ranges("foo(a,b);", "    foo(a, b);").unwrap();
+
+        assert!(old_ranges.is_empty());
+        assert!(new_ranges.is_empty());
+    }
+
+    #[test]
+    fn test_changed_word_ranges_skips_unrelated_lines() {
+        assert!(changed_word_ranges("fn main() {", "// completely different words here").is_none());
+    }
+
+    #[test]
+    fn test_compute_diff_lines_pairs_hunk_lines() {
+        let parent = lines(&["fn main() {", "    let x = 1;", "    let y = 2;", "}"]);
+        let current = lines(&["fn main() {", "    let x = 10;", "    let y = 2;", "    done();", "}"]);
+        let diff = compute_diff_lines(&parent, &current);
+
+        let types: Vec<&str> = diff
+            .iter()
+            .map(|line| match line.line_type {
+                DiffLineType::Added => "+",
+                DiffLineType::Removed => "-",
+                DiffLineType::Unchanged => " ",
+            })
+            .collect();
+        assert_eq!(types, vec![" ", "-", "+", " ", "+", " "]);
+
+        assert_eq!(changed_text(&diff[1]), vec!["1"]);
+        assert_eq!(changed_text(&diff[2]), vec!["10"]);
+        // An added line without a removed partner has nothing to compare against
+        assert!(diff[4].changed_ranges.is_empty());
+        assert!(diff[0].changed_ranges.is_empty());
+    }
+
+    #[test]
+    fn test_compute_diff_lines_for_new_file() {
+        let diff = compute_diff_lines(&[], &lines(&["a", "b"]));
+
+        assert_eq!(diff.len(), 2);
+        assert!(diff.iter().all(|line| matches!(line.line_type, DiffLineType::Added)));
+        assert_eq!(diff[1].new_line_num, Some(2));
+        assert_eq!(diff[1].content, "b\n");
+    }
+
+    #[test]
+    fn test_compute_diff_lines_line_numbers() {
+        let parent = lines(&["a", "b", "c"]);
+        let current = lines(&["a", "c", "d"]);
+        let diff = compute_diff_lines(&parent, &current);
+
+        let numbers: Vec<(Option<usize>, Option<usize>)> = diff
+            .iter()
+            .map(|line| (line.old_line_num, line.new_line_num))
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![
+                (Some(1), Some(1)),
+                (Some(2), None),
+                (Some(3), Some(2)),
+                (None, Some(3)),
+            ]
+        );
+    }
+}
@@ -1,6 +1,9 @@
-use crate::app::{App, PanelFocus};
+use crate::app::{App, DiffBase, PanelFocus};
 use crate::async_task::Task;
-use crate::event::EventResult;
+use crate::event::{
+    file_loader, jump_to_blame_commit, request_blame_if_needed, request_diff, set_diff_base,
+    EventResult,
+};
 use crossterm::event::{KeyCode, KeyEvent};
 use tokio::sync::mpsc;
 
@@ -21,7 +24,7 @@ pub fn handle_code_inspector_event(
             }
         }
         KeyCode::Down => {
-            if app.inspector.cursor_line < app.inspector.current_content.len().saturating_sub(1) {
+            if app.inspector.cursor_line < app.inspector.line_count().saturating_sub(1) {
                 app.inspector.cursor_line += 1;
                 app.ensure_inspector_cursor_visible();
             }
@@ -34,7 +37,7 @@ pub fn handle_code_inspector_event(
         KeyCode::PageDown => {
             let page_size = app.inspector.visible_height.saturating_sub(2);
             app.inspector.cursor_line = (app.inspector.cursor_line + page_size)
-                .min(app.inspector.current_content.len().saturating_sub(1));
+                .min(app.inspector.line_count().saturating_sub(1));
             app.ensure_inspector_cursor_visible();
         }
         KeyCode::Home => {
@@ -42,7 +45,7 @@ pub fn handle_code_inspector_event(
             app.ensure_inspector_cursor_visible();
         }
         KeyCode::End => {
-            app.inspector.cursor_line = app.inspector.current_content.len().saturating_sub(1);
+            app.inspector.cursor_line = app.inspector.line_count().saturating_sub(1);
             app.ensure_inspector_cursor_visible();
         }
         KeyCode::Left => {
@@ -51,62 +54,105 @@ pub fn handle_code_inspector_event(
         KeyCode::Right => {
             app.inspector.scroll_horizontal += 1;
         }
+        KeyCode::Enter if app.get_history_directory().is_some() => {
+            file_loader::open_directory_change(app, task_sender)?;
+        }
         KeyCode::Char('g') => {
             app.inspector.cursor_line = 0;
             app.ensure_inspector_cursor_visible();
         }
         KeyCode::Char('G') => {
-            app.inspector.cursor_line = app.inspector.current_content.len().saturating_sub(1);
+            app.inspector.cursor_line = app.inspector.line_count().saturating_sub(1);
             app.ensure_inspector_cursor_visible();
         }
         KeyCode::Char('d') => {
-            app.inspector.show_diff_view = !app.inspector.show_diff_view;
-            
             if app.inspector.show_diff_view {
-                // Check if we need to generate diff
-                if app.inspector.diff_lines.is_none() {
-                    // Get current commit and file
-                    if let (Some(current_commit), Some(file_path)) = (
-                        &app.history.selected_commit_hash,
-                        app.get_active_file()
-                    ) {
-                        // Get parent commit
-                        match crate::git_utils::get_parent_commit(&app.repo, current_commit) {
-                            Ok(Some(parent_commit)) => {
-                                app.inspector.parent_commit_hash = Some(parent_commit.clone());
-                                app.ui.status_message = format!(
-                                    "Loading diff view for {} at {}...",
-                                    file_path.display(),
-                                    &current_commit[..8]
-                                );
-                                app.ui.is_loading = true;
-                                app.active_background_tasks += 1;
-                                
-                                // Send diff generation task
-                                let _ = task_sender.try_send(Task::GenerateDiff {
-                                    file_path: file_path.to_string_lossy().to_string(),
-                                    current_commit: current_commit.clone(),
-                                    parent_commit,
-                                });
-                            }
-                            Ok(None) => {
-                                app.ui.status_message = "No parent commit - this is the initial commit".to_string();
-                                app.inspector.show_diff_view = false; // Revert toggle
-                            }
-                            Err(e) => {
-                                app.ui.status_message = format!("Failed to get parent commit: {}", e);
-                                app.inspector.show_diff_view = false; // Revert toggle
-                            }
-                        }
-                    } else {
-                        app.ui.status_message = "No file or commit selected for diff view".to_string();
-                        app.inspector.show_diff_view = false; // Revert toggle
-                    }
+                app.inspector.set_show_diff_view(false);
+                app.ensure_inspector_cursor_visible();
+                app.ui.status_message = "Switched to full file view".to_string();
+            } else {
+                open_diff_view(app, task_sender);
+            }
+        }
+        KeyCode::Char('s') => {
+            if app.inspector.show_diff_view {
+                app.inspector.toggle_diff_side_by_side();
+                app.ensure_inspector_cursor_visible();
+                app.ui.status_message = if app.inspector.diff_side_by_side {
+                    "Switched to side-by-side diff".to_string()
                 } else {
-                    app.ui.status_message = "Switched to diff view".to_string();
+                    "Switched to inline diff".to_string()
+                };
+            } else {
+                app.inspector.diff_side_by_side = true;
+                open_diff_view(app, task_sender);
+            }
+        }
+        KeyCode::Char('w') => {
+            // Cycle the diff base: working tree -> index -> parent
+            let base = match app.inspector.diff_base {
+                DiffBase::WorkingTree => DiffBase::Index,
+                DiffBase::Index => DiffBase::default(),
+                _ => DiffBase::WorkingTree,
+            };
+            set_diff_base(app, base, task_sender);
+            if !app.inspector.show_diff_view {
+                open_diff_view(app, task_sender);
+            }
+        }
+        KeyCode::Char('P') => {
+            let parent_count = app
+                .history
+                .selected_commit_hash
+                .as_ref()
+                .and_then(|hash| crate::git_utils::get_parent_commits(&app.repo, hash).ok())
+                .map_or(0, |parents| parents.len());
+
+            if parent_count < 2 {
+                app.ui.status_message = "Not a merge commit - there is only one parent to diff against".to_string();
+            } else {
+                let next_parent = match app.inspector.diff_base {
+                    DiffBase::Parent(n) => (n + 1) % parent_count,
+                    _ => 0,
+                };
+                set_diff_base(app, DiffBase::Parent(next_parent), task_sender);
+                if !app.inspector.show_diff_view {
+                    open_diff_view(app, task_sender);
                 }
+            }
+        }
+        KeyCode::Char('b') => {
+            app.inspector.show_blame = !app.inspector.show_blame;
+
+            if app.inspector.show_blame {
+                request_blame_if_needed(app, task_sender);
+                app.ui.status_message = if app.inspector.current_blame.is_some() {
+                    "Blame column shown".to_string()
+                } else {
+                    "Loading blame...".to_string()
+                };
             } else {
-                app.ui.status_message = "Switched to full file view".to_string();
+                app.ui.status_message = "Blame column hidden".to_string();
+            }
+        }
+        KeyCode::Char('B') => {
+            jump_to_blame_commit(app, task_sender);
+        }
+        KeyCode::Char('v') => {
+            if app.inspector.selection_anchor.take().is_some() {
+                app.ui.status_message = "Line selection cleared".to_string();
+            } else {
+                app.inspector.selection_anchor = Some(app.inspector.cursor_line);
+                app.ui.status_message =
+                    "Selecting lines - move the cursor, then press L for their history".to_string();
+            }
+        }
+        KeyCode::Char('L') => {
+            if app.history.line_range.is_some() && app.inspector.selection_anchor.is_none() {
+                // Leave line range mode and show the whole file's history again
+                file_loader::load_commit_history_for_selected_file(app, task_sender)?;
+            } else {
+                file_loader::load_line_range_history(app, task_sender)?;
             }
         }
         _ => return Ok(false),
@@ -114,3 +160,19 @@ pub fn handle_code_inspector_event(
 
     Ok(true)
 }
+
+/// Show the diff view, requesting the diff against the parent commit if it isn't loaded yet
+fn open_diff_view(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    app.inspector.set_show_diff_view(true);
+
+    if app.inspector.diff_lines.is_some() {
+        app.ensure_inspector_cursor_visible();
+        app.ui.status_message = "Switched to diff view".to_string();
+        return;
+    }
+
+    if let Err(message) = request_diff(app, task_sender) {
+        app.ui.status_message = message;
+        app.inspector.set_show_diff_view(false); // Revert toggle
+    }
+}
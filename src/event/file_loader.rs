@@ -1,15 +1,17 @@
-use crate::app::App;
+use crate::app::{App, LineRange, PendingJump};
 use crate::async_task::Task;
 use crate::event::EventResult;
+use std::path::PathBuf;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
-/// Load commit history for the currently selected file
+/// Load commit history for the currently selected file or directory
 pub fn load_commit_history_for_selected_file(
     app: &mut App,
     task_sender: &mpsc::Sender<Task>,
 ) -> EventResult {
-    if let Some(path) = app.get_active_file() {
+    // A directory's history covers all files below it
+    if let Some(path) = app.get_history_path() {
         let file_path = path.to_string_lossy().to_string();
 
         // Reset history state for the new file
@@ -17,8 +19,11 @@ pub fn load_commit_history_for_selected_file(
 
         // Clear inspector content immediately to prevent showing stale content
         app.inspector.current_content.clear();
-        app.inspector.diff_lines = None;
-        app.inspector.parent_commit_hash = None;
+        app.inspector.directory_changes.clear();
+        app.inspector.set_diff_lines(None);
+        app.inspector.base_commit_hash = None;
+        app.inspector.clear_blame();
+        app.inspector.selection_anchor = None;
         app.inspector.cursor_line = 0;
         app.inspector.scroll_vertical = 0;
         app.inspector.scroll_horizontal = 0;
@@ -30,6 +35,8 @@ pub fn load_commit_history_for_selected_file(
         // Start the streaming task
         let task = Task::LoadCommitHistoryStreaming {
             file_path: file_path.clone(),
+            revision: app.revision.clone(),
+            mode: app.history.mode,
             cancellation_token,
         };
 
@@ -44,11 +51,12 @@ pub fn load_commit_history_for_selected_file(
         app.ui.is_loading = true;
         app.ui.status_message = format!("Loading history for {}...", file_path);
     } else {
-        // Directory is selected or no selection
-        app.history.commit_list.clear();
+        // The deleted files folder is selected or no selection
+        app.history.clear_commits();
         app.history.selected_commit_index = None;
         app.history.selected_commit_hash = None;
         app.inspector.current_content.clear();
+        app.inspector.directory_changes.clear();
         app.ui.status_message = if app.navigator.get_selection().is_some() {
             "Directory selected - select a file to view history".to_string()
         } else {
@@ -59,22 +67,52 @@ pub fn load_commit_history_for_selected_file(
     Ok(true)
 }
 
+/// Open the file under the cursor in the files a commit changed below the selected directory,
+/// selecting the same commit in the file's history once it has been streamed in
+pub fn open_directory_change(
+    app: &mut App,
+    task_sender: &mpsc::Sender<Task>,
+) -> EventResult {
+    let (Some(file), Some(commit_hash)) = (
+        app.inspector.directory_changes.get(app.inspector.cursor_line).cloned(),
+        app.history.selected_commit_hash.clone(),
+    ) else {
+        return Ok(false);
+    };
+
+    if !app.navigator.reveal(PathBuf::from(&file.path)) {
+        app.ui.status_message = format!("{} is not in the file tree", file.path);
+        return Ok(true);
+    }
+    load_commit_history_for_selected_file(app, task_sender)?;
+    app.history.pending_jump = Some(PendingJump {
+        commit_hash: commit_hash.clone(),
+        line_number: None,
+    });
+    app.ui.status_message = format!("Opening {} at {}...", file.path, &commit_hash[..8]);
+
+    Ok(true)
+}
+
 /// Load more commit history for the currently selected file
 pub fn load_more_commit_history(
     app: &mut App,
     task_sender: &mpsc::Sender<Task>,
 ) -> EventResult {
-    if app.history.is_loading_more || app.history.history_complete {
+    // A line range history is streamed in full and has no chunks to load
+    if app.history.is_loading_more || app.history.history_complete || app.history.line_range.is_some() {
         return Ok(false);
     }
 
-    if let Some(path) = app.get_active_file() {
+    if let Some(path) = app.get_history_path() {
         let file_path = path.to_string_lossy().to_string();
         let chunk_size = 50; // Load 50 commits at a time
         let start_offset = app.history.next_chunk_offset;
 
         let task = Task::LoadCommitHistoryProgressive {
             file_path,
+            revision: app.revision.clone(),
+            mode: app.history.mode,
             chunk_size,
             start_offset,
         };
@@ -93,3 +131,65 @@ pub fn load_more_commit_history(
 
     Ok(true)
 }
+
+/// Filter the history to the commits that touched the selected lines (or the cursor line)
+pub fn load_line_range_history(
+    app: &mut App,
+    task_sender: &mpsc::Sender<Task>,
+) -> EventResult {
+    let (Some(active_file), Some(commit_hash)) =
+        (app.get_active_file(), app.history.selected_commit_hash.clone())
+    else {
+        app.ui.status_message = "No file or commit selected for line history".to_string();
+        return Ok(false);
+    };
+    if app.inspector.current_content.is_empty() {
+        return Ok(false);
+    }
+
+    let (start_line, end_line) = app
+        .inspector
+        .selected_range()
+        .unwrap_or((app.inspector.cursor_line, app.inspector.cursor_line));
+    let file_path = app
+        .get_file_path_at_commit(&commit_hash)
+        .unwrap_or_else(|| active_file.clone());
+    let line_range = LineRange {
+        commit_hash,
+        file_path: file_path.to_string_lossy().to_string(),
+        start_line,
+        end_line,
+    };
+
+    // Replace the whole-file history; the inspector keeps showing the current version
+    // until the first matching commit arrives
+    app.history.reset_for_new_file();
+    app.history.line_range = Some(line_range.clone());
+    app.inspector.selection_anchor = None;
+
+    let cancellation_token = CancellationToken::new();
+    app.history.streaming_cancellation_token = Some(cancellation_token.clone());
+
+    let task = Task::LoadLineRangeHistoryStreaming {
+        file_path: active_file.to_string_lossy().to_string(),
+        line_range,
+        cancellation_token,
+    };
+
+    let sender = task_sender.clone();
+    tokio::spawn(async move {
+        if let Err(e) = sender.send(task).await {
+            log::error!("Failed to send LoadLineRangeHistoryStreaming task: {}", e);
+        }
+    });
+
+    app.start_background_task();
+    app.ui.is_loading = true;
+    app.ui.status_message = format!(
+        "Loading history for lines {}-{}...",
+        start_line + 1,
+        end_line + 1
+    );
+
+    Ok(true)
+}
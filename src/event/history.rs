@@ -1,5 +1,9 @@
-use crate::app::{App, PanelFocus};
-use crate::event::{file_loader, update_code_inspector_for_commit, EventResult};
+use crate::app::{App, DiffBase, PanelFocus};
+use crate::event::revision::switch_revision;
+use crate::event::{
+    file_loader, request_changed_files_if_needed, request_containing_refs_if_needed, set_diff_base,
+    update_code_inspector_for_commit, EventResult,
+};
 use crossterm::event::{KeyCode, KeyEvent};
 use tokio::sync::mpsc;
 
@@ -42,6 +46,55 @@ pub fn handle_history_event(
             // Switch focus to inspector
             app.ui.active_panel = PanelFocus::Inspector;
         }
+        KeyCode::Char('m') => {
+            if let Some(commit_hash) = app.history.selected_commit_hash.clone() {
+                let base = DiffBase::Commit(commit_hash.clone());
+                if app.inspector.diff_base == base {
+                    set_diff_base(app, DiffBase::default(), task_sender);
+                    app.ui.status_message = "Diff base cleared - diffing against parent".to_string();
+                } else {
+                    set_diff_base(app, base, task_sender);
+                    app.ui.status_message = format!(
+                        "Marked {} as diff base - the diff view compares other commits against it",
+                        &commit_hash[..8]
+                    );
+                }
+            }
+        }
+        KeyCode::Char('i') => {
+            app.history.show_commit_details = !app.history.show_commit_details;
+            if app.history.show_commit_details {
+                request_changed_files_if_needed(app, task_sender);
+                request_containing_refs_if_needed(app, task_sender);
+            } else {
+                app.ui.status_message = "Commit details hidden".to_string();
+            }
+        }
+        KeyCode::Char('t') => {
+            if let Some(previous_revision) = app.revision_before_commit_tree.take() {
+                if let Err(message) = switch_revision(app, previous_revision, task_sender) {
+                    app.ui.status_message = message;
+                }
+            } else if let Some(commit_hash) = app.history.selected_commit_hash.clone() {
+                // Show the files as of the selected commit, including those deleted since
+                let previous_revision = app.revision.clone();
+                match switch_revision(app, Some(commit_hash.clone()), task_sender) {
+                    Ok(()) => {
+                        app.revision_before_commit_tree = Some(previous_revision);
+                        app.ui.status_message = format!(
+                            "Browsing the tree of {} | t: Back",
+                            &commit_hash[..8]
+                        );
+                    }
+                    Err(message) => app.ui.status_message = message,
+                }
+            }
+        }
+        KeyCode::Char('f') => {
+            app.history.mode = app.history.mode.next();
+            file_loader::load_commit_history_for_selected_file(app, task_sender)?;
+            app.ui.status_message = format!("History mode: {}", app.history.mode.describe());
+        }
         _ => return Ok(false),
     }
 
@@ -16,10 +16,14 @@ pub fn handle_inspector_event(
     match key.code {
         KeyCode::Char('p') => {
             // Find previous change for the current line
-            if let (Some(file_path), Some(commit_hash)) =
-                (app.get_active_file().as_ref(), &app.history.selected_commit_hash)
-            {
-                let task = Task::FindNextChange {
+            if let (Some(file_path), Some(commit_hash)) = (
+                app.history
+                    .selected_commit_hash
+                    .as_ref()
+                    .and_then(|hash| app.get_file_path_at_commit(hash)),
+                &app.history.selected_commit_hash,
+            ) {
+                let task = Task::FindPreviousChange {
                     file_path: file_path.to_string_lossy().to_string(),
                     current_commit: commit_hash.clone(),
                     line_number: app.inspector.cursor_line,
@@ -28,7 +32,7 @@ pub fn handle_inspector_event(
                 let sender = task_sender.clone();
                 tokio::spawn(async move {
                     if let Err(e) = sender.send(task).await {
-                        log::error!("Failed to send FindNextChange task: {}", e);
+                        log::error!("Failed to send FindPreviousChange task: {}", e);
                     }
                 });
 
@@ -39,11 +43,16 @@ pub fn handle_inspector_event(
         }
         KeyCode::Char('n') => {
             // Find next change for the current line
-            if let (Some(file_path), Some(commit_hash)) =
-                (app.get_active_file().as_ref(), &app.history.selected_commit_hash)
-            {
+            if let (Some(file_path), Some(commit_hash)) = (
+                app.history
+                    .selected_commit_hash
+                    .as_ref()
+                    .and_then(|hash| app.get_file_path_at_commit(hash)),
+                &app.history.selected_commit_hash,
+            ) {
                 let task = Task::FindNextChange {
                     file_path: file_path.to_string_lossy().to_string(),
+                    revision: app.revision.clone(),
                     current_commit: commit_hash.clone(),
                     line_number: app.inspector.cursor_line,
                 };
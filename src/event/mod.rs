@@ -1,7 +1,8 @@
-use crate::app::{App, PanelFocus};
+use crate::app::{App, DiffBase, FileChangeKind, PanelFocus};
 use crate::async_task::Task;
 use crossterm::event::{Event, KeyCode, KeyModifiers};
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 pub mod code_inspector;
 pub mod file_loader;
@@ -9,6 +10,7 @@ pub mod history;
 pub mod inspector;
 pub mod navigation;
 pub mod navigator;
+pub mod revision;
 
 pub type EventResult = Result<bool, Box<dyn std::error::Error>>;
 
@@ -19,6 +21,11 @@ pub fn handle_event(
     task_sender: &mpsc::Sender<Task>,
 ) -> EventResult {
     if let Event::Key(key) = event {
+        // The ref picker takes all input while it is open
+        if app.ui.ref_picker.is_some() {
+            return revision::handle_ref_picker_event(key, app, task_sender);
+        }
+
         // Global keybindings - but not in search mode
         if key.code == KeyCode::Char('q') && !app.navigator.is_searching() {
             app.should_quit = true;
@@ -79,9 +86,14 @@ pub fn update_code_inspector_for_commit_no_diff(app: &mut App) {
             let commit_hash = commit.hash.clone();
             app.history.selected_commit_hash = Some(commit_hash.clone());
             
-            // Clear diff data when switching commits
-            app.inspector.diff_lines = None;
-            app.inspector.parent_commit_hash = None;
+            // Clear diff data and line selection when switching commits
+            app.inspector.set_diff_lines(None);
+            app.inspector.base_commit_hash = None;
+            app.inspector.selection_anchor = None;
+            // The chosen parent of a merge commit only applies to that commit
+            if matches!(app.inspector.diff_base, DiffBase::Parent(_)) {
+                app.inspector.diff_base = DiffBase::default();
+            }
 
             if let Some(file_path) = app.get_active_file() {
                 // Save current cursor position before switching
@@ -89,14 +101,17 @@ pub fn update_code_inspector_for_commit_no_diff(app: &mut App) {
                     app.save_cursor_position(&last_commit, &file_path);
                 }
 
-                // Load file content at the new commit
+                // Load file content at the new commit, under the name it had back then
+                let historical_path = app
+                    .get_file_path_at_commit(&commit_hash)
+                    .unwrap_or_else(|| file_path.clone());
                 match crate::git_utils::get_file_content_at_commit(
                     &app.repo,
-                    &file_path.to_string_lossy(),
+                    &historical_path.to_string_lossy(),
                     &commit_hash,
                 ) {
                     Ok(content) => {
-                        app.inspector.current_content = content;
+                        app.inspector.set_content(content);
                         app.inspector.scroll_horizontal = 0;
 
                         // Apply smart cursor positioning
@@ -107,64 +122,423 @@ pub fn update_code_inspector_for_commit_no_diff(app: &mut App) {
                         app.ensure_inspector_cursor_visible();
                     }
                     Err(e) => {
-                        app.inspector.current_content.clear();
-                        app.ui.status_message = format!("Error loading file: {}", e);
+                        // The commit that deleted the file shows the content it removed
+                        let path = historical_path.to_string_lossy();
+                        let last_version =
+                            crate::git_utils::get_commit_before_deletion(&app.repo, &path, &commit_hash)
+                                .ok()
+                                .flatten()
+                                .and_then(|parent_hash| {
+                                    let content =
+                                        crate::git_utils::get_file_content_at_commit(&app.repo, &path, &parent_hash);
+                                    Some((parent_hash, content.ok()?))
+                                });
+                        match last_version {
+                            Some((parent_hash, content)) => {
+                                app.inspector.set_content(content);
+                                app.inspector.scroll_horizontal = 0;
+                                app.inspector.cursor_line = 0;
+                                app.inspector.scroll_vertical = 0;
+                                app.ui.status_message = format!(
+                                    "{} was deleted in {} | Showing its last content from {}",
+                                    historical_path.display(),
+                                    &commit_hash[..8],
+                                    &parent_hash[..8]
+                                );
+                            }
+                            None => {
+                                app.inspector.current_content.clear();
+                                app.ui.status_message = format!("Error loading file: {}", e);
+                            }
+                        }
                     }
                 }
                 
                 // Update the last commit for future line mapping
                 app.last_commit_for_mapping = Some(commit_hash);
+            } else if app.get_history_directory().is_some() {
+                app.inspector.current_content.clear();
+                app.inspector.directory_changes.clear();
+                show_directory_changes(app);
             }
         }
     }
 }
 
+/// In the history of a directory, list the files below it that the selected commit changed in
+/// place of file content, once the files changed by the commit have been loaded
+pub fn show_directory_changes(app: &mut App) {
+    let (Some(directory), Some(commit_hash)) =
+        (app.get_history_directory(), app.history.selected_commit_hash.clone())
+    else {
+        return;
+    };
+    app.inspector.cursor_line = 0;
+    app.inspector.scroll_vertical = 0;
+    app.inspector.scroll_horizontal = 0;
+
+    let changed_files = app
+        .history
+        .changed_files
+        .as_ref()
+        .filter(|_| app.history.changed_files_commit.as_ref() == Some(&commit_hash));
+    let Some(changed_files) = changed_files else {
+        app.ui.status_message = format!(
+            "Loading files changed below {} in {}...",
+            directory.display(),
+            &commit_hash[..8]
+        );
+        return;
+    };
+
+    // A file renamed out of the directory counts as changed below it too
+    let prefix = format!("{}/", directory.to_string_lossy());
+    let below = |path: &str| path.starts_with(&prefix);
+    app.inspector.directory_changes = changed_files
+        .iter()
+        .filter(|file| below(&file.path) || matches!(&file.kind, FileChangeKind::Renamed(from) if below(from)))
+        .cloned()
+        .collect();
+    app.ui.status_message = format!(
+        "{} files changed below {} in {} | Enter: Open file",
+        app.inspector.directory_changes.len(),
+        directory.display(),
+        &commit_hash[..8]
+    );
+}
+
+/// Select a commit in the history list and place the inspector cursor on the given line.
+/// Without a line the cursor follows the current line via smart cursor positioning.
+/// Returns false if the commit is not (yet) part of the history list.
+pub fn jump_to_commit_line(app: &mut App, commit_hash: &str, line_number: Option<usize>) -> bool {
+    let Some(index) = app
+        .history
+        .commit_list
+        .iter()
+        .position(|c| c.hash == commit_hash)
+    else {
+        return false;
+    };
+
+    app.history.selected_commit_index = Some(index);
+    update_code_inspector_for_commit_no_diff(app);
+
+    if let Some(line_number) = line_number {
+        app.inspector.cursor_line =
+            line_number.min(app.inspector.current_content.len().saturating_sub(1));
+        app.ensure_inspector_cursor_visible();
+    }
+    true
+}
+
+/// Jump to the commit that last changed the line under the cursor, keeping the cursor on that
+/// line. If the blame of the selected commit is not loaded yet, it is requested and the jump is
+/// completed once it arrives; if the commit has not been streamed into the history yet, the jump
+/// is completed once it arrives.
+pub fn jump_to_blame_commit(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    let Some(commit_hash) = app.history.selected_commit_hash.clone() else {
+        app.ui.status_message = "No file or commit selected".to_string();
+        return;
+    };
+    if app.get_file_path_at_commit(&commit_hash).is_none() {
+        app.ui.status_message = "No file or commit selected".to_string();
+        return;
+    }
+
+    // Reuse the blame shown in the gutter, otherwise load it for this commit in the background
+    if app.inspector.current_blame.is_none()
+        || app.inspector.blame_commit_hash.as_ref() != Some(&commit_hash)
+    {
+        if app.inspector.blame_commit_hash.as_ref() != Some(&commit_hash) {
+            request_blame(app, task_sender);
+        }
+        app.inspector.pending_blame_jump = Some(crate::app::PendingJump {
+            commit_hash,
+            line_number: Some(app.inspector.cursor_line),
+        });
+        app.ui.status_message = "Loading blame...".to_string();
+        return;
+    }
+
+    let cursor_line = app.inspector.cursor_line;
+    if finish_blame_jump(app, cursor_line) {
+        request_commit_details(app, task_sender);
+    }
+    load_history_for_pending_jump(app, task_sender);
+}
+
+/// Jump from the selected commit to the commit the loaded blame attributes the given line to,
+/// placing the cursor on that line as it was in that commit. Returns true if the selected
+/// commit changed.
+pub fn finish_blame_jump(app: &mut App, line_number: usize) -> bool {
+    let Some(commit_hash) = app.history.selected_commit_hash.clone() else {
+        return false;
+    };
+    let Some(blame_line) = app
+        .inspector
+        .current_blame
+        .as_ref()
+        .and_then(|blame| blame.get(line_number))
+        .cloned()
+    else {
+        app.ui.status_message = "No blame information for this line".to_string();
+        return false;
+    };
+
+    if blame_line.commit_hash == commit_hash {
+        app.ui.status_message = format!(
+            "Line {} was last changed in the selected commit ({})",
+            line_number + 1,
+            blame_line.short_hash
+        );
+        return false;
+    }
+
+    if jump_to_commit_line(
+        app,
+        &blame_line.commit_hash,
+        Some(blame_line.original_line_number),
+    ) {
+        app.ui.status_message = format!(
+            "Jumped to {} by {} (line {})",
+            blame_line.short_hash,
+            blame_line.author,
+            app.inspector.cursor_line + 1
+        );
+        true
+    } else if !app.history.history_complete {
+        app.history.pending_jump = Some(crate::app::PendingJump {
+            commit_hash: blame_line.commit_hash,
+            line_number: Some(blame_line.original_line_number),
+        });
+        app.ui.status_message = format!(
+            "Line last changed in {}, waiting for history to load...",
+            blame_line.short_hash
+        );
+        false
+    } else {
+        app.ui.status_message = format!(
+            "Line last changed in {}, but commit not in history",
+            blame_line.short_hash
+        );
+        false
+    }
+}
+
+/// Keep loading history chunks while a pending jump's commit has not arrived yet.
+/// Streaming loads the whole history on its own, so this only matters for progressive loading.
+pub fn load_history_for_pending_jump(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    if app.history.pending_jump.is_none() || app.history.streaming_cancellation_token.is_some() {
+        return;
+    }
+
+    let status_message = app.ui.status_message.clone();
+    if let Ok(true) = file_loader::load_more_commit_history(app, task_sender) {
+        app.ui.status_message = status_message;
+    }
+}
+
 /// Update the code inspector with content from the selected commit and regenerate diff if needed
 pub fn update_code_inspector_for_commit(app: &mut App, task_sender: &mpsc::Sender<Task>) {
     update_code_inspector_for_commit_no_diff(app);
+    request_commit_details(app, task_sender);
+}
+
+/// Request the blame, changed files, containing refs and diff shown for the selected commit
+fn request_commit_details(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    request_blame_if_needed(app, task_sender);
+    request_changed_files_if_needed(app, task_sender);
+    request_containing_refs_if_needed(app, task_sender);
     
     // If diff view is active, regenerate diff for the new commit
     if app.inspector.show_diff_view {
-        if let Some(selected) = app.history.selected_commit_index {
-            if let Some(commit) = app.history.commit_list.get(selected) {
-                if let Some(file_path) = app.get_active_file() {
-                    let current_commit = commit.hash.clone();
-                    
-                    // Get parent commit
-                    match crate::git_utils::get_parent_commit(&app.repo, &current_commit) {
-                        Ok(Some(parent_commit)) => {
-                            let file_path_str = file_path.to_string_lossy().to_string();
-                            let task = Task::GenerateDiff {
-                                file_path: file_path_str,
-                                current_commit: current_commit.clone(),
-                                parent_commit,
-                            };
-                            
-                            let sender = task_sender.clone();
-                            tokio::spawn(async move {
-                                if let Err(e) = sender.send(task).await {
-                                    log::error!("Failed to send GenerateDiff task: {}", e);
-                                }
-                            });
-                            
-                            app.start_background_task();
-                            app.ui.status_message = "Loading diff view...".to_string();
-                        }
-                        Ok(None) => {
-                            app.ui.status_message = "No parent commit - this is the initial commit".to_string();
-                            // Clear diff data for initial commit
-                            app.inspector.diff_lines = None;
-                            app.inspector.parent_commit_hash = None;
-                        }
-                        Err(e) => {
-                            app.ui.status_message = format!("Failed to get parent commit: {}", e);
-                            // Clear diff data on error
-                            app.inspector.diff_lines = None;
-                            app.inspector.parent_commit_hash = None;
-                        }
-                    }
-                }
+        if let Err(message) = request_diff(app, task_sender) {
+            app.ui.status_message = message;
+            // Clear diff data when there is nothing to diff against
+            app.inspector.set_diff_lines(None);
+            app.inspector.base_commit_hash = None;
+        }
+    }
+}
+
+/// Change what the diff view compares against, reloading the diff if it is shown
+pub fn set_diff_base(app: &mut App, base: DiffBase, task_sender: &mpsc::Sender<Task>) {
+    app.inspector.set_diff_lines(None);
+    app.inspector.diff_base = base;
+    app.inspector.base_commit_hash = None;
+
+    if app.inspector.show_diff_view {
+        if let Err(message) = request_diff(app, task_sender) {
+            app.ui.status_message = message;
+        }
+    }
+}
+
+/// Request the diff of the selected commit against the chosen diff base.
+///
+/// Returns a message for the status bar if there is nothing to diff against, e.g. the parent
+/// of the initial commit.
+pub fn request_diff(app: &mut App, task_sender: &mpsc::Sender<Task>) -> Result<(), String> {
+    let Some(current_commit) = app.history.selected_commit_hash.clone() else {
+        return Err("No file or commit selected for diff view".to_string());
+    };
+    let Some(file_path) = app.get_file_path_at_commit(&current_commit) else {
+        return Err("No file or commit selected for diff view".to_string());
+    };
+
+    let base = app.inspector.diff_base.clone();
+    let base_file_path = match &base {
+        DiffBase::Parent(n) => {
+            let parents = crate::git_utils::get_parent_commits(&app.repo, &current_commit)
+                .map_err(|e| format!("Failed to get parent commit: {}", e))?;
+            if parents.is_empty() {
+                return Err("No parent commit - this is the initial commit".to_string());
+            }
+            if *n >= parents.len() {
+                return Err(format!(
+                    "Commit {} has only {} parent(s)",
+                    &current_commit[..8],
+                    parents.len()
+                ));
             }
+            file_path.clone()
         }
+        DiffBase::Commit(hash) => app
+            .get_file_path_at_commit(hash)
+            .unwrap_or_else(|| file_path.clone()),
+        // The index and working tree have the file under its current name
+        DiffBase::Index | DiffBase::WorkingTree => {
+            app.get_active_file().unwrap_or_else(|| file_path.clone())
+        }
+    };
+
+    let task = Task::GenerateDiff {
+        file_path: file_path.to_string_lossy().to_string(),
+        current_commit: current_commit.clone(),
+        base: base.clone(),
+        base_file_path: base_file_path.to_string_lossy().to_string(),
+    };
+
+    let sender = task_sender.clone();
+    tokio::spawn(async move {
+        if let Err(e) = sender.send(task).await {
+            log::error!("Failed to send GenerateDiff task: {}", e);
+        }
+    });
+
+    app.start_background_task();
+    app.ui.is_loading = true;
+    app.ui.status_message = format!(
+        "Loading diff of {} at {} against {}...",
+        file_path.display(),
+        &current_commit[..8],
+        base.describe()
+    );
+    Ok(())
+}
+
+/// Request blame for the selected commit if the blame column is shown and the loaded blame is for another commit
+pub fn request_blame_if_needed(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    if !app.inspector.show_blame {
+        return;
+    }
+    if app.inspector.blame_commit_hash.is_some()
+        && app.inspector.blame_commit_hash == app.history.selected_commit_hash
+    {
+        return;
+    }
+    request_blame(app, task_sender);
+}
+
+/// Load the blame of the selected commit in the background, replacing any loaded blame
+fn request_blame(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    let Some(commit_hash) = app.history.selected_commit_hash.clone() else {
+        return;
+    };
+    let Some(file_path) = app.get_file_path_at_commit(&commit_hash) else {
+        return;
+    };
+
+    app.inspector.current_blame = None;
+    app.inspector.blame_commit_hash = Some(commit_hash.clone());
+
+    let task = Task::LoadBlame {
+        file_path: file_path.to_string_lossy().to_string(),
+        commit_hash,
+    };
+
+    let sender = task_sender.clone();
+    tokio::spawn(async move {
+        if let Err(e) = sender.send(task).await {
+            log::error!("Failed to send LoadBlame task: {}", e);
+        }
+    });
+
+    app.start_background_task();
+}
+
+/// Request the branches and tags containing the selected commit if the commit details are shown and
+/// the loaded refs are for another commit
+pub fn request_containing_refs_if_needed(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    if !app.history.show_commit_details {
+        return;
+    }
+
+    if let Some(commit_hash) = app.history.selected_commit_hash.clone() {
+        if app.history.containing_refs_commit.as_ref() == Some(&commit_hash) {
+            return;
+        }
+
+        app.history.containing_refs = None;
+        app.history.containing_refs_commit = Some(commit_hash.clone());
+
+        // The walk can be slow on large repositories; stop the one for the previous commit
+        if let Some(token) = app.history.containing_refs_cancellation_token.take() {
+            token.cancel();
+        }
+        let cancellation_token = CancellationToken::new();
+        app.history.containing_refs_cancellation_token = Some(cancellation_token.clone());
+
+        let task = Task::LoadContainingRefs {
+            commit_hash,
+            cancellation_token,
+        };
+
+        let sender = task_sender.clone();
+        tokio::spawn(async move {
+            if let Err(e) = sender.send(task).await {
+                log::error!("Failed to send LoadContainingRefs task: {}", e);
+            }
+        });
+
+        app.start_background_task();
+    }
+}
+
+/// Request the files changed by the selected commit if the commit details or the history of a
+/// directory are shown and the loaded list is for another commit
+pub fn request_changed_files_if_needed(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    if !app.history.show_commit_details && app.get_history_directory().is_none() {
+        return;
+    }
+
+    if let Some(commit_hash) = app.history.selected_commit_hash.clone() {
+        if app.history.changed_files_commit.as_ref() == Some(&commit_hash) {
+            return;
+        }
+
+        app.history.changed_files = None;
+        app.history.changed_files_commit = Some(commit_hash.clone());
+
+        let task = Task::LoadChangedFiles { commit_hash };
+
+        let sender = task_sender.clone();
+        tokio::spawn(async move {
+            if let Err(e) = sender.send(task).await {
+                log::error!("Failed to send LoadChangedFiles task: {}", e);
+            }
+        });
+
+        app.start_background_task();
     }
 }
@@ -17,6 +17,11 @@ pub fn handle_navigation_event(key: KeyEvent, app: &mut App, task_sender: &mpsc:
             app.ui.active_panel = PanelFocus::Inspector;
         }
 
+        // Choose the revision the tree and histories are rooted at
+        KeyCode::Char('r') => {
+            crate::event::revision::open_ref_picker(app, task_sender);
+        }
+
         // Older/Younger commit navigation (global)
         KeyCode::Char('[') => {
             // Select previous (older) commit
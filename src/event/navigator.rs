@@ -2,7 +2,9 @@ use crate::app::{App, PanelFocus};
 use crate::async_task::Task;
 use crate::event::{file_loader, EventResult};
 use crossterm::event::{KeyCode, KeyEvent};
+use std::path::Path;
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 pub fn handle_navigator_event(
     key: KeyEvent,
@@ -29,25 +31,25 @@ pub fn handle_navigator_event(
                 event_handled = true;
             }
             KeyCode::Char(c) => {
-                let previous_selection = app.get_active_file();
+                let previous_selection = app.get_history_path();
                 let mut query = app.navigator.get_search_query();
                 query.push(c);
                 app.navigator
                     .handle_event(crate::navigator::NavigatorEvent::UpdateSearchQuery(query))?;
-                // Check if the active file changed
-                if previous_selection != app.get_active_file() {
+                // Check if the file or directory whose history is shown changed
+                if previous_selection != app.get_history_path() {
                     file_loader::load_commit_history_for_selected_file(app, task_sender)?;
                 }
                 event_handled = true;
             }
             KeyCode::Backspace => {
-                let previous_selection = app.get_active_file();
+                let previous_selection = app.get_history_path();
                 let mut query = app.navigator.get_search_query();
                 query.pop();
                 app.navigator
                     .handle_event(crate::navigator::NavigatorEvent::UpdateSearchQuery(query))?;
-                // Check if the active file changed
-                if previous_selection != app.get_active_file() {
+                // Check if the file or directory whose history is shown changed
+                if previous_selection != app.get_history_path() {
                     file_loader::load_commit_history_for_selected_file(app, task_sender)?;
                 }
                 event_handled = true;
@@ -63,20 +65,20 @@ pub fn handle_navigator_event(
     // Handle normal mode navigation
     match key.code {
         KeyCode::Up => {
-            let previous_selection = app.get_active_file();
+            let previous_selection = app.get_history_path();
             app.navigator
                 .handle_event(crate::navigator::NavigatorEvent::NavigateUp)?;
-            // Check if the active file changed
-            if previous_selection != app.get_active_file() {
+            // Check if the file or directory whose history is shown changed
+            if previous_selection != app.get_history_path() {
                 file_loader::load_commit_history_for_selected_file(app, task_sender)?;
             }
         }
         KeyCode::Down => {
-            let previous_selection = app.get_active_file();
+            let previous_selection = app.get_history_path();
             app.navigator
                 .handle_event(crate::navigator::NavigatorEvent::NavigateDown)?;
-            // Check if the active file changed
-            if previous_selection != app.get_active_file() {
+            // Check if the file or directory whose history is shown changed
+            if previous_selection != app.get_history_path() {
                 file_loader::load_commit_history_for_selected_file(app, task_sender)?;
             }
         }
@@ -87,6 +89,7 @@ pub fn handle_navigator_event(
         KeyCode::Right => {
             app.navigator
                 .handle_event(crate::navigator::NavigatorEvent::ExpandSelected)?;
+            load_deleted_files_if_expanded(app, task_sender);
         }
         KeyCode::Enter => {
             if let Some(selection) = app.navigator.get_selection() {
@@ -95,6 +98,7 @@ pub fn handle_navigator_event(
                     app.navigator.handle_event(
                         crate::navigator::NavigatorEvent::ToggleExpanded(selection),
                     )?;
+                    load_deleted_files_if_expanded(app, task_sender);
                 } else {
                     // Enter on a file moves focus to Inspector panel
                     app.ui.active_panel = PanelFocus::Inspector;
@@ -105,8 +109,43 @@ pub fn handle_navigator_event(
             app.navigator
                 .handle_event(crate::navigator::NavigatorEvent::StartSearch)?;
         }
+        KeyCode::Char('R') => {
+            // Re-read which files are changed in the index and working tree
+            let sender = task_sender.clone();
+            tokio::spawn(async move {
+                if let Err(e) = sender.send(Task::LoadWorktreeStatus).await {
+                    log::error!("Failed to send LoadWorktreeStatus task: {}", e);
+                }
+            });
+            app.start_background_task();
+            app.ui.status_message = "Refreshing working tree status...".to_string();
+        }
         _ => return Ok(false),
     }
 
     Ok(true)
 }
+
+/// Look up the files deleted in the history the first time their folder is opened
+fn load_deleted_files_if_expanded(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    let folder = Path::new(crate::tree::DELETED_FILES_PATH);
+    if app.deleted_files.is_some() || !app.navigator.is_expanded(folder) {
+        return;
+    }
+    app.deleted_files = Some(Vec::new());
+    let cancellation_token = CancellationToken::new();
+    app.deleted_files_cancellation_token = Some(cancellation_token.clone());
+
+    let task = Task::LoadDeletedFiles {
+        revision: app.revision.clone(),
+        cancellation_token,
+    };
+    let sender = task_sender.clone();
+    tokio::spawn(async move {
+        if let Err(e) = sender.send(task).await {
+            log::error!("Failed to send LoadDeletedFiles task: {}", e);
+        }
+    });
+    app.start_background_task();
+    app.ui.status_message = "Looking for deleted files in the history...".to_string();
+}
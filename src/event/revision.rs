@@ -0,0 +1,134 @@
+use crate::app::{App, RefKind, RefPickerState};
+use crate::async_task::Task;
+use crate::event::{file_loader, EventResult};
+use crossterm::event::{KeyCode, KeyEvent};
+use tokio::sync::mpsc;
+
+/// Open the ref picker and start loading the references it lists
+pub fn open_ref_picker(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+    app.ui.ref_picker = Some(RefPickerState::default());
+    app.ui.status_message =
+        "Type to filter refs or enter any revision | ↑↓: Select | Enter: Switch | Esc: Cancel".to_string();
+
+    let sender = task_sender.clone();
+    tokio::spawn(async move {
+        if let Err(e) = sender.send(Task::LoadRefs).await {
+            log::error!("Failed to send LoadRefs task: {}", e);
+        }
+    });
+    app.start_background_task();
+}
+
+/// Handle keys while the ref picker is open; it captures all input until closed
+pub fn handle_ref_picker_event(
+    key: KeyEvent,
+    app: &mut App,
+    task_sender: &mpsc::Sender<Task>,
+) -> EventResult {
+    let Some(picker) = app.ui.ref_picker.as_mut() else {
+        return Ok(false);
+    };
+
+    match key.code {
+        KeyCode::Char(c) => {
+            picker.query.push(c);
+            update_query(picker, &app.repo);
+        }
+        KeyCode::Backspace => {
+            picker.query.pop();
+            update_query(picker, &app.repo);
+        }
+        KeyCode::Up => {
+            picker.selected = picker.selected.saturating_sub(1);
+            picker.selection_moved = true;
+        }
+        KeyCode::Down => {
+            // Moving off a resolving query first selects the top match
+            if !picker.uses_query() && picker.selected + 1 < picker.filtered_refs().len() {
+                picker.selected += 1;
+            }
+            picker.selection_moved = true;
+        }
+        KeyCode::Esc => {
+            app.ui.ref_picker = None;
+            app.ui.status_message = "Revision unchanged".to_string();
+        }
+        KeyCode::Enter => {
+            // A query that resolves as a revspec (such as `HEAD~3` or an abbreviated hash) wins
+            // unless a ref was picked with the arrow keys; refs are switched to by full name
+            let query = picker.query.trim();
+            let revision = match picker.selected_ref() {
+                _ if picker.uses_query() => Some(query.to_string()),
+                Some(r) if r.kind == RefKind::Head => None,
+                Some(r) => Some(r.full_name.clone()),
+                None => (!query.is_empty()).then(|| query.to_string()),
+            };
+            match switch_revision(app, revision, task_sender) {
+                Ok(()) => {
+                    app.ui.ref_picker = None;
+                    // A picked revision replaces a commit tree being browsed
+                    app.revision_before_commit_tree = None;
+                }
+                // Keep the picker open so the revision can be corrected
+                Err(message) => app.ui.status_message = message,
+            }
+        }
+        _ => {}
+    }
+
+    Ok(true)
+}
+
+/// Reset the selection after the query changed and resolve the query as a revspec
+fn update_query(picker: &mut RefPickerState, repo: &gix::Repository) {
+    picker.selected = 0;
+    picker.selection_moved = false;
+    let query = picker.query.trim();
+    picker.query_commit = (!query.is_empty())
+        .then(|| crate::git_utils::resolve_revision(repo, Some(query)).ok())
+        .flatten()
+        .map(|id| id.to_string());
+}
+
+/// Re-root the file tree and the history of the selected file at `revision` (HEAD if None)
+pub fn switch_revision(
+    app: &mut App,
+    revision: Option<String>,
+    task_sender: &mpsc::Sender<Task>,
+) -> Result<(), String> {
+    let commit_id = crate::git_utils::resolve_revision(&app.repo, revision.as_deref())
+        .map_err(|e| e.to_string())?;
+
+    app.revision = revision;
+    // Files deleted before the previous revision are looked up again when asked for
+    app.deleted_files = None;
+    if let Some(token) = app.deleted_files_cancellation_token.take() {
+        token.cancel();
+    }
+
+    let task = Task::LoadFileTree {
+        revision: app.revision.clone(),
+    };
+    let sender = task_sender.clone();
+    tokio::spawn(async move {
+        if let Err(e) = sender.send(task).await {
+            log::error!("Failed to send LoadFileTree task: {}", e);
+        }
+    });
+    app.start_background_task();
+
+    // The selected file or directory stays selected if it exists at the new revision
+    if app.get_history_path().is_some() {
+        file_loader::load_commit_history_for_selected_file(app, task_sender)
+            .map_err(|e| e.to_string())?;
+    }
+
+    app.ui.is_loading = true;
+    app.ui.status_message = format!(
+        "Switched to {} ({})",
+        crate::app::revision_label(app.revision.as_deref()),
+        &commit_id.to_string()[..8]
+    );
+
+    Ok(())
+}
@@ -1,4 +1,8 @@
-use crate::{app::PanelFocus, command::Command, test_config::TestConfig};
+use crate::{
+    app::{DiffBase, PanelFocus},
+    command::Command,
+    test_config::TestConfig,
+};
 
 /// Result of executing a command
 #[derive(Debug, Clone)]
@@ -193,18 +197,13 @@ impl Executor {
 
             Command::PreviousChange => {
                 if new_config.active_panel == PanelFocus::Inspector {
-                    // Simulate finding previous change (would use Git blame in real implementation)
-                    status_message = Some(format!(
-                        "Previous change for line {}",
-                        new_config.cursor_line + 1
-                    ));
+                    Self::execute_change_search(&mut new_config, false, &mut status_message);
                 }
             }
 
             Command::NextChange => {
                 if new_config.active_panel == PanelFocus::Inspector {
-                    new_config.is_loading = true;
-                    status_message = Some("Searching for next change...".to_string());
+                    Self::execute_change_search(&mut new_config, true, &mut status_message);
                 }
             }
 
@@ -219,6 +218,113 @@ impl Executor {
                 }
             }
 
+            Command::ToggleSideBySide => {
+                if new_config.active_panel == PanelFocus::Inspector {
+                    // Opening the diff from the file view always starts side by side
+                    new_config.diff_side_by_side =
+                        !new_config.show_diff_view || !new_config.diff_side_by_side;
+                    new_config.show_diff_view = true;
+                    status_message = Some(if new_config.diff_side_by_side {
+                        "Switched to side-by-side diff".to_string()
+                    } else {
+                        "Switched to inline diff".to_string()
+                    });
+                }
+            }
+
+            Command::MarkDiffBase => {
+                if new_config.active_panel == PanelFocus::History {
+                    if let Some(commit_hash) = new_config.selected_commit_hash.clone() {
+                        let base = DiffBase::Commit(commit_hash.clone());
+                        if new_config.diff_base == base {
+                            new_config.diff_base = DiffBase::default();
+                            status_message = Some("Diff base cleared - diffing against parent".to_string());
+                        } else {
+                            new_config.diff_base = base;
+                            status_message = Some(format!(
+                                "Marked {} as diff base - the diff view compares other commits against it",
+                                &commit_hash[..8.min(commit_hash.len())]
+                            ));
+                        }
+                    }
+                }
+            }
+
+            Command::ToggleCommitDetails => {
+                if new_config.active_panel == PanelFocus::History {
+                    new_config.show_commit_details = !new_config.show_commit_details;
+                    if new_config.show_commit_details {
+                        Self::execute_load_changed_files(&mut new_config, &mut status_message);
+                    } else {
+                        status_message = Some("Commit details hidden".to_string());
+                    }
+                }
+            }
+
+            Command::CycleHistoryMode => {
+                if new_config.active_panel == PanelFocus::History {
+                    Self::execute_cycle_history_mode(&mut new_config, &mut status_message);
+                }
+            }
+
+            Command::CycleWorktreeDiff => {
+                if new_config.active_panel == PanelFocus::Inspector {
+                    new_config.diff_base = match new_config.diff_base {
+                        DiffBase::WorkingTree => DiffBase::Index,
+                        DiffBase::Index => DiffBase::default(),
+                        _ => DiffBase::WorkingTree,
+                    };
+                    new_config.show_diff_view = true;
+                    status_message = Some(format!(
+                        "Diffing against {}",
+                        new_config.diff_base.describe()
+                    ));
+                }
+            }
+
+            Command::NextDiffParent => {
+                if new_config.active_panel == PanelFocus::Inspector {
+                    Self::execute_next_diff_parent(&mut new_config, &mut status_message);
+                }
+            }
+
+            Command::ToggleBlame => {
+                if new_config.active_panel == PanelFocus::Inspector {
+                    new_config.show_blame = !new_config.show_blame;
+                    status_message = Some(if new_config.show_blame {
+                        "Blame column shown".to_string()
+                    } else {
+                        "Blame column hidden".to_string()
+                    });
+                }
+            }
+
+            Command::JumpToBlameCommit => {
+                if new_config.active_panel == PanelFocus::Inspector {
+                    Self::execute_blame_jump(&mut new_config, &mut status_message);
+                }
+            }
+
+            Command::ToggleSelection => {
+                if new_config.active_panel == PanelFocus::Inspector {
+                    if new_config.selection_anchor.take().is_some() {
+                        status_message = Some("Line selection cleared".to_string());
+                    } else {
+                        new_config.selection_anchor = Some(new_config.cursor_line);
+                        status_message = Some(
+                            "Selecting lines - move the cursor, then press L for their history"
+                                .to_string(),
+                        );
+                    }
+                }
+            }
+
+            Command::LineRangeHistory => {
+                if new_config.active_panel == PanelFocus::Inspector {
+                    Self::execute_line_range_history(&mut new_config, &mut status_message);
+                }
+            }
+
             Command::Sequence(commands) => {
                 // Execute commands in sequence
                 for cmd in commands {
@@ -319,4 +425,166 @@ impl Executor {
             (config.cursor_line + 10).min(config.current_content.len().saturating_sub(1));
         *status_message = Some(format!("Page down - Line: {}", config.cursor_line + 1));
     }
-}
\ No newline at end of file
+
+    /// Start looking for the next/previous change of the cursor line, as 'n' and 'p' do in the UI
+    fn execute_change_search(
+        config: &mut TestConfig,
+        forward: bool,
+        status_message: &mut Option<String>,
+    ) {
+        let direction = if forward { "next" } else { "previous" };
+
+        if config.selected_commit_hash.is_none() {
+            *status_message = Some(format!("No file or commit selected for {} change", direction));
+            return;
+        }
+
+        // The search runs in the background in the UI
+        config.is_loading = true;
+        *status_message = Some(format!("Searching for {} change...", direction));
+    }
+
+    /// Select the commit that last changed the cursor line, as the 'B' key does in the UI
+    fn execute_blame_jump(config: &mut TestConfig, status_message: &mut Option<String>) {
+        let Some(commit_hash) = config.selected_commit_hash.clone() else {
+            *status_message = Some("No file or commit selected".to_string());
+            return;
+        };
+
+        // Blame is loaded in the background in the UI, so it has to be part of the configuration
+        let Some(blame) = config.current_blame.clone() else {
+            *status_message = Some("No blame loaded for the selected commit".to_string());
+            return;
+        };
+
+        let Some(blame_line) = blame.get(config.cursor_line) else {
+            *status_message = Some("No blame information for this line".to_string());
+            return;
+        };
+
+        if blame_line.commit_hash == commit_hash {
+            *status_message = Some(format!(
+                "Line {} was last changed in the selected commit ({})",
+                config.cursor_line + 1,
+                blame_line.short_hash
+            ));
+            return;
+        }
+
+        match config
+            .commit_list
+            .iter()
+            .position(|c| c.hash == blame_line.commit_hash)
+        {
+            Some(index) => {
+                config.selected_commit_index = Some(index);
+                config.selected_commit_hash = Some(blame_line.commit_hash.clone());
+                config.cursor_line = blame_line.original_line_number;
+                config.current_blame = None;
+                *status_message = Some(format!(
+                    "Jumped to {} by {} (line {})",
+                    blame_line.short_hash,
+                    blame_line.author,
+                    blame_line.original_line_number + 1
+                ));
+            }
+            None => {
+                *status_message = Some(format!(
+                    "Line last changed in {}, but commit not in history",
+                    blame_line.short_hash
+                ));
+            }
+        }
+    }
+
+    /// Report the files changed by the selected commit shown in the commit details; they are
+    /// loaded in the background in the UI, so only those in the configuration are known
+    fn execute_load_changed_files(config: &mut TestConfig, status_message: &mut Option<String>) {
+        let Some(commit_hash) = config.selected_commit_hash.clone() else {
+            *status_message = Some("No commit selected".to_string());
+            return;
+        };
+
+        *status_message = Some(match &config.changed_files {
+            Some(changed_files) => format!(
+                "Commit {} changed {} file(s)",
+                &commit_hash[..8.min(commit_hash.len())],
+                changed_files.len()
+            ),
+            None => "Loading changed files...".to_string(),
+        });
+    }
+
+    /// Diff a merge commit against its next parent, as 'P' does in the UI
+    fn execute_next_diff_parent(config: &mut TestConfig, status_message: &mut Option<String>) {
+        let Some(commit_hash) = config.selected_commit_hash.clone() else {
+            *status_message = Some("No commit selected".to_string());
+            return;
+        };
+        let parent_count = config
+            .commit_list
+            .iter()
+            .find(|commit| commit.hash == commit_hash)
+            .map_or(0, |commit| commit.parent_ids.len());
+
+        if parent_count < 2 {
+            *status_message =
+                Some("Not a merge commit - there is only one parent to diff against".to_string());
+            return;
+        }
+
+        let next_parent = match config.diff_base {
+            DiffBase::Parent(n) => (n + 1) % parent_count,
+            _ => 0,
+        };
+        config.diff_base = DiffBase::Parent(next_parent);
+        config.show_diff_view = true;
+        *status_message = Some(format!("Diffing against {}", config.diff_base.describe()));
+    }
+
+    /// Switch to the next history mode; the history is loaded again in the background in the UI
+    fn execute_cycle_history_mode(config: &mut TestConfig, status_message: &mut Option<String>) {
+        config.history_mode = config.history_mode.next();
+        config.line_range = None;
+        config.is_loading = true;
+        *status_message = Some(format!("History mode: {}", config.history_mode.describe()));
+    }
+
+    /// Start the history of the selected lines (or the cursor line), as 'L' does in the UI
+    fn execute_line_range_history(config: &mut TestConfig, status_message: &mut Option<String>) {
+        let navigator = config.navigator();
+        let active_file = navigator
+            .get_selection()
+            .filter(|path| !navigator.is_path_directory(path));
+        let (Some(active_file), Some(commit_hash)) = (active_file, config.selected_commit_hash.clone()) else {
+            *status_message = Some("No file or commit selected for line history".to_string());
+            return;
+        };
+
+        let (start_line, end_line) = match config.selection_anchor {
+            Some(anchor) => (anchor.min(config.cursor_line), anchor.max(config.cursor_line)),
+            None => (config.cursor_line, config.cursor_line),
+        };
+        // Before a rename the selected commit knows the file under its older path
+        let file_path = config
+            .commit_list
+            .iter()
+            .find(|commit| commit.hash == commit_hash)
+            .and_then(|commit| commit.file_path.clone())
+            .unwrap_or_else(|| active_file.to_string_lossy().to_string());
+
+        config.line_range = Some(crate::app::LineRange {
+            commit_hash,
+            file_path,
+            start_line,
+            end_line,
+        });
+        config.selection_anchor = None;
+        config.is_loading = true;
+        *status_message = Some(format!(
+            "Loading history for lines {}-{}...",
+            start_line + 1,
+            end_line + 1
+        ));
+    }
+}
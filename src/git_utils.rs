@@ -1,9 +1,16 @@
 use gix::Repository;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
 
-use crate::app::CommitInfo;
+use crate::app::{
+    BlameLine, ChangedFile, CommitInfo, ContainingRefs, FileChangeKind, HistoryMode, RefInfo, RefKind,
+};
+use crate::cache::{self, CacheKind};
+use crate::commit_graph::{self, CommitData, CommitGraph};
 
 /// Represents a file or directory entry from a Git tree
 #[derive(Debug, Clone)]
@@ -19,10 +26,201 @@ pub fn open_repository<P: AsRef<Path>>(
     Ok(gix::discover(path)?)
 }
 
+/// List HEAD, local branches, remote-tracking branches and tags (each group most recent first)
+/// together with the commit they point at. References that do not lead to a commit are skipped.
+pub fn list_refs(repo: &Repository) -> Result<Vec<RefInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut refs = Vec::new();
+
+    if let Ok(mut head) = repo.head_commit() {
+        refs.push(ref_info("HEAD".to_string(), "HEAD".to_string(), RefKind::Head, &mut head)?);
+    }
+
+    let platform = repo.references()?;
+    let groups = [
+        (RefKind::Branch, platform.local_branches()?),
+        (RefKind::RemoteBranch, platform.remote_branches()?),
+        (RefKind::Tag, platform.tags()?),
+    ];
+    for (kind, iter) in groups {
+        let mut group = Vec::new();
+        for reference in iter {
+            let mut reference = reference?;
+            let name = reference.name().shorten().to_string();
+            // `origin/HEAD` only mirrors another remote branch
+            if kind == RefKind::RemoteBranch && name.ends_with("/HEAD") {
+                continue;
+            }
+            let Ok(mut commit) = reference.peel_to_commit() else {
+                continue;
+            };
+            let full_name = reference.name().as_bstr().to_string();
+            group.push(ref_info(name, full_name, kind, &mut commit)?);
+        }
+        group.sort_by(|a, b| b.commit_time.cmp(&a.commit_time).then_with(|| a.name.cmp(&b.name)));
+        refs.extend(group);
+    }
+
+    Ok(refs)
+}
+
+/// Find the branches and tags whose history contains `commit_hash`, like `git tag --contains`.
+/// Once cancelled, stops early with an error, as the references found so far are incomplete.
+pub fn find_refs_containing(
+    repo: &Repository,
+    commit_hash: &str,
+    cancellation_token: &tokio_util::sync::CancellationToken,
+) -> Result<ContainingRefs, Box<dyn std::error::Error + Send + Sync>> {
+    let target_id = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let graph = CommitGraph::open(repo);
+
+    // Generation numbers only grow from parent to child, so a commit whose generation is not
+    // above the target's cannot have it as an ancestor. The commit-graph is closed under
+    // reachability, so no commit in it reaches a target that is newer than the commit-graph.
+    let target_generation = graph.as_ref().map(|graph| graph.generation(&target_id));
+    let cannot_reach_target = |id: &gix::oid| -> bool {
+        let (Some(graph), Some(target_generation)) = (&graph, target_generation) else {
+            return false;
+        };
+        match (graph.generation(id), target_generation) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(generation), Some(target)) => generation != 0 && target != 0 && generation <= target,
+        }
+    };
+
+    // Whether each visited commit has the target among its ancestors (or is the target)
+    let mut contains: HashMap<gix::ObjectId, bool> = HashMap::new();
+    contains.insert(target_id, true);
+
+    let mut containing = ContainingRefs::default();
+    for r in list_refs(repo)? {
+        if r.kind == RefKind::Head {
+            continue;
+        }
+        let tip = gix::ObjectId::from_hex(r.commit_hash.as_bytes())?;
+
+        // Depth-first walk; a commit is decided once all its parents are, which is when it is
+        // popped again with its parents attached
+        let mut stack: Vec<(gix::ObjectId, Option<Vec<gix::ObjectId>>)> = vec![(tip, None)];
+        while let Some((id, parents)) = stack.pop() {
+            if contains.contains_key(&id) {
+                continue;
+            }
+            if let Some(parent_ids) = parents {
+                let reaches = parent_ids.iter().any(|p| contains.get(p) == Some(&true));
+                contains.insert(id, reaches);
+                continue;
+            }
+            if cancellation_token.is_cancelled() {
+                log::info!("🕐 find_refs_containing: Cancelled for {}", &commit_hash[..8]);
+                return Err("Search for containing references was cancelled".into());
+            }
+            if cannot_reach_target(&id) {
+                contains.insert(id, false);
+                continue;
+            }
+            // Commits missing from a shallow clone cannot lead to the target
+            let Ok(commit) = commit_graph::commit_data(repo, graph.as_ref(), id) else {
+                contains.insert(id, false);
+                continue;
+            };
+            let unvisited: Vec<_> = commit
+                .parent_ids
+                .iter()
+                .filter(|p| !contains.contains_key(*p))
+                .map(|p| (*p, None))
+                .collect();
+            stack.push((id, Some(commit.parent_ids)));
+            stack.extend(unvisited);
+        }
+
+        if contains.get(&tip) == Some(&true) {
+            match r.kind {
+                RefKind::Tag => containing.tags.push(r),
+                _ => containing.branches.push(r),
+            }
+        }
+    }
+
+    containing
+        .tags
+        .sort_by(|a, b| a.commit_time.cmp(&b.commit_time).then_with(|| a.name.cmp(&b.name)));
+
+    Ok(containing)
+}
+
+fn ref_info(
+    name: String,
+    full_name: String,
+    kind: RefKind,
+    commit: &mut gix::Commit<'_>,
+) -> Result<RefInfo, Box<dyn std::error::Error + Send + Sync>> {
+    let commit_time = commit.time()?.seconds;
+    let date = Local
+        .timestamp_opt(commit_time, 0)
+        .single()
+        .unwrap_or_else(Local::now)
+        .format("%Y-%m-%d %H:%M")
+        .to_string();
+
+    Ok(RefInfo {
+        name,
+        full_name,
+        kind,
+        commit_hash: commit.id.to_string(),
+        commit_time,
+        date,
+    })
+}
+
+/// Resolve a revision (anything `git rev-parse` accepts, e.g. a branch, tag or `HEAD~3`) to the
+/// commit it points at; None stands for HEAD
+pub fn resolve_revision(
+    repo: &Repository,
+    revision: Option<&str>,
+) -> Result<gix::ObjectId, Box<dyn std::error::Error + Send + Sync>> {
+    match revision {
+        None => Ok(repo.head_id()?.detach()),
+        Some(revision) => {
+            let id = repo
+                .rev_parse_single(revision)
+                .map_err(|e| format!("Unknown revision '{}': {}", revision, e))?;
+            Ok(id.object()?.peel_to_commit()?.id)
+        }
+    }
+}
+
+/// A file's history as kept in the on-disk cache
+#[derive(Serialize, Deserialize)]
+struct CachedHistory {
+    /// Commit the revision pointed at when the history was walked
+    tip: String,
+    commits: Vec<CommitInfo>,
+}
+
+fn history_cache_key(revision: Option<&str>, path: &str, mode: HistoryMode) -> String {
+    format!("{}:{:?}:{}", revision.unwrap_or("HEAD"), mode, path)
+}
+
+/// The cached history for `key`, unless the revision has moved away from `tip` since
+fn load_cached_history(repo: &Repository, key: &str, tip: gix::ObjectId) -> Option<Vec<CommitInfo>> {
+    let cached: CachedHistory = cache::load(repo, CacheKind::History, key)?;
+    (cached.tip == tip.to_string()).then_some(cached.commits)
+}
+
+fn store_cached_history(repo: &Repository, key: &str, tip: gix::ObjectId, commits: &[CommitInfo]) {
+    let cached = CachedHistory {
+        tip: tip.to_string(),
+        commits: commits.to_vec(),
+    };
+    cache::store(repo, CacheKind::History, key, &cached);
+}
 
 pub fn get_commit_history_for_file(
     repo: &Repository,
     file_path: &str,
+    revision: Option<&str>,
+    mode: HistoryMode,
 ) -> Result<Vec<CommitInfo>, Box<dyn std::error::Error + Send + Sync>> {
     let start_time = Instant::now();
     log::debug!("🕐 get_commit_history_for_file: Starting for file: {}", file_path);
@@ -38,112 +236,45 @@ pub fn get_commit_history_for_file(
 
     // Use gix to walk the commit history
     let head_setup_start = Instant::now();
-    let head_id = repo.head_id()?;
-    let commit_iter = repo.rev_walk([head_id]).all()?;
+    let start_id = resolve_revision(repo, revision)?;
+    let cache_key = history_cache_key(revision, normalized_path, mode);
+    if let Some(commits) = load_cached_history(repo, &cache_key, start_id) {
+        log::info!("🕐 get_commit_history_for_file: Loaded {} cached commits for '{}' in {:?}",
+                 commits.len(), file_path, start_time.elapsed());
+        return Ok(commits);
+    }
+    let mut walk = FileHistoryWalk::new(repo, start_id, normalized_path, mode)?;
     log::debug!("🕐 get_commit_history_for_file: Head setup took: {:?}", head_setup_start.elapsed());
 
     // Walk through commits and check if they modified the file
     let mut commits_processed = 0;
     let commit_iteration_start = Instant::now();
     
-    for commit_info in commit_iter {
+    while let Some(step) = walk.next_commit()? {
         let commit_start = Instant::now();
-        let commit_info = commit_info?;
-        let commit = repo.find_object(commit_info.id)?.try_into_commit()?;
         commits_processed += 1;
 
-        // Check if this commit actually modified the file by comparing with parent(s)
-        let modified_file = if commit.parent_ids().count() == 0 {
-            // This is the initial commit, check if file exists
-            let tree = commit.tree()?;
-            tree.lookup_entry_by_path(normalized_path)?
-                .is_some()
-        } else {
-            // Compare with parent commit(s) to see if file was modified
-            let mut file_modified = false;
-            let parent_comparison_start = Instant::now();
-
-            for parent_id in commit.parent_ids() {
-                let parent_commit = repo.find_object(parent_id)?.try_into_commit()?;
-                let current_tree = commit.tree()?;
-                let parent_tree = parent_commit.tree()?;
-
-                let current_entry =
-                    current_tree.lookup_entry_by_path(normalized_path)?;
-                let parent_entry =
-                    parent_tree.lookup_entry_by_path(normalized_path)?;
-
-                match (current_entry, parent_entry) {
-                    (Some(current), Some(parent)) => {
-                        // File exists in both - check if content changed
-                        if current.oid() != parent.oid() {
-                            file_modified = true;
-                            break;
-                        }
-                    }
-                    (Some(_), None) => {
-                        // File was added
-                        file_modified = true;
-                        break;
-                    }
-                    (None, Some(_)) => {
-                        // File was deleted
-                        file_modified = true;
-                        break;
-                    }
-                    (None, None) => {
-                        // File doesn't exist in either - not modified
-                    }
-                }
-            }
-            log::debug!("🕐 get_commit_history_for_file: Parent comparison for commit {} took: {:?}", 
-                      commit_info.id.to_string()[..8].to_string(), parent_comparison_start.elapsed());
-
-            file_modified
-        };
-
-        if modified_file {
-            // Get commit metadata
-            let commit_obj = commit.decode()?;
-            let author = &commit_obj.author;
-            let message = commit_obj.message.to_string();
-
-            // Parse Git timestamp format: "timestamp timezone" (e.g., "1751295482 -0400")
-            let timestamp = match author.time.split_whitespace().next() {
-                Some(ts_str) => ts_str.parse::<i64>().unwrap_or(0),
-                None => 0,
-            };
-            
-            // Format date as human-readable
-            let datetime = Local.timestamp_opt(timestamp, 0).single().unwrap_or_else(|| Local::now());
-            let date = datetime.format("%Y-%m-%d %H:%M").to_string();
-
-            let commit_hash = commit_info.id.to_string();
-            let short_hash = commit_hash[..8].to_string();
-
-            commits.push(CommitInfo {
-                hash: commit_hash,
-                short_hash,
-                author: author.name.to_string(),
-                date,
-                subject: message,
-            });
+        if step.modified {
+            commits.push(step.commit_info()?);
         }
         
         log::debug!("🕐 get_commit_history_for_file: Commit {} processing took: {:?}", 
-                  commit_info.id.to_string()[..8].to_string(), commit_start.elapsed());
+                  &step.id.to_string()[..8], commit_start.elapsed());
     }
     
     log::info!("🕐 get_commit_history_for_file: Completed for '{}' - {} commits found from {} processed in {:?}", 
              file_path, commits.len(), commits_processed, start_time.elapsed());
     log::debug!("🕐 get_commit_history_for_file: Commit iteration took: {:?}", commit_iteration_start.elapsed());
 
+    store_cached_history(repo, &cache_key, start_id, &commits);
     Ok(commits)
 }
 
 pub fn get_commit_history_chunk(
     repo: &Repository,
     file_path: &str,
+    revision: Option<&str>,
+    mode: HistoryMode,
     chunk_size: usize,
     start_offset: usize,
 ) -> Result<(Vec<CommitInfo>, bool), Box<dyn std::error::Error + Send + Sync>> {
@@ -151,8 +282,6 @@ pub fn get_commit_history_chunk(
     log::debug!("🕐 get_commit_history_chunk: Starting for file: {} (chunk_size: {}, offset: {})", 
               file_path, chunk_size, start_offset);
     
-    let mut commits = Vec::new();
-    let mut commits_found = 0;
     let mut commits_processed = 0;
 
     // Normalize the file path by removing "./" prefix if present
@@ -164,125 +293,57 @@ pub fn get_commit_history_chunk(
 
     // Use gix to walk the commit history
     let head_setup_start = Instant::now();
-    let head_id = repo.head_id()?;
-    let commit_iter = repo.rev_walk([head_id]).all()?;
+    let start_id = resolve_revision(repo, revision)?;
+    let cache_key = history_cache_key(revision, normalized_path, mode);
+    if let Some(cached) = load_cached_history(repo, &cache_key, start_id) {
+        let end = cached.len().min(start_offset + chunk_size);
+        let commits = cached.get(start_offset..end).unwrap_or_default().to_vec();
+        log::info!("🕐 get_commit_history_chunk: Loaded {} cached commits for '{}' in {:?}",
+                 commits.len(), file_path, start_time.elapsed());
+        return Ok((commits, end == cached.len()));
+    }
+    let mut walk = FileHistoryWalk::new(repo, start_id, normalized_path, mode)?;
     log::debug!("🕐 get_commit_history_chunk: Head setup took: {:?}", head_setup_start.elapsed());
 
     // Walk through commits and check if they modified the file
     let commit_iteration_start = Instant::now();
+    // The commits before the chunk are kept too, so the last chunk can cache the whole history
+    let mut listed = Vec::new();
     
-    for commit_info in commit_iter {
+    while let Some(step) = walk.next_commit()? {
         let commit_start = Instant::now();
-        let commit_info = commit_info?;
-        let commit = repo.find_object(commit_info.id)?.try_into_commit()?;
         commits_processed += 1;
 
-        // Check if this commit actually modified the file by comparing with parent(s)
-        let modified_file = if commit.parent_ids().count() == 0 {
-            // This is the initial commit, check if file exists
-            let tree = commit.tree()?;
-            tree.lookup_entry_by_path(normalized_path)?
-                .is_some()
-        } else {
-            // Compare with parent commit(s) to see if file was modified
-            let mut file_modified = false;
-            let parent_comparison_start = Instant::now();
-
-            for parent_id in commit.parent_ids() {
-                let parent_commit = repo.find_object(parent_id)?.try_into_commit()?;
-                let current_tree = commit.tree()?;
-                let parent_tree = parent_commit.tree()?;
-
-                let current_entry =
-                    current_tree.lookup_entry_by_path(normalized_path)?;
-                let parent_entry =
-                    parent_tree.lookup_entry_by_path(normalized_path)?;
-
-                match (current_entry, parent_entry) {
-                    (Some(current), Some(parent)) => {
-                        // File exists in both - check if content changed
-                        if current.oid() != parent.oid() {
-                            file_modified = true;
-                            break;
-                        }
-                    }
-                    (Some(_), None) => {
-                        // File was added
-                        file_modified = true;
-                        break;
-                    }
-                    (None, Some(_)) => {
-                        // File was deleted
-                        file_modified = true;
-                        break;
-                    }
-                    (None, None) => {
-                        // File doesn't exist in either - not modified
-                    }
-                }
-            }
-            log::debug!("🕐 get_commit_history_chunk: Parent comparison for commit {} took: {:?}", 
-                      commit_info.id.to_string()[..8].to_string(), parent_comparison_start.elapsed());
-
-            file_modified
-        };
-
-        if modified_file {
-            // Skip commits until we reach the start_offset
-            if commits_found < start_offset {
-                commits_found += 1;
-                continue;
-            }
-
+        if step.modified {
             // Stop if we've collected enough commits for this chunk
-            if commits.len() >= chunk_size {
+            if listed.len() >= start_offset + chunk_size {
+                let commits = listed.split_off(start_offset);
                 log::info!("🕐 get_commit_history_chunk: Chunk complete for '{}' - {} commits collected in {:?}", 
                          file_path, commits.len(), start_time.elapsed());
                 return Ok((commits, false)); // More commits available
             }
 
-            // Get commit metadata
-            let commit_obj = commit.decode()?;
-            let author = &commit_obj.author;
-            let message = commit_obj.message.to_string();
-
-            // Parse Git timestamp format: "timestamp timezone" (e.g., "1751295482 -0400")
-            let timestamp = match author.time.split_whitespace().next() {
-                Some(ts_str) => ts_str.parse::<i64>().unwrap_or(0),
-                None => 0,
-            };
-            
-            // Format date as human-readable
-            let datetime = Local.timestamp_opt(timestamp, 0).single().unwrap_or_else(|| Local::now());
-            let date = datetime.format("%Y-%m-%d %H:%M").to_string();
-
-            let commit_hash = commit_info.id.to_string();
-            let short_hash = commit_hash[..8].to_string();
-
-            commits.push(CommitInfo {
-                hash: commit_hash,
-                short_hash,
-                author: author.name.to_string(),
-                date,
-                subject: message,
-            });
-            commits_found += 1;
+            listed.push(step.commit_info()?);
         }
         
         log::debug!("🕐 get_commit_history_chunk: Commit {} processing took: {:?}", 
-                  commit_info.id.to_string()[..8].to_string(), commit_start.elapsed());
+                  &step.id.to_string()[..8], commit_start.elapsed());
     }
     
     log::info!("🕐 get_commit_history_chunk: Completed for '{}' - {} commits found from {} processed in {:?}", 
-             file_path, commits.len(), commits_processed, start_time.elapsed());
+             file_path, listed.len(), commits_processed, start_time.elapsed());
     log::debug!("🕐 get_commit_history_chunk: Commit iteration took: {:?}", commit_iteration_start.elapsed());
 
+    store_cached_history(repo, &cache_key, start_id, &listed);
+    let commits = listed.split_off(start_offset.min(listed.len()));
     Ok((commits, true)) // All commits loaded
 }
 
 pub fn get_commit_history_streaming<F>(
     repo: &Repository,
     file_path: &str,
+    revision: Option<&str>,
+    mode: HistoryMode,
     mut on_commit_found: F,
     cancellation_token: &tokio_util::sync::CancellationToken,
 ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>>
@@ -304,164 +365,1176 @@ where
 
     // Use gix to walk the commit history
     let head_setup_start = Instant::now();
-    let head_id = repo.head_id()?;
-    let commit_iter = repo.rev_walk([head_id]).all()?;
+    let start_id = resolve_revision(repo, revision)?;
+    let cache_key = history_cache_key(revision, normalized_path, mode);
+    if let Some(commits) = load_cached_history(repo, &cache_key, start_id) {
+        for commit in commits {
+            if cancellation_token.is_cancelled() {
+                break;
+            }
+            commits_found += 1;
+            if !on_commit_found(commit, commits_found) {
+                break;
+            }
+        }
+        log::info!("🕐 get_commit_history_streaming: Streamed {} cached commits for '{}' in {:?}",
+                 commits_found, file_path, start_time.elapsed());
+        return Ok(commits_found);
+    }
+    let mut walk = FileHistoryWalk::new(repo, start_id, normalized_path, mode)?;
     log::debug!("🕐 get_commit_history_streaming: Head setup took: {:?}", head_setup_start.elapsed());
 
     // Walk through commits and check if they modified the file
     let commit_iteration_start = Instant::now();
+    // Only a walk that ran to the end is cached
+    let mut found_commits = Vec::new();
+    let mut completed = true;
     
-    for commit_info in commit_iter {
+    while let Some(step) = walk.next_commit()? {
         // Check for cancellation at the start of each commit iteration
         if cancellation_token.is_cancelled() {
             log::info!("🕐 get_commit_history_streaming: Task cancelled, stopping at {} commits found from {} processed", commits_found, commits_processed);
+            completed = false;
             break;
         }
         
         let commit_start = Instant::now();
-        let commit_info = commit_info?;
-        let commit = repo.find_object(commit_info.id)?.try_into_commit()?;
         commits_processed += 1;
 
-        // Check if this commit actually modified the file by comparing with parent(s)
-        let modified_file = if commit.parent_ids().count() == 0 {
-            // This is the initial commit, check if file exists
-            let tree = commit.tree()?;
-            tree.lookup_entry_by_path(normalized_path)?
-                .is_some()
+        if step.modified {
+            let commit_info = step.commit_info()?;
+            found_commits.push(commit_info.clone());
+            
+            commits_found += 1;
+            
+            // Call the callback with the found commit
+            if !on_commit_found(commit_info, commits_found) {
+                log::info!("🕐 get_commit_history_streaming: Stopped early at {} commits by callback", commits_found);
+                completed = false;
+                break;
+            }
+        }
+        
+        log::debug!("🕐 get_commit_history_streaming: Commit {} processing took: {:?}", 
+                  &step.id.to_string()[..8], commit_start.elapsed());
+    }
+    
+    log::info!("🕐 get_commit_history_streaming: Completed for '{}' - {} commits found from {} processed in {:?}", 
+             file_path, commits_found, commits_processed, start_time.elapsed());
+    log::debug!("🕐 get_commit_history_streaming: Commit iteration took: {:?}", commit_iteration_start.elapsed());
+
+    if completed {
+        store_cached_history(repo, &cache_key, start_id, &found_commits);
+    }
+    Ok(commits_found)
+}
+
+/// A line range waiting to be examined at a commit
+struct PendingRange {
+    path: String,
+    blob_id: gix::ObjectId,
+    /// First and last line (0-based, inclusive) of the range in this commit's version
+    start_line: usize,
+    end_line: usize,
+}
+
+/// Stream the commits that changed a range of lines, like `git log -L`.
+///
+/// The range is given in `commit_hash`'s version of the file and tracked backwards through
+/// a `LineMapping` at each step, following renames. Commits are reported newest-first; a
+/// commit is skipped when the range passes through one of its parents unchanged.
+pub fn get_line_range_history_streaming<F>(
+    repo: &Repository,
+    file_path: &str,
+    commit_hash: &str,
+    start_line: usize,
+    end_line: usize,
+    mut on_commit_found: F,
+    cancellation_token: &tokio_util::sync::CancellationToken,
+) -> Result<usize, Box<dyn std::error::Error>>
+where
+    F: FnMut(CommitInfo, usize) -> bool, // Returns false to stop early
+{
+    let start_time = Instant::now();
+    log::debug!("🕐 get_line_range_history_streaming: Starting for '{}' lines {}-{} at {}",
+              file_path, start_line + 1, end_line + 1, &commit_hash[..8]);
+
+    // Normalize the file path by removing "./" prefix if present
+    let normalized_path = file_path.strip_prefix("./").unwrap_or(file_path);
+
+    let start_id = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let start_commit = repo.find_object(start_id)?.try_into_commit()?;
+    let start_blob_id = find_blob_id_at_commit(repo, start_id, normalized_path)?
+        .ok_or_else(|| format!("File '{}' not found in commit {}", normalized_path, commit_hash))?;
+
+    let mut pending: HashMap<gix::ObjectId, PendingRange> = HashMap::new();
+    let mut queue: BinaryHeap<(i64, gix::ObjectId)> = BinaryHeap::new();
+
+    pending.insert(
+        start_id,
+        PendingRange {
+            path: normalized_path.to_string(),
+            blob_id: start_blob_id,
+            start_line: start_line.min(end_line),
+            end_line: start_line.max(end_line),
+        },
+    );
+    queue.push((start_commit.time()?.seconds, start_id));
+
+    let mut commits_found = 0;
+    let mut commits_processed = 0;
+
+    while let Some((_, commit_id)) = queue.pop() {
+        if cancellation_token.is_cancelled() {
+            log::info!("🕐 get_line_range_history_streaming: Task cancelled, stopping at {} commits found from {} processed", commits_found, commits_processed);
+            break;
+        }
+        let Some(range) = pending.remove(&commit_id) else {
+            continue;
+        };
+        commits_processed += 1;
+
+        let commit = repo.find_object(commit_id)?.try_into_commit()?;
+        let tree = commit.tree()?;
+
+        // Work out which lines of each parent the range came from
+        let mut parent_ranges: Vec<(gix::ObjectId, i64, PendingRange, bool)> = Vec::new();
+        for (parent_index, parent_id) in commit.parent_ids().enumerate() {
+            let parent_id = parent_id.detach();
+            let parent_commit = repo.find_object(parent_id)?.try_into_commit()?;
+            let parent_tree = parent_commit.tree()?;
+
+            let parent_path = if parent_tree.lookup_entry_by_path(&range.path)?.is_some() {
+                range.path.clone()
+            } else if parent_index == 0 {
+                // Follow the file into the parent if this commit renamed it
+                let renamed_from = find_renames(repo, &parent_tree, &tree)
+                    .map_err(|e| e as Box<dyn std::error::Error>)?
+                    .into_iter()
+                    .find(|(_, destination)| *destination == range.path)
+                    .map(|(source, _)| source);
+                match renamed_from {
+                    Some(source) => source,
+                    None => continue,
+                }
+            } else {
+                continue;
+            };
+            let Some(parent_entry) = parent_tree.lookup_entry_by_path(&parent_path)? else {
+                continue;
+            };
+            let parent_blob_id = parent_entry.oid().to_owned();
+
+            let (parent_start, parent_end, unchanged) = if parent_blob_id == range.blob_id {
+                (range.start_line, range.end_line, true)
+            } else {
+                let mapping = crate::line_mapping::map_lines_between_blobs(repo, parent_blob_id, range.blob_id)?;
+
+                // The range was inserted by this commit, so it has no history in this parent
+                let Some((parent_start, parent_end)) = mapping.reverse_map_range(range.start_line, range.end_line) else {
+                    continue;
+                };
+                let unchanged = parent_end - parent_start == range.end_line - range.start_line
+                    && (range.start_line..=range.end_line).all(|line| mapping.reverse_map_line(line).is_some());
+                (parent_start, parent_end, unchanged)
+            };
+
+            parent_ranges.push((
+                parent_id,
+                parent_commit.time()?.seconds,
+                PendingRange {
+                    path: parent_path,
+                    blob_id: parent_blob_id,
+                    start_line: parent_start,
+                    end_line: parent_end,
+                },
+                unchanged,
+            ));
+        }
+
+        // Like `git log`, follow a parent that left the range untouched instead of reporting this commit
+        let untouched = parent_ranges.iter().position(|(_, _, _, unchanged)| *unchanged);
+        if let Some(index) = untouched {
+            let parent = parent_ranges.swap_remove(index);
+            parent_ranges = vec![parent];
         } else {
-            // Compare with parent commit(s) to see if file was modified
-            let mut file_modified = false;
-            let parent_comparison_start = Instant::now();
-
-            for parent_id in commit.parent_ids() {
-                let parent_commit = repo.find_object(parent_id)?.try_into_commit()?;
-                let current_tree = commit.tree()?;
-                let parent_tree = parent_commit.tree()?;
-
-                let current_entry =
-                    current_tree.lookup_entry_by_path(normalized_path)?;
-                let parent_entry =
-                    parent_tree.lookup_entry_by_path(normalized_path)?;
-
-                match (current_entry, parent_entry) {
-                    (Some(current), Some(parent)) => {
-                        // File exists in both - check if content changed
-                        if current.oid() != parent.oid() {
-                            file_modified = true;
-                            break;
-                        }
-                    }
-                    (Some(_), None) => {
-                        // File was added
-                        file_modified = true;
-                        break;
+            commits_found += 1;
+            let commit_info = build_commit_info(&commit, commit_id, &range.path).map_err(|e| e as Box<dyn std::error::Error>)?;
+            if !on_commit_found(commit_info, commits_found) {
+                log::info!("🕐 get_line_range_history_streaming: Stopped early at {} commits by callback", commits_found);
+                break;
+            }
+        }
+
+        for (parent_id, parent_time, parent_range, _) in parent_ranges {
+            match pending.get_mut(&parent_id) {
+                Some(existing) => {
+                    // Reached through several children - track the union of the ranges
+                    existing.start_line = existing.start_line.min(parent_range.start_line);
+                    existing.end_line = existing.end_line.max(parent_range.end_line);
+                }
+                None => {
+                    pending.insert(parent_id, parent_range);
+                    queue.push((parent_time, parent_id));
+                }
+            }
+        }
+    }
+
+    log::info!("🕐 get_line_range_history_streaming: Completed for '{}' lines {}-{} - {} commits found from {} processed in {:?}",
+             file_path, start_line + 1, end_line + 1, commits_found, commits_processed, start_time.elapsed());
+
+    Ok(commits_found)
+}
+
+/// Build the `CommitInfo` shown in the history list for a commit
+fn build_commit_info(
+    commit: &gix::Commit<'_>,
+    commit_id: gix::ObjectId,
+    file_path: &str,
+) -> Result<CommitInfo, Box<dyn std::error::Error + Send + Sync>> {
+    let commit_obj = commit.decode()?;
+    let author = &commit_obj.author;
+    let committer = &commit_obj.committer;
+    let (author_time, author_utc_offset) = parse_signature_time(author.time);
+    let (committer_time, committer_utc_offset) = parse_signature_time(committer.time);
+
+    // Format date as human-readable
+    let datetime = Local.timestamp_opt(author_time, 0).single().unwrap_or_else(Local::now);
+    let date = datetime.format("%Y-%m-%d %H:%M").to_string();
+
+    let commit_hash = commit_id.to_string();
+    let short_hash = commit_hash[..8].to_string();
+    let (subject, body, trailers) = split_commit_message(&commit_obj.message.to_string());
+
+    Ok(CommitInfo {
+        hash: commit_hash,
+        short_hash,
+        author: author.name.to_string(),
+        date,
+        subject,
+        file_path: Some(file_path.to_string()),
+        author_email: author.email.to_string(),
+        author_time,
+        author_utc_offset,
+        committer: committer.name.to_string(),
+        committer_email: committer.email.to_string(),
+        committer_time,
+        committer_utc_offset,
+        parent_ids: commit.parent_ids().map(|id| id.to_string()).collect(),
+        body,
+        trailers,
+        graph_children: Vec::new(),
+        files_changed: None,
+    })
+}
+
+/// Split a commit message into its subject line, body and trailers.
+///
+/// Trailers are recognized like git does: the last paragraph of the body counts as a trailer
+/// block only if every line in it has the form `Key: value`.
+fn split_commit_message(message: &str) -> (String, String, Vec<(String, String)>) {
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default().trim().to_string();
+    let rest: Vec<&str> = lines.collect();
+
+    let mut paragraphs: Vec<Vec<&str>> = rest
+        .split(|line| line.trim().is_empty())
+        .filter(|paragraph| !paragraph.is_empty())
+        .map(|paragraph| paragraph.to_vec())
+        .collect();
+
+    let trailers: Option<Vec<(String, String)>> = paragraphs
+        .last()
+        .and_then(|paragraph| paragraph.iter().map(|line| parse_trailer(line)).collect());
+    let trailers = match trailers {
+        Some(trailers) => {
+            paragraphs.pop();
+            trailers
+        }
+        None => Vec::new(),
+    };
+
+    let body = paragraphs
+        .iter()
+        .map(|paragraph| paragraph.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    (subject, body, trailers)
+}
+
+/// Parse a `Key: value` trailer line; keys are made of letters, digits and dashes
+fn parse_trailer(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once(':')?;
+    let value = value.trim();
+    let is_key = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !key.starts_with('-');
+    (is_key && !value.is_empty()).then(|| (key.to_string(), value.to_string()))
+}
+
+/// How a commit touched a file compared with its parent(s)
+struct FileChange {
+    modified: bool,
+    /// Path the file had in the parent when this commit renamed it
+    renamed_from: Option<String>,
+    /// Parents the history walk continues with
+    follow_parents: Vec<gix::ObjectId>,
+}
+
+/// Check whether `commit` added, modified, deleted or renamed the file at `path`, and which
+/// parents the walk should follow, according to the history simplification `mode`
+fn detect_file_change(
+    repo: &Repository,
+    graph: Option<&CommitGraph>,
+    commit: &CommitData,
+    path: &str,
+    mode: HistoryMode,
+) -> Result<FileChange, Box<dyn std::error::Error + Send + Sync>> {
+    let mut parent_ids = commit.parent_ids.clone();
+    if mode == HistoryMode::FirstParent {
+        parent_ids.truncate(1);
+    }
+
+    if parent_ids.is_empty() {
+        // This is the initial commit, check if file exists
+        let tree = repo.find_object(commit.tree_id)?.try_into_tree()?;
+        return Ok(FileChange {
+            modified: tree.lookup_entry_by_path(path)?.is_some(),
+            renamed_from: None,
+            follow_parents: Vec::new(),
+        });
+    }
+
+    // A parent is TREESAME when the file is identical (or equally absent) in it
+    let mut treesame_parent = None;
+    let mut differs = false;
+    let mut renamed_from = None;
+    for (parent_index, parent_id) in parent_ids.iter().enumerate() {
+        // The changed-path Bloom filter of a commit covers the diff to its first parent
+        let filtered_out = parent_index == 0
+            && graph
+                .zip(commit.graph_position)
+                .and_then(|(graph, position)| graph.maybe_changed(position, path))
+                == Some(false);
+
+        let same = filtered_out || {
+            let parent = commit_graph::commit_data(repo, graph, *parent_id)?;
+            match compare_path_in_trees(repo, commit.tree_id, parent.tree_id, path)? {
+                PathChange::Unchanged => true,
+                PathChange::Added => {
+                    // File was added, possibly by renaming it from another path.
+                    // Like `git log --follow`, renames are only followed along the first parent.
+                    if parent_index == 0 {
+                        let current_tree = repo.find_object(commit.tree_id)?.try_into_tree()?;
+                        let parent_tree = repo.find_object(parent.tree_id)?.try_into_tree()?;
+                        renamed_from = find_renames(repo, &parent_tree, &current_tree)?
+                            .into_iter()
+                            .find(|(_, destination)| destination == path)
+                            .map(|(source, _)| source);
                     }
-                    (None, Some(_)) => {
-                        // File was deleted
-                        file_modified = true;
-                        break;
+                    false
+                }
+                PathChange::Deleted | PathChange::Modified => false,
+            }
+        };
+
+        if same {
+            treesame_parent.get_or_insert(parent_index);
+        } else {
+            differs = true;
+        }
+        // Simplification only needs the first TREESAME parent, the other modes one that differs
+        let decided = match mode {
+            HistoryMode::Simplified => same,
+            HistoryMode::FullHistory | HistoryMode::FirstParent => !same,
+        };
+        if decided {
+            break;
+        }
+    }
+
+    let change = match mode {
+        HistoryMode::FullHistory | HistoryMode::FirstParent => FileChange {
+            modified: differs,
+            renamed_from,
+            follow_parents: parent_ids,
+        },
+        HistoryMode::Simplified => match treesame_parent {
+            // The file came unchanged from this parent, so the other sides of a merge are pruned
+            Some(index) => FileChange {
+                modified: false,
+                renamed_from: None,
+                follow_parents: vec![parent_ids[index]],
+            },
+            None => FileChange {
+                modified: true,
+                renamed_from,
+                follow_parents: parent_ids,
+            },
+        },
+    };
+    Ok(change)
+}
+
+/// How the entry at a path differs between a tree and the tree of a parent
+#[derive(Debug, PartialEq)]
+enum PathChange {
+    Unchanged,
+    Added,
+    Deleted,
+    Modified,
+}
+
+/// Compare the entry at `path` in two trees, descending only as far as their subtrees differ
+fn compare_path_in_trees(
+    repo: &Repository,
+    tree_id: gix::ObjectId,
+    parent_tree_id: gix::ObjectId,
+    path: &str,
+) -> Result<PathChange, Box<dyn std::error::Error + Send + Sync>> {
+    let components: Vec<&str> = path.split('/').collect();
+    let mut ids = (Some(tree_id), Some(parent_tree_id));
+    for (depth, name) in components.iter().enumerate() {
+        // Identical trees hold identical files, so there is no need to look further
+        if ids.0 == ids.1 {
+            return Ok(PathChange::Unchanged);
+        }
+        let is_last = depth + 1 == components.len();
+        ids = (
+            ids.0.map(|id| tree_entry_id(repo, id, name, is_last)).transpose()?.flatten(),
+            ids.1.map(|id| tree_entry_id(repo, id, name, is_last)).transpose()?.flatten(),
+        );
+    }
+
+    Ok(match ids {
+        (current, parent) if current == parent => PathChange::Unchanged,
+        (Some(_), None) => PathChange::Added,
+        (None, Some(_)) => PathChange::Deleted,
+        _ => PathChange::Modified,
+    })
+}
+
+/// Id of the entry `name` in a tree; unless `any_kind`, only subtrees are returned
+fn tree_entry_id(
+    repo: &Repository,
+    tree_id: gix::ObjectId,
+    name: &str,
+    any_kind: bool,
+) -> Result<Option<gix::ObjectId>, Box<dyn std::error::Error + Send + Sync>> {
+    let tree = repo.find_object(tree_id)?.try_into_tree()?;
+    Ok(tree
+        .find_entry(name)
+        .filter(|entry| any_kind || entry.mode().is_tree())
+        .map(|entry| entry.object_id()))
+}
+
+/// A commit visited while walking a file's history
+struct HistoryStep<'repo> {
+    repo: &'repo Repository,
+    id: gix::ObjectId,
+    /// Whether the commit is part of the file's history in the walk's mode
+    modified: bool,
+    /// Path of the file in this commit
+    file_path: String,
+    /// Whether the history is that of a directory
+    is_directory: bool,
+    /// Listed commits that reach this one through commits which are not listed, with the index
+    /// of the parent they reach it through
+    graph_children: Vec<(String, usize)>,
+}
+
+impl HistoryStep<'_> {
+    fn commit_info(&self) -> Result<CommitInfo, Box<dyn std::error::Error + Send + Sync>> {
+        let commit = self.repo.find_object(self.id)?.try_into_commit()?;
+        let mut info = build_commit_info(&commit, self.id, &self.file_path)?;
+        info.graph_children = self.graph_children.clone();
+        if self.is_directory {
+            info.files_changed = Some(count_files_changed_below(self.repo, &commit, &self.file_path)?);
+        }
+        Ok(info)
+    }
+}
+
+/// Walks the commits reachable from a start commit newest first, following only the parents
+/// the history simplification mode keeps, and reports for each whether it touched the file.
+/// Renames along the first parent are followed like `git log --follow`.
+///
+/// Commits covered by the commit-graph are read from it instead of being decoded, and its
+/// changed-path Bloom filters rule out most commits that did not touch the file.
+///
+/// As the walk goes by commit date, a commit dated no later than one of its ancestors may be
+/// reached after that ancestor was reported, which loses the graph link that passes through it.
+struct FileHistoryWalk<'repo> {
+    repo: &'repo Repository,
+    mode: HistoryMode,
+    graph: Option<CommitGraph>,
+    /// Commits to visit, newest first; like git, those with the same date in the order they were queued
+    queue: BinaryHeap<(i64, Reverse<usize>, gix::ObjectId)>,
+    queued: HashSet<gix::ObjectId>,
+    /// Data of the commits in the queue, read when they were queued
+    queued_commits: HashMap<gix::ObjectId, CommitData>,
+    /// Whether the path is a directory at the start commit, whose history covers all files below it
+    is_directory: bool,
+    /// Path of the file in each queued commit, which differs between the sides of a merge when
+    /// only one of them renamed it
+    queued_paths: HashMap<gix::ObjectId, String>,
+    /// For each queued commit, the listed commits it descends from without a listed commit in
+    /// between; this rewrites the parents of the history to the commits it shows
+    pending_children: HashMap<gix::ObjectId, Vec<(String, usize)>>,
+}
+
+impl<'repo> FileHistoryWalk<'repo> {
+    fn new(
+        repo: &'repo Repository,
+        start_id: gix::ObjectId,
+        path: &str,
+        mode: HistoryMode,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let is_directory = repo
+            .find_object(start_id)?
+            .try_into_commit()?
+            .tree()?
+            .lookup_entry_by_path(path)?
+            .is_some_and(|entry| entry.mode().is_tree());
+        let mut walk = Self {
+            repo,
+            mode,
+            graph: CommitGraph::open(repo),
+            queue: BinaryHeap::new(),
+            queued: HashSet::new(),
+            queued_commits: HashMap::new(),
+            is_directory,
+            queued_paths: HashMap::new(),
+            pending_children: HashMap::new(),
+        };
+        walk.enqueue(start_id, path)?;
+        Ok(walk)
+    }
+
+    /// Queue a commit, knowing the file as `path` in it; a commit reached through several children
+    /// keeps the path it was first queued with
+    fn enqueue(&mut self, id: gix::ObjectId, path: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.queued.insert(id) {
+            let commit = commit_graph::commit_data(self.repo, self.graph.as_ref(), id)?;
+            self.queue.push((commit.time, Reverse(self.queued.len()), id));
+            self.queued_commits.insert(id, commit);
+            self.queued_paths.insert(id, path.to_string());
+        }
+        Ok(())
+    }
+
+    fn next_commit(&mut self) -> Result<Option<HistoryStep<'repo>>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some((_, _, id)) = self.queue.pop() else {
+            return Ok(None);
+        };
+        let commit = match self.queued_commits.remove(&id) {
+            Some(commit) => commit,
+            None => commit_graph::commit_data(self.repo, self.graph.as_ref(), id)?,
+        };
+        let file_path = self.queued_paths.remove(&id).unwrap_or_default();
+
+        let parent_comparison_start = Instant::now();
+        let change = detect_file_change(self.repo, self.graph.as_ref(), &commit, &file_path, self.mode)?;
+        log::debug!("🕐 FileHistoryWalk: Parent comparison for commit {} took: {:?}",
+                  &id.to_string()[..8], parent_comparison_start.elapsed());
+
+        // The first parent knows the file under the name it had before this commit renamed it
+        if let Some(previous_path) = &change.renamed_from {
+            log::debug!("Following rename of '{}' from '{}' in commit {}",
+                      file_path, previous_path, &id.to_string()[..8]);
+        }
+
+        let children = self.pending_children.remove(&id).unwrap_or_default();
+        for (parent_index, parent_id) in change.follow_parents.into_iter().enumerate() {
+            // Commits that are not listed pass their own children on to their parents
+            let passed = if change.modified {
+                vec![(id.to_string(), parent_index)]
+            } else {
+                children.clone()
+            };
+            let parent_children = self.pending_children.entry(parent_id).or_default();
+            for child in passed {
+                if !parent_children.iter().any(|(hash, _)| *hash == child.0) {
+                    parent_children.push(child);
+                }
+            }
+            let parent_path = match &change.renamed_from {
+                Some(previous_path) if parent_index == 0 => previous_path,
+                _ => &file_path,
+            };
+            self.enqueue(parent_id, parent_path)?;
+        }
+
+        Ok(Some(HistoryStep {
+            repo: self.repo,
+            id,
+            modified: change.modified,
+            file_path,
+            is_directory: self.is_directory,
+            graph_children: if change.modified { children } else { Vec::new() },
+        }))
+    }
+}
+
+/// Number of files below `directory` that a commit changed compared to its first parent,
+/// counting a rename within the directory once
+fn count_files_changed_below(
+    repo: &Repository,
+    commit: &gix::Commit<'_>,
+    directory: &str,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let tree = subtree_or_empty(repo, commit.tree()?, directory)?;
+    let parent_tree = match commit.parent_ids().next() {
+        Some(parent_id) => {
+            let parent_tree = repo.find_object(parent_id)?.try_into_commit()?.tree()?;
+            subtree_or_empty(repo, parent_tree, directory)?
+        }
+        None => repo.empty_tree(),
+    };
+
+    let options = gix::diff::Options::default().with_rewrites(Some(gix::diff::Rewrites::default()));
+    let changes = repo.diff_tree_to_tree(&parent_tree, &tree, options)?;
+    Ok(changes.iter().filter(|change| !change.entry_mode().is_tree()).count())
+}
+
+/// The subtree at `directory`, or the empty tree if there is none
+fn subtree_or_empty<'repo>(
+    repo: &'repo Repository,
+    tree: gix::Tree<'repo>,
+    directory: &str,
+) -> Result<gix::Tree<'repo>, Box<dyn std::error::Error + Send + Sync>> {
+    match tree.lookup_entry_by_path(directory)? {
+        Some(entry) if entry.mode().is_tree() => Ok(entry.object()?.try_into_tree()?),
+        _ => Ok(repo.empty_tree()),
+    }
+}
+
+/// Find all renames between two trees as (source path, destination path) pairs,
+/// using Git's default similarity-based rename detection
+fn find_renames(
+    repo: &Repository,
+    old_tree: &gix::Tree<'_>,
+    new_tree: &gix::Tree<'_>,
+) -> Result<Vec<(String, String)>, Box<dyn std::error::Error + Send + Sync>> {
+    let options = gix::diff::Options::default().with_rewrites(Some(gix::diff::Rewrites::default()));
+    let changes = repo.diff_tree_to_tree(old_tree, new_tree, options)?;
+
+    Ok(changes
+        .into_iter()
+        .filter_map(|change| match change {
+            gix::object::tree::diff::ChangeDetached::Rewrite {
+                source_location,
+                location,
+                copy: false,
+                ..
+            } => Some((source_location.to_string(), location.to_string())),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Find the path a file had in `parent_commit` if `commit` renamed it to `file_path`
+pub fn find_rename_source(
+    repo: &Repository,
+    commit_hash: &str,
+    parent_commit: &str,
+    file_path: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let normalized_path = file_path.strip_prefix("./").unwrap_or(file_path);
+    let commit_id = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let parent_id = gix::ObjectId::from_hex(parent_commit.as_bytes())?;
+    let tree = repo.find_object(commit_id)?.try_into_commit()?.tree()?;
+    let parent_tree = repo.find_object(parent_id)?.try_into_commit()?.tree()?;
+
+    Ok(find_renames(repo, &parent_tree, &tree)?
+        .into_iter()
+        .find(|(_, destination)| destination == normalized_path)
+        .map(|(source, _)| source))
+}
+
+/// A set of lines whose origin is still unknown, waiting to be examined at a commit
+struct PendingBlame {
+    path: String,
+    blob_id: gix::ObjectId,
+    /// Pairs of (line number in this commit's version, line number in the blamed version)
+    lines: Vec<(usize, usize)>,
+}
+
+/// Compute line-level blame for a file as of the given commit.
+///
+/// Walks the history from `commit_hash` newest-first, carrying each unattributed line
+/// into the parent(s) through a `LineMapping` until it reaches the commit that introduced it.
+pub fn get_blame_at_commit(
+    repo: &Repository,
+    file_path: &str,
+    commit_hash: &str,
+) -> Result<Vec<BlameLine>, Box<dyn std::error::Error>> {
+    let start_time = Instant::now();
+    log::debug!("🕐 get_blame_at_commit: Starting for file: {} at commit: {}", file_path, &commit_hash[..8]);
+
+    // Normalize the file path by removing "./" prefix if present
+    let normalized_path = file_path.strip_prefix("./").unwrap_or(file_path);
+
+    // Commits never change, so neither does the blame of a file in one
+    let cache_key = format!("{}:{}", commit_hash, normalized_path);
+    if let Some(blame) = cache::load(repo, CacheKind::Blame, &cache_key) {
+        log::info!("🕐 get_blame_at_commit: Loaded cached blame for '{}' at {} in {:?}",
+                 file_path, &commit_hash[..8], start_time.elapsed());
+        return Ok(blame);
+    }
+
+    let start_id = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let start_commit = repo.find_object(start_id)?.try_into_commit()?;
+    let start_entry = start_commit
+        .tree()?
+        .lookup_entry_by_path(normalized_path)?
+        .ok_or_else(|| format!("File '{}' not found in commit {}", normalized_path, commit_hash))?;
+    let start_blob_id = start_entry.oid().to_owned();
+
+    let start_data = crate::memory_cache::blob_data(repo, start_blob_id)
+        .map_err(|e| -> Box<dyn std::error::Error> { e })?;
+    let line_count = String::from_utf8_lossy(&start_data).lines().count();
+
+    let mut result: Vec<Option<BlameLine>> = vec![None; line_count];
+    let mut pending: HashMap<gix::ObjectId, PendingBlame> = HashMap::new();
+    let mut queue: BinaryHeap<(i64, gix::ObjectId)> = BinaryHeap::new();
+
+    pending.insert(
+        start_id,
+        PendingBlame {
+            path: normalized_path.to_string(),
+            blob_id: start_blob_id,
+            lines: (0..line_count).map(|line| (line, line)).collect(),
+        },
+    );
+    queue.push((start_commit.time()?.seconds, start_id));
+
+    let mut commits_processed = 0;
+
+    // Process commits newest-first so that lines reaching the same commit through
+    // different paths are examined together
+    while let Some((_, commit_id)) = queue.pop() {
+        let Some(entry) = pending.remove(&commit_id) else {
+            continue;
+        };
+        commits_processed += 1;
+
+        let commit = repo.find_object(commit_id)?.try_into_commit()?;
+        let mut remaining = entry.lines;
+
+        for parent_id in commit.parent_ids() {
+            if remaining.is_empty() {
+                break;
+            }
+
+            let parent_id = parent_id.detach();
+            let parent_commit = repo.find_object(parent_id)?.try_into_commit()?;
+            let parent_tree = parent_commit.tree()?;
+            let (parent_path, parent_entry) = match parent_tree.lookup_entry_by_path(&entry.path)? {
+                Some(parent_entry) => (entry.path.clone(), parent_entry),
+                None => {
+                    // Follow the file into the parent if this commit renamed it
+                    let renamed_from = find_renames(repo, &parent_tree, &commit.tree()?)
+                        .map_err(|e| e as Box<dyn std::error::Error>)?
+                        .into_iter()
+                        .find(|(_, destination)| *destination == entry.path)
+                        .map(|(source, _)| source);
+                    let Some(source_path) = renamed_from else {
+                        continue;
+                    };
+                    match parent_tree.lookup_entry_by_path(&source_path)? {
+                        Some(parent_entry) => (source_path, parent_entry),
+                        None => continue,
                     }
-                    (None, None) => {
-                        // File doesn't exist in either - not modified
+                }
+            };
+            let parent_blob_id = parent_entry.oid().to_owned();
+
+            // Lines that survive into this parent are passed on; the rest stay with this commit
+            let passed: Vec<(usize, usize)> = if parent_blob_id == entry.blob_id {
+                std::mem::take(&mut remaining)
+            } else {
+                let mapping = crate::line_mapping::map_lines_between_blobs(repo, parent_blob_id, entry.blob_id)?;
+
+                let mut passed = Vec::new();
+                remaining.retain(|&(line, final_line)| match mapping.reverse_map_line(line) {
+                    Some(parent_line) => {
+                        passed.push((parent_line, final_line));
+                        false
                     }
+                    None => true,
+                });
+                passed
+            };
+
+            if passed.is_empty() {
+                continue;
+            }
+
+            match pending.get_mut(&parent_id) {
+                Some(parent_pending) => parent_pending.lines.extend(passed),
+                None => {
+                    pending.insert(
+                        parent_id,
+                        PendingBlame {
+                            path: parent_path,
+                            blob_id: parent_blob_id,
+                            lines: passed,
+                        },
+                    );
+                    queue.push((parent_commit.time()?.seconds, parent_id));
                 }
             }
-            log::debug!("🕐 get_commit_history_streaming: Parent comparison for commit {} took: {:?}", 
-                      commit_info.id.to_string()[..8].to_string(), parent_comparison_start.elapsed());
+        }
+
+        if remaining.is_empty() {
+            continue;
+        }
+
+        // Whatever could not be traced into a parent was introduced by this commit
+        let author = commit.author()?;
+        let author_name = author.name.to_string();
+        let timestamp = parse_signature_seconds(author.time);
+        let commit_hash = commit_id.to_string();
+        let short_hash = commit_hash[..8].to_string();
+
+        for (line, final_line) in remaining {
+            result[final_line] = Some(BlameLine {
+                commit_hash: commit_hash.clone(),
+                short_hash: short_hash.clone(),
+                author: author_name.clone(),
+                timestamp,
+                original_line_number: line,
+                original_path: entry.path.clone(),
+            });
+        }
+    }
+
+    log::info!("🕐 get_blame_at_commit: Completed for '{}' at {} - {} lines from {} commits in {:?}",
+             file_path, &commit_hash[..8], line_count, commits_processed, start_time.elapsed());
+
+    let blame: Vec<BlameLine> = result
+        .into_iter()
+        .map(|line| line.expect("every line is attributed once the walk completes"))
+        .collect();
+    cache::store(repo, CacheKind::Blame, &cache_key, &blame);
+    Ok(blame)
+}
+
+/// Parse the seconds part of a Git signature time ("timestamp timezone", e.g. "1751295482 -0400")
+fn parse_signature_seconds(time: &str) -> i64 {
+    parse_signature_time(time).0
+}
+
+/// Parse a Git signature time into seconds since the epoch and the timezone offset in seconds
+fn parse_signature_time(time: &str) -> (i64, i32) {
+    let mut parts = time.split_whitespace();
+    let seconds = parts
+        .next()
+        .and_then(|ts_str| ts_str.parse::<i64>().ok())
+        .unwrap_or(0);
+    let offset = parts
+        .next()
+        .filter(|tz| tz.len() == 5)
+        .and_then(|tz| {
+            let sign = match &tz[..1] {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let hours: i32 = tz[1..3].parse().ok()?;
+            let minutes: i32 = tz[3..5].parse().ok()?;
+            Some(sign * (hours * 3600 + minutes * 60))
+        })
+        .unwrap_or(0);
+    (seconds, offset)
+}
+
+pub fn get_file_content_at_commit(
+    repo: &Repository,
+    file_path: &str,
+    commit_hash: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    get_file_content_with_gix(repo, file_path, commit_hash)
+}
+
+/// The commit holding the last version of a file deleted by `commit_hash`: its first parent.
+/// None unless the commit deleted the file.
+pub fn get_commit_before_deletion(
+    repo: &Repository,
+    file_path: &str,
+    commit_hash: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let oid = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let commit = repo.find_object(oid)?.try_into_commit()?;
+    if commit.tree()?.lookup_entry_by_path(file_path)?.is_some() {
+        return Ok(None);
+    }
+    let Some(parent_id) = commit.parent_ids().next() else {
+        return Ok(None);
+    };
+    let parent_tree = repo.find_object(parent_id)?.try_into_commit()?.tree()?;
+    Ok(parent_tree
+        .lookup_entry_by_path(file_path)?
+        .map(|_| parent_id.to_string()))
+}
+
+pub fn get_parent_commit(
+    repo: &Repository,
+    commit_hash: &str,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let start_time = Instant::now();
+    log::debug!("🕐 get_parent_commit: Starting for commit: {}", &commit_hash[..8]);
+    
+    // Parse the commit hash
+    let oid = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let commit = repo.find_object(oid)?.try_into_commit()?;
+    
+    // Get first parent if it exists
+    let parent_id = commit.parent_ids().next();
+    
+    let result = parent_id.map(|id| id.to_string());
+    
+    log::debug!("🕐 get_parent_commit: Completed in {:?}, parent: {:?}", 
+               start_time.elapsed(), 
+               result.as_ref().map(|h| &h[..8]));
+    
+    Ok(result)
+}
+
+/// All parents of a commit in order; merge commits have more than one
+pub fn get_parent_commits(
+    repo: &Repository,
+    commit_hash: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let oid = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let commit = repo.find_object(oid)?.try_into_commit()?;
 
-            file_modified
+    Ok(commit.parent_ids().map(|id| id.to_string()).collect())
+}
+
+/// Files changed by a commit relative to its first parent (or all files for a root commit),
+/// with renames detected
+pub fn get_changed_files(
+    repo: &Repository,
+    commit_hash: &str,
+) -> Result<Vec<ChangedFile>, Box<dyn std::error::Error>> {
+    let oid = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
+    let commit = repo.find_object(oid)?.try_into_commit()?;
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent_ids().next() {
+        Some(parent_id) => repo.find_object(parent_id)?.try_into_commit()?.tree()?,
+        None => repo.empty_tree(),
+    };
+
+    let options = gix::diff::Options::default().with_rewrites(Some(gix::diff::Rewrites::default()));
+    let changes = repo.diff_tree_to_tree(&parent_tree, &tree, options)?;
+
+    use gix::object::tree::diff::ChangeDetached;
+    let mut changed_files: Vec<ChangedFile> = changes
+        .into_iter()
+        .filter_map(|change| {
+            let (path, kind, mode) = match change {
+                ChangeDetached::Addition { location, entry_mode, .. } => {
+                    (location, FileChangeKind::Added, entry_mode)
+                }
+                ChangeDetached::Deletion { location, entry_mode, .. } => {
+                    (location, FileChangeKind::Deleted, entry_mode)
+                }
+                ChangeDetached::Modification { location, entry_mode, .. } => {
+                    (location, FileChangeKind::Modified, entry_mode)
+                }
+                ChangeDetached::Rewrite { source_location, location, entry_mode, copy, .. } => {
+                    let kind = if copy {
+                        FileChangeKind::Added
+                    } else {
+                        FileChangeKind::Renamed(source_location.to_string())
+                    };
+                    (location, kind, entry_mode)
+                }
+            };
+            // Directories are implied by the files inside them
+            (!mode.is_tree()).then(|| ChangedFile {
+                path: path.to_string(),
+                kind,
+            })
+        })
+        .collect();
+    changed_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(changed_files)
+}
+
+/// A file that is gone at a revision, with the commit that deleted it
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletedFile {
+    pub path: String,
+    pub commit_hash: String,
+}
+
+/// Files that were deleted in the history of `revision` (HEAD if None) and do not exist at
+/// the revision, sorted by path. Each file comes with the newest commit that deleted it.
+///
+/// Renames are not detected, so a file renamed away is listed under its old name. Merge commits
+/// are skipped, as the diff to their first parent repeats the deletions made on the merged
+/// branch. When cancelled, the files found so far are returned.
+pub fn get_deleted_files(
+    repo: &Repository,
+    revision: Option<&str>,
+    cancellation_token: &tokio_util::sync::CancellationToken,
+) -> Result<Vec<DeletedFile>, Box<dyn std::error::Error + Send + Sync>> {
+    use gix::object::tree::diff::ChangeDetached;
+
+    let start_time = Instant::now();
+    let tip = resolve_revision(repo, revision)?;
+    let tip_tree = repo.find_object(tip)?.try_into_commit()?.tree()?;
+
+    let walk = repo
+        .rev_walk([tip])
+        .sorting(gix::revision::walk::Sorting::ByCommitTime(
+            gix::traverse::commit::simple::CommitTimeOrder::NewestFirst,
+        ))
+        .all()?;
+    let options = gix::diff::Options::default().with_rewrites(None);
+
+    let mut seen = HashSet::new();
+    let mut deleted_files = Vec::new();
+    let mut commits_processed = 0;
+    for info in walk {
+        if cancellation_token.is_cancelled() {
+            log::info!("🕐 get_deleted_files: Cancelled after {} commits", commits_processed);
+            break;
+        }
+        let info = info?;
+        commits_processed += 1;
+        let commit = info.object()?;
+        let mut parent_ids = commit.parent_ids();
+        let (Some(parent_id), None) = (parent_ids.next(), parent_ids.next()) else {
+            continue;
         };
+        let parent_tree = repo.find_object(parent_id)?.try_into_commit()?.tree()?;
 
-        if modified_file {
-            // Get commit metadata
-            let commit_obj = commit.decode()?;
-            let author = &commit_obj.author;
-            let message = commit_obj.message.to_string();
-
-            // Parse Git timestamp format: "timestamp timezone" (e.g., "1751295482 -0400")
-            let timestamp = match author.time.split_whitespace().next() {
-                Some(ts_str) => ts_str.parse::<i64>().unwrap_or(0),
-                None => 0,
-            };
-            
-            // Format date as human-readable
-            let datetime = Local.timestamp_opt(timestamp, 0).single().unwrap_or_else(|| Local::now());
-            let date = datetime.format("%Y-%m-%d %H:%M").to_string();
-
-            let commit_hash = commit_info.id.to_string();
-            let short_hash = commit_hash[..8].to_string();
-
-            let commit_info = CommitInfo {
-                hash: commit_hash,
-                short_hash,
-                author: author.name.to_string(),
-                date,
-                subject: message,
+        for change in repo.diff_tree_to_tree(&parent_tree, &commit.tree()?, options)? {
+            let ChangeDetached::Deletion { location, entry_mode, .. } = change else {
+                continue;
             };
-            
-            commits_found += 1;
-            
-            // Call the callback with the found commit
-            if !on_commit_found(commit_info, commits_found) {
-                log::info!("🕐 get_commit_history_streaming: Stopped early at {} commits by callback", commits_found);
-                break;
+            let path = location.to_string();
+            // An older deletion of the same path was undone before the newer one
+            if entry_mode.is_tree() || !seen.insert(path.clone()) {
+                continue;
+            }
+            if tip_tree.lookup_entry_by_path(&path)?.is_none() {
+                deleted_files.push(DeletedFile {
+                    path,
+                    commit_hash: info.id.to_string(),
+                });
             }
         }
-        
-        log::debug!("🕐 get_commit_history_streaming: Commit {} processing took: {:?}", 
-                  commit_info.id.to_string()[..8].to_string(), commit_start.elapsed());
     }
-    
-    log::info!("🕐 get_commit_history_streaming: Completed for '{}' - {} commits found from {} processed in {:?}", 
-             file_path, commits_found, commits_processed, start_time.elapsed());
-    log::debug!("🕐 get_commit_history_streaming: Commit iteration took: {:?}", commit_iteration_start.elapsed());
+    deleted_files.sort_by(|a, b| a.path.cmp(&b.path));
 
-    Ok(commits_found)
+    log::info!("🕐 get_deleted_files: Found {} deleted files in {} commits in {:?}",
+             deleted_files.len(), commits_processed, start_time.elapsed());
+    Ok(deleted_files)
 }
 
-pub fn get_blame_at_commit(
+/// Content of a file as staged in the index
+pub fn get_file_content_in_index(
     repo: &Repository,
     file_path: &str,
-    commit_hash: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    // TODO: Implement using gix::Repository::blame()
-    // For historical states, use blame.at_commit(<selected_commit_id>)
-
-    // For now, return placeholder
-    let _ = (repo, file_path, commit_hash);
-    Ok("Mock blame data".to_string())
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let index = repo.index_or_empty()?;
+    let entry = index
+        .entry_by_path(file_path.into())
+        .ok_or_else(|| format!("File '{}' not found in the index", file_path))?;
+    let blob = repo.find_object(entry.id)?.try_into_blob()?;
+
+    Ok(String::from_utf8_lossy(&blob.data)
+        .lines()
+        .map(|line| line.to_string())
+        .collect())
 }
 
-pub fn get_file_content_at_commit(
+/// Content of a file as it currently is in the working tree
+pub fn get_file_content_in_worktree(
     repo: &Repository,
     file_path: &str,
-    commit_hash: &str,
 ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-    get_file_content_with_gix(repo, file_path, commit_hash)
+    let path = repo
+        .workdir_path(file_path)
+        .ok_or("Repository has no working tree")?;
+    let content = std::fs::read(&path)
+        .map_err(|e| format!("File '{}' not found in the working tree: {}", file_path, e))?;
+
+    Ok(String::from_utf8_lossy(&content)
+        .lines()
+        .map(|line| line.to_string())
+        .collect())
 }
 
-pub fn get_parent_commit(
-    repo: &Repository,
-    commit_hash: &str,
-) -> Result<Option<String>, Box<dyn std::error::Error>> {
+/// Files that differ between HEAD, the index and the working tree, like the two columns of
+/// `git status --short`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorktreeStatus {
+    /// Changes staged in the index: 'A' added, 'M' modified or 'D' deleted
+    pub staged: HashMap<PathBuf, char>,
+    /// Changes in the working tree that are not staged: 'M' modified, 'D' deleted,
+    /// 'U' unmerged or '?' untracked
+    pub unstaged: HashMap<PathBuf, char>,
+}
+
+/// Compare HEAD with the index and the index with the working tree.
+/// Repositories without a working tree have no changes.
+pub fn get_worktree_status(repo: &Repository) -> Result<WorktreeStatus, Box<dyn std::error::Error + Send + Sync>> {
     let start_time = Instant::now();
-    log::debug!("🕐 get_parent_commit: Starting for commit: {}", &commit_hash[..8]);
-    
-    // Parse the commit hash
-    let oid = gix::ObjectId::from_hex(commit_hash.as_bytes())?;
-    let commit = repo.find_object(oid)?.try_into_commit()?;
-    
-    // Get first parent if it exists
-    let parent_id = commit.parent_ids().next();
-    
-    let result = parent_id.map(|id| id.to_string());
-    
-    log::debug!("🕐 get_parent_commit: Completed in {:?}, parent: {:?}", 
-               start_time.elapsed(), 
-               result.as_ref().map(|h| &h[..8]));
-    
-    Ok(result)
+    let mut status = WorktreeStatus::default();
+    if repo.workdir().is_none() {
+        return Ok(status);
+    }
+
+    let items = repo
+        .status(gix::progress::Discard)?
+        .untracked_files(gix::status::UntrackedFiles::Files)
+        .tree_index_track_renames(gix::status::tree_index::TrackRenames::Disabled)
+        .index_worktree_rewrites(None)
+        .into_iter(Vec::new())?;
+
+    for item in items {
+        match item? {
+            gix::status::Item::TreeIndex(change) => {
+                use gix::diff::index::ChangeRef;
+                let status_char = match change {
+                    ChangeRef::Addition { .. } => 'A',
+                    ChangeRef::Deletion { .. } => 'D',
+                    ChangeRef::Modification { .. } | ChangeRef::Rewrite { .. } => 'M',
+                };
+                let (location, ..) = change.fields();
+                status.staged.insert(gix::path::from_bstr(location).into_owned(), status_char);
+            }
+            gix::status::Item::IndexWorktree(item) => {
+                use gix::status::index_worktree::iter::Summary;
+                let status_char = match item.summary() {
+                    Some(Summary::Added) => '?',
+                    Some(Summary::Removed) => 'D',
+                    Some(Summary::Conflict) => 'U',
+                    Some(Summary::Modified | Summary::TypeChange | Summary::IntentToAdd) => 'M',
+                    // Renames and copies are not tracked; entries that only need a refresh are unchanged
+                    Some(Summary::Renamed | Summary::Copied) | None => continue,
+                };
+                status.unstaged.insert(gix::path::from_bstr(item.rela_path()).into_owned(), status_char);
+            }
+        }
+    }
+
+    log::debug!(
+        "🕐 get_worktree_status: {} staged and {} unstaged changes in {:?}",
+        status.staged.len(),
+        status.unstaged.len(),
+        start_time.elapsed()
+    );
+    Ok(status)
 }
 
 pub fn get_file_content_at_head(
@@ -509,8 +1582,8 @@ fn get_file_content_with_gix(
 
     // Get the blob content
     let blob_start = Instant::now();
-    let blob = file_entry.object()?.try_into_blob()?;
-    let content_bytes = blob.data.clone();
+    let content_bytes = crate::memory_cache::blob_data(repo, file_entry.object_id())
+        .map_err(|e| e.to_string())?;
     log::debug!("🕐 get_file_content_with_gix: Blob retrieval took: {:?}, size: {} bytes", 
               blob_start.elapsed(), content_bytes.len());
 
@@ -527,48 +1600,226 @@ fn get_file_content_with_gix(
     Ok(lines)
 }
 
+/// Find the next commit after `current_commit` (walking toward `revision`, HEAD if None) that
+/// modifies the given line.
+///
+/// The line is carried forward through every commit on the way using a `LineMapping`.
+/// Returns the hash of the commit that changed the line together with the best matching
+/// line number (0-based) in that commit's version of the file.
 pub fn find_next_change_for_line(
     repo: &Repository,
     file_path: &str,
+    revision: Option<&str>,
     current_commit: &str,
     line_number: usize,
-) -> Result<Option<String>, Box<dyn std::error::Error>> {
-    // TODO: Implement the complex next change algorithm
-    // This is the most complex operation and should:
-    // 1. Create a rev-walk from HEAD to current_commit
-    // 2. Iterate through commits chronologically forward from current_commit
-    // 3. For each commit, diff with its parent
-    // 4. Use the `similar` crate to analyze if the target line was modified
-    // 5. Return the first commit where the line was changed
-
-    // For now, return None as placeholder
-    let _ = (repo, file_path, current_commit, line_number);
+) -> Result<Option<(String, usize)>, Box<dyn std::error::Error>> {
+    let start_time = Instant::now();
+    log::debug!("🕐 find_next_change_for_line: Starting for '{}' line {} from {}", file_path, line_number, &current_commit[..8]);
+
+    // Normalize the file path by removing "./" prefix if present
+    let normalized_path = file_path.strip_prefix("./").unwrap_or(file_path);
+
+    let current_id = gix::ObjectId::from_hex(current_commit.as_bytes())?;
+    let start_id = resolve_revision(repo, revision).map_err(|e| e as Box<dyn std::error::Error>)?;
+    let Some(path) = find_path_from_ancestor(repo, current_id, start_id)? else {
+        log::info!("🕐 find_next_change_for_line: {} is not an ancestor of {}",
+                 &current_commit[..8], revision.unwrap_or("HEAD"));
+        return Ok(None);
+    };
+
+    let mut blob_id = find_blob_id_at_commit(repo, current_id, normalized_path)?
+        .ok_or_else(|| format!("File '{}' not found in commit {}", normalized_path, current_commit))?;
+    let mut line = line_number;
+    let mut current_path = normalized_path.to_string();
+    let mut previous_id = current_id;
+
+    for commit_id in path {
+        let next_blob_id = match find_blob_id_at_commit(repo, commit_id, &current_path)? {
+            Some(next_blob_id) => next_blob_id,
+            None => {
+                // The file may have been renamed rather than deleted
+                let previous_tree = repo.find_object(previous_id)?.try_into_commit()?.tree()?;
+                let tree = repo.find_object(commit_id)?.try_into_commit()?.tree()?;
+                let renamed_to = find_renames(repo, &previous_tree, &tree)
+                    .map_err(|e| e as Box<dyn std::error::Error>)?
+                    .into_iter()
+                    .find(|(source, _)| *source == current_path)
+                    .map(|(_, destination)| destination);
+                let next_blob_id = match &renamed_to {
+                    Some(destination) => find_blob_id_at_commit(repo, commit_id, destination)?,
+                    None => None,
+                };
+                let (Some(destination), Some(next_blob_id)) = (renamed_to, next_blob_id) else {
+                    // Deleting the file changes every line in it
+                    log::info!("🕐 find_next_change_for_line: File deleted in {} after {:?}", &commit_id.to_string()[..8], start_time.elapsed());
+                    return Ok(Some((commit_id.to_string(), 0)));
+                };
+                current_path = destination;
+                next_blob_id
+            }
+        };
+        previous_id = commit_id;
+        if next_blob_id == blob_id {
+            continue;
+        }
+
+        let mapping = crate::line_mapping::map_lines_between_blobs(repo, blob_id, next_blob_id)?;
+
+        match mapping.map_line(line) {
+            Some(mapped_line) => line = mapped_line,
+            None => {
+                // The line was modified or removed here - land where the change happened
+                let landing_line = mapping.map_line_to_change_position(line);
+                log::info!("🕐 find_next_change_for_line: Line {} changed in {} (now line {}) after {:?}",
+                         line, &commit_id.to_string()[..8], landing_line, start_time.elapsed());
+                return Ok(Some((commit_id.to_string(), landing_line)));
+            }
+        }
+        blob_id = next_blob_id;
+    }
+
+    log::info!("🕐 find_next_change_for_line: No later change to line {} found in {:?}", line_number, start_time.elapsed());
     Ok(None)
 }
 
-/// Walk the Git tree from HEAD commit and return all files and directories
+/// Find the previous commit that modified the given line as seen at `current_commit`.
+///
+/// This is the commit blame attributes the line to. If the line was last modified by
+/// `current_commit` itself, the search continues from its parent so that repeated
+/// lookups keep stepping back through the line's history.
+/// Returns the commit hash and the line number (0-based) in that commit's version of the file.
+pub fn find_previous_change_for_line(
+    repo: &Repository,
+    file_path: &str,
+    current_commit: &str,
+    line_number: usize,
+) -> Result<Option<(String, usize)>, Box<dyn std::error::Error>> {
+    let start_time = Instant::now();
+    log::debug!("🕐 find_previous_change_for_line: Starting for '{}' line {} from {}", file_path, line_number, &current_commit[..8]);
+
+    let blame = get_blame_at_commit(repo, file_path, current_commit)?;
+    let Some(blame_line) = blame.get(line_number) else {
+        return Ok(None);
+    };
+
+    if blame_line.commit_hash != current_commit {
+        log::info!("🕐 find_previous_change_for_line: Line {} last changed in {} after {:?}",
+                 line_number, blame_line.short_hash, start_time.elapsed());
+        return Ok(Some((blame_line.commit_hash.clone(), blame_line.original_line_number)));
+    }
+
+    // The line was changed by the current commit - continue from the closest line in its parent
+    let Some(parent_commit) = get_parent_commit(repo, current_commit)? else {
+        return Ok(None);
+    };
+    let normalized_path = file_path.strip_prefix("./").unwrap_or(file_path);
+    let current_id = gix::ObjectId::from_hex(current_commit.as_bytes())?;
+    let parent_id = gix::ObjectId::from_hex(parent_commit.as_bytes())?;
+    // The file may have had another name in the parent if the current commit renamed it
+    let parent_path = match find_blob_id_at_commit(repo, parent_id, normalized_path)? {
+        Some(_) => normalized_path.to_string(),
+        None => find_rename_source(repo, current_commit, &parent_commit, normalized_path)
+            .map_err(|e| e as Box<dyn std::error::Error>)?
+            .unwrap_or_else(|| normalized_path.to_string()),
+    };
+    let Some(parent_blob_id) = find_blob_id_at_commit(repo, parent_id, &parent_path)? else {
+        // The file was added by the current commit, so there is nothing before it
+        return Ok(None);
+    };
+    let current_blob_id = find_blob_id_at_commit(repo, current_id, normalized_path)?
+        .ok_or_else(|| format!("File '{}' not found in commit {}", normalized_path, current_commit))?;
+
+    let mapping = crate::line_mapping::map_lines_between_blobs(repo, current_blob_id, parent_blob_id)?;
+    let parent_line = mapping.map_line_to_change_position(line_number);
+
+    let parent_blame = get_blame_at_commit(repo, &parent_path, &parent_commit)?;
+    let result = parent_blame
+        .get(parent_line)
+        .map(|line| (line.commit_hash.clone(), line.original_line_number));
+
+    log::info!("🕐 find_previous_change_for_line: Completed for line {} via parent {} in {:?}",
+             line_number, &parent_commit[..8], start_time.elapsed());
+    Ok(result)
+}
+
+/// Look up the blob id of a file in a commit's tree
+fn find_blob_id_at_commit(
+    repo: &Repository,
+    commit_id: gix::ObjectId,
+    path: &str,
+) -> Result<Option<gix::ObjectId>, Box<dyn std::error::Error>> {
+    let tree = repo.find_object(commit_id)?.try_into_commit()?.tree()?;
+    Ok(tree.lookup_entry_by_path(path)?.map(|entry| entry.oid().to_owned()))
+}
+
+/// Find a chain of commits leading from `ancestor` (exclusive) to `descendant` (inclusive),
+/// ordered from oldest to newest. Returns None if `ancestor` is not reachable from `descendant`.
+fn find_path_from_ancestor(
+    repo: &Repository,
+    ancestor: gix::ObjectId,
+    descendant: gix::ObjectId,
+) -> Result<Option<Vec<gix::ObjectId>>, Box<dyn std::error::Error>> {
+    // Breadth-first search from the descendant, remembering through which child each commit was reached
+    let mut child_of: HashMap<gix::ObjectId, gix::ObjectId> = HashMap::new();
+    let mut queue = std::collections::VecDeque::from([descendant]);
+    let mut found = ancestor == descendant;
+
+    while let Some(commit_id) = queue.pop_front() {
+        if commit_id == ancestor {
+            found = true;
+            break;
+        }
+
+        let commit = repo.find_object(commit_id)?.try_into_commit()?;
+        for parent_id in commit.parent_ids() {
+            let parent_id = parent_id.detach();
+            if parent_id != descendant && !child_of.contains_key(&parent_id) {
+                child_of.insert(parent_id, commit_id);
+                queue.push_back(parent_id);
+            }
+        }
+    }
+
+    if !found {
+        return Ok(None);
+    }
+
+    let mut path = Vec::new();
+    let mut commit_id = ancestor;
+    while commit_id != descendant {
+        commit_id = child_of[&commit_id];
+        path.push(commit_id);
+    }
+    Ok(Some(path))
+}
+
+/// Walk the Git tree of `revision` (HEAD if None) and return all files and directories
 pub fn get_git_tree_entries(
     repo: &Repository,
+    revision: Option<&str>,
 ) -> Result<Vec<GitTreeEntry>, Box<dyn std::error::Error + Send + Sync>> {
     let start_time = Instant::now();
     log::info!("🕐 get_git_tree_entries: Starting Git tree traversal");
     
     let mut entries = Vec::new();
     
-    // Get HEAD commit
+    // Get the commit the tree is taken from
     let head_setup_start = Instant::now();
-    let head_id = match repo.head_id() {
-        Ok(id) => id,
-        Err(_) => {
-            // No commits yet, return empty tree
-            log::debug!("🕐 get_git_tree_entries: No commits found, returning empty tree");
-            return Ok(entries);
-        }
+    let start_id = match revision {
+        Some(_) => resolve_revision(repo, revision)?,
+        None => match repo.head_id() {
+            Ok(id) => id.detach(),
+            Err(_) => {
+                // No commits yet, return empty tree
+                log::debug!("🕐 get_git_tree_entries: No commits found, returning empty tree");
+                return Ok(entries);
+            }
+        },
     };
-    let commit = repo.find_object(head_id)?.try_into_commit()?;
-    log::debug!("🕐 get_git_tree_entries: HEAD resolution took: {:?}", head_setup_start.elapsed());
+    let commit = repo.find_object(start_id)?.try_into_commit()?;
+    log::debug!("🕐 get_git_tree_entries: Revision resolution took: {:?}", head_setup_start.elapsed());
     
-    // Get the tree from HEAD commit
+    // Get the tree from the commit
     let tree_start = Instant::now();
     let tree = commit.tree()?;
     log::debug!("🕐 get_git_tree_entries: Tree retrieval took: {:?}", tree_start.elapsed());
@@ -617,7 +1868,7 @@ fn walk_git_tree_recursive(
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
     #[test]
@@ -632,7 +1883,7 @@ mod tests {
         // Test getting commit history for a file that exists in this repo
         let repo = open_repository(".").expect("Should be able to open repository");
         let commits =
-            get_commit_history_for_file(&repo, "src/main.rs").expect("Should get commit history");
+            get_commit_history_for_file(&repo, "src/main.rs", None, HistoryMode::default()).expect("Should get commit history");
 
         // We should have at least one commit for main.rs
         assert!(!commits.is_empty(), "Should have commits for main.rs");
@@ -665,9 +1916,9 @@ mod tests {
 
         // Test with README.md which should have fewer commits than src/main.rs
         let readme_commits =
-            get_commit_history_for_file(&repo, "README.md").expect("Should get README.md history");
+            get_commit_history_for_file(&repo, "README.md", None, HistoryMode::default()).expect("Should get README.md history");
         let main_commits =
-            get_commit_history_for_file(&repo, "src/main.rs").expect("Should get main.rs history");
+            get_commit_history_for_file(&repo, "src/main.rs", None, HistoryMode::default()).expect("Should get main.rs history");
 
         // Uncomment for debugging:
         // println!("README.md has {} commits", readme_commits.len());
@@ -701,7 +1952,7 @@ mod tests {
     fn test_commit_history_for_nonexistent_file() {
         // Test that we get empty history for a file that doesn't exist
         let repo = open_repository(".").expect("Should be able to open repository");
-        let commits = get_commit_history_for_file(&repo, "nonexistent/file.txt")
+        let commits = get_commit_history_for_file(&repo, "nonexistent/file.txt", None, HistoryMode::default())
             .expect("Should handle nonexistent file");
 
         assert!(
@@ -714,7 +1965,7 @@ mod tests {
     fn test_gix_author_time_parsing() {
         // Test that we can properly parse gix author time format
         let repo = open_repository(".").expect("Should be able to open repository");
-        let commits = get_commit_history_for_file(&repo, "src/main.rs").expect("Should get commit history");
+        let commits = get_commit_history_for_file(&repo, "src/main.rs", None, HistoryMode::default()).expect("Should get commit history");
         
         if !commits.is_empty() {
             let commit = &commits[0];
@@ -756,7 +2007,7 @@ mod tests {
 
         // First get some commits for main.rs to test with
         let commits =
-            get_commit_history_for_file(&repo, "src/main.rs").expect("Should get commit history");
+            get_commit_history_for_file(&repo, "src/main.rs", None, HistoryMode::default()).expect("Should get commit history");
 
         if !commits.is_empty() {
             let latest_commit = &commits[0];
@@ -788,4 +2039,920 @@ mod tests {
             // println!("No commits found for src/main.rs, skipping content test");
         }
     }
+
+    pub(crate) fn run_git(repo_path: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(repo_path)
+            .output()
+            .expect("Failed to run git");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// Initialize an empty repository on branch main that commits can be made in
+    pub(crate) fn init_test_repo(temp_dir: &tempfile::TempDir) -> &Path {
+        let repo_path = temp_dir.path();
+        run_git(repo_path, &["init", "-q", "-b", "main"]);
+        run_git(repo_path, &["config", "user.name", "Test User"]);
+        run_git(repo_path, &["config", "user.email", "test@example.com"]);
+        repo_path
+    }
+
+    fn create_blame_test_repo(temp_dir: &tempfile::TempDir) -> (String, String) {
+        let repo_path = init_test_repo(temp_dir);
+
+        std::fs::write(repo_path.join("file.txt"), "a\nb\nc\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "First commit"]);
+        let first = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        std::fs::write(repo_path.join("file.txt"), "a\nB\nc\nd\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Second commit"]);
+        let second = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        (first, second)
+    }
+
+    #[test]
+    fn test_get_blame_at_commit() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        let blame = get_blame_at_commit(&repo, "file.txt", &second).expect("Should compute blame");
+        assert_eq!(blame.len(), 4);
+
+        // Unchanged lines are attributed to the commit that introduced them
+        assert_eq!(blame[0].commit_hash, first);
+        assert_eq!(blame[0].original_line_number, 0);
+        assert_eq!(blame[2].commit_hash, first);
+        assert_eq!(blame[2].original_line_number, 2);
+
+        // Modified and appended lines belong to the second commit
+        assert_eq!(blame[1].commit_hash, second);
+        assert_eq!(blame[1].original_line_number, 1);
+        assert_eq!(blame[3].commit_hash, second);
+        assert_eq!(blame[3].original_line_number, 3);
+
+        assert_eq!(blame[1].author, "Test User");
+        assert_eq!(blame[1].short_hash, &second[..8]);
+        assert!(blame[1].timestamp > 0);
+        assert_eq!(blame[0].original_path, "file.txt");
+
+        // Blaming the first commit attributes everything to it
+        let blame = get_blame_at_commit(&repo, "file.txt", &first).expect("Should compute blame");
+        assert_eq!(blame.len(), 3);
+        assert!(blame.iter().all(|line| line.commit_hash == first));
+    }
+
+    #[test]
+    fn test_find_next_change_for_line() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        // "b" is replaced by "B" in the second commit
+        let next = find_next_change_for_line(&repo, "file.txt", None, &first, 1).unwrap();
+        assert_eq!(next, Some((second.clone(), 1)));
+
+        // "a" never changes again
+        let next = find_next_change_for_line(&repo, "file.txt", None, &first, 0).unwrap();
+        assert_eq!(next, None);
+
+        // Nothing comes after HEAD
+        let next = find_next_change_for_line(&repo, "file.txt", None, &second, 1).unwrap();
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn test_resolve_revision() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        run_git(temp_dir.path(), &["tag", "-a", "v1", "-m", "Version 1", &first]);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        assert_eq!(resolve_revision(&repo, None).unwrap().to_string(), second);
+        assert_eq!(resolve_revision(&repo, Some("HEAD~1")).unwrap().to_string(), first);
+        // Annotated tags are peeled to their commit
+        assert_eq!(resolve_revision(&repo, Some("v1")).unwrap().to_string(), first);
+
+        let error = resolve_revision(&repo, Some("no-such-branch")).unwrap_err();
+        assert!(error.to_string().contains("Unknown revision 'no-such-branch'"));
+    }
+
+    #[test]
+    fn test_list_refs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+        run_git(repo_path, &["branch", "-M", "main"]);
+        run_git(repo_path, &["branch", "old", &first]);
+        run_git(repo_path, &["tag", "-a", "v1", "-m", "Version 1", &first]);
+        run_git(repo_path, &["update-ref", "refs/remotes/origin/main", &second]);
+        run_git(repo_path, &["symbolic-ref", "refs/remotes/origin/HEAD", "refs/remotes/origin/main"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+
+        let refs = list_refs(&repo).unwrap();
+        let summary: Vec<_> = refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.kind, r.commit_hash.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("HEAD", RefKind::Head, second.as_str()),
+                ("main", RefKind::Branch, second.as_str()),
+                ("old", RefKind::Branch, first.as_str()),
+                ("origin/main", RefKind::RemoteBranch, second.as_str()),
+                // Annotated tags are peeled to their commit
+                ("v1", RefKind::Tag, first.as_str()),
+            ]
+        );
+        assert!(refs.iter().all(|r| r.commit_time > 0 && !r.date.is_empty()));
+    }
+
+    #[test]
+    fn test_full_ref_names_resolve_unambiguously() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+        // A branch and a tag share a name, and a local branch looks like a remote one
+        run_git(repo_path, &["branch", "v1", &second]);
+        run_git(repo_path, &["tag", "v1", &first]);
+        run_git(repo_path, &["branch", "origin/main", &first]);
+        run_git(repo_path, &["update-ref", "refs/remotes/origin/main", &second]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+
+        let refs = list_refs(&repo).unwrap();
+        for r in &refs {
+            let resolved = resolve_revision(&repo, Some(&r.full_name)).unwrap().to_string();
+            assert_eq!(resolved, r.commit_hash, "{} resolves to its own commit", r.full_name);
+        }
+        let full_names: Vec<_> = refs.iter().map(|r| (r.name.as_str(), r.full_name.as_str())).collect();
+        assert!(full_names.contains(&("v1", "refs/heads/v1")));
+        assert!(full_names.contains(&("v1", "refs/tags/v1")));
+        assert!(full_names.contains(&("origin/main", "refs/remotes/origin/main")));
+    }
+
+    #[test]
+    fn test_find_refs_containing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+        run_git(repo_path, &["branch", "-M", "main"]);
+        run_git(repo_path, &["branch", "old", &first]);
+        run_git(repo_path, &["tag", "-a", "v1", "-m", "Version 1", &first]);
+        run_git(repo_path, &["tag", "v2", &second]);
+
+        // A side branch off the first commit does not contain the second
+        run_git(repo_path, &["checkout", "-q", "-b", "side", &first]);
+        std::fs::write(repo_path.join("side.txt"), "side\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Side commit"]);
+        run_git(repo_path, &["checkout", "-q", "main"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+
+        let names = |refs: &[RefInfo]| -> Vec<String> { refs.iter().map(|r| r.name.clone()).collect() };
+        let token = tokio_util::sync::CancellationToken::new();
+
+        let containing = find_refs_containing(&repo, &first, &token).unwrap();
+        let mut branches = names(&containing.branches);
+        branches.sort();
+        assert_eq!(branches, vec!["main", "old", "side"]);
+        assert_eq!(names(&containing.tags), vec!["v1", "v2"]);
+        assert_eq!(containing.earliest_tag().map(|t| t.name.as_str()), Some("v1"));
+
+        let containing = find_refs_containing(&repo, &second, &token).unwrap();
+        assert_eq!(names(&containing.branches), vec!["main"]);
+        assert_eq!(names(&containing.tags), vec!["v2"]);
+        assert_eq!(containing.earliest_tag().map(|t| t.name.as_str()), Some("v2"));
+    }
+
+    #[test]
+    fn test_find_refs_containing_with_skewed_commit_dates() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, _) = create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+        run_git(repo_path, &["tag", "v1", &first]);
+
+        // Commits made on a machine whose clock was years behind
+        for message in ["Skewed commit", "Another skewed commit"] {
+            std::fs::write(repo_path.join("skewed.txt"), message).unwrap();
+            run_git(repo_path, &["add", "."]);
+            let output = std::process::Command::new("git")
+                .args(["commit", "-m", message])
+                .env("GIT_COMMITTER_DATE", "2001-01-01T00:00:00Z")
+                .env("GIT_AUTHOR_DATE", "2001-01-01T00:00:00Z")
+                .current_dir(repo_path)
+                .output()
+                .expect("Failed to run git");
+            assert!(output.status.success());
+        }
+        let skewed = run_git(repo_path, &["rev-parse", "HEAD~1"]);
+        run_git(repo_path, &["tag", "v2"]);
+        let token = tokio_util::sync::CancellationToken::new();
+        let tag_names = |containing: &ContainingRefs| -> Vec<String> {
+            // Sorted by name, as the tags' dates are skewed too
+            let mut names: Vec<String> = containing.tags.iter().map(|t| t.name.clone()).collect();
+            names.sort();
+            names
+        };
+
+        // Both with and without generation numbers from a commit-graph
+        for write_commit_graph in [false, true] {
+            if write_commit_graph {
+                run_git(repo_path, &["commit-graph", "write", "--reachable"]);
+            }
+            let repo = open_repository(repo_path).expect("Should open test repository");
+
+            let containing = find_refs_containing(&repo, &first, &token).unwrap();
+            assert_eq!(tag_names(&containing), vec!["v1", "v2"]);
+
+            let containing = find_refs_containing(&repo, &skewed, &token).unwrap();
+            assert_eq!(tag_names(&containing), vec!["v2"]);
+        }
+
+        // A cancelled search gives no answer rather than an incomplete one
+        token.cancel();
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        assert!(find_refs_containing(&repo, &first, &token).is_err());
+    }
+
+    #[test]
+    fn test_history_modes_around_merge() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = init_test_repo(&temp_dir);
+
+        std::fs::write(repo_path.join("file.txt"), "a\n").unwrap();
+        std::fs::write(repo_path.join("other.txt"), "a\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Initial"]);
+        let initial = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        // The side branch changes the file while the mainline only touches another one
+        run_git(repo_path, &["checkout", "-q", "-b", "side"]);
+        std::fs::write(repo_path.join("file.txt"), "side\n").unwrap();
+        run_git(repo_path, &["commit", "-am", "Side change"]);
+        let side = run_git(repo_path, &["rev-parse", "HEAD"]);
+        run_git(repo_path, &["checkout", "-q", "main"]);
+        std::fs::write(repo_path.join("other.txt"), "main\n").unwrap();
+        run_git(repo_path, &["commit", "-am", "Main change"]);
+        run_git(repo_path, &["merge", "-q", "--no-ff", "-m", "Merge side", "side"]);
+        let merge = run_git(repo_path, &["rev-parse", "HEAD"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+
+        let history = |mode| -> Vec<String> {
+            get_commit_history_for_file(&repo, "file.txt", None, mode)
+                .unwrap()
+                .into_iter()
+                .map(|c| c.hash)
+                .collect()
+        };
+
+        // The merge took the file unchanged from the side branch, so git's default hides it
+        assert_eq!(history(HistoryMode::Simplified), vec![side.clone(), initial.clone()]);
+        assert_eq!(
+            history(HistoryMode::FullHistory),
+            vec![merge.clone(), side.clone(), initial.clone()]
+        );
+        // On the mainline the merge is what brought the change in
+        assert_eq!(history(HistoryMode::FirstParent), vec![merge, initial]);
+    }
+
+    #[test]
+    fn test_history_cache_is_invalidated_when_refs_move() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+        run_git(repo_path, &["config", "lineage.cache", "true"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        let mode = HistoryMode::default();
+
+        let commits = get_commit_history_for_file(&repo, "file.txt", None, mode).unwrap();
+        assert_eq!(commits.len(), 2);
+
+        // While HEAD stays put the cached history is served, here doctored to prove it
+        let key = history_cache_key(None, "file.txt", mode);
+        let tip = gix::ObjectId::from_hex(second.as_bytes()).unwrap();
+        store_cached_history(&repo, &key, tip, &commits[1..]);
+        let hashes = |commits: Vec<CommitInfo>| -> Vec<String> {
+            commits.into_iter().map(|commit| commit.hash).collect()
+        };
+        let cached = get_commit_history_for_file(&repo, "file.txt", None, mode).unwrap();
+        assert_eq!(hashes(cached), vec![first.clone()]);
+        let (chunk, complete) = get_commit_history_chunk(&repo, "file.txt", None, mode, 10, 0).unwrap();
+        assert_eq!((hashes(chunk), complete), (vec![first.clone()], true));
+
+        // A new commit moves HEAD, so the history is walked again
+        std::fs::write(repo_path.join("file.txt"), "a\nB\nc\nD\n").unwrap();
+        run_git(repo_path, &["commit", "-q", "-am", "Third commit"]);
+        let third = run_git(repo_path, &["rev-parse", "HEAD"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        let mut streamed = Vec::new();
+        let token = tokio_util::sync::CancellationToken::new();
+        get_commit_history_streaming(
+            &repo,
+            "file.txt",
+            None,
+            mode,
+            |commit, _| {
+                streamed.push(commit.hash);
+                true
+            },
+            &token,
+        )
+        .unwrap();
+        assert_eq!(streamed, vec![third.clone(), second.clone(), first.clone()]);
+
+        // The last chunk of a walk caches the whole history
+        std::fs::write(repo_path.join("file.txt"), "a\nB\nC\nD\n").unwrap();
+        run_git(repo_path, &["commit", "-q", "-am", "Fourth commit"]);
+        let fourth = run_git(repo_path, &["rev-parse", "HEAD"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        let (chunk, complete) = get_commit_history_chunk(&repo, "file.txt", None, mode, 2, 2).unwrap();
+        assert_eq!((hashes(chunk), complete), (vec![second.clone(), first.clone()], true));
+        let tip = gix::ObjectId::from_hex(fourth.as_bytes()).unwrap();
+        let cached = load_cached_history(&repo, &key, tip).expect("History should be cached");
+        assert_eq!(hashes(cached), vec![fourth, third, second, first]);
+    }
+
+    #[test]
+    fn test_deleted_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = init_test_repo(&temp_dir);
+        std::fs::create_dir(repo_path.join("src")).unwrap();
+        for file in ["kept.txt", "src/gone.txt", "moved.txt", "back.txt"] {
+            std::fs::write(repo_path.join(file), format!("content of {}\n", file)).unwrap();
+        }
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-q", "-m", "Initial commit"]);
+        let first = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        run_git(repo_path, &["rm", "-q", "src/gone.txt", "back.txt"]);
+        run_git(repo_path, &["mv", "moved.txt", "renamed.txt"]);
+        run_git(repo_path, &["commit", "-q", "-m", "Delete files"]);
+        let second = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        std::fs::write(repo_path.join("back.txt"), "restored\n").unwrap();
+        run_git(repo_path, &["add", "back.txt"]);
+        run_git(repo_path, &["commit", "-q", "-m", "Restore back.txt"]);
+
+        // Restored files are not gone, renamed files are gone under their old name
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        let token = tokio_util::sync::CancellationToken::new();
+        let deleted = get_deleted_files(&repo, None, &token).unwrap();
+        assert_eq!(
+            deleted,
+            vec![
+                DeletedFile {
+                    path: "moved.txt".to_string(),
+                    commit_hash: second.clone(),
+                },
+                DeletedFile {
+                    path: "src/gone.txt".to_string(),
+                    commit_hash: second.clone(),
+                },
+            ]
+        );
+        // Before the restore, back.txt was deleted too
+        let deleted = get_deleted_files(&repo, Some(&second), &token).unwrap();
+        let paths: Vec<&str> = deleted.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["back.txt", "moved.txt", "src/gone.txt"]);
+
+        // A cancelled search finds nothing
+        token.cancel();
+        assert!(get_deleted_files(&repo, None, &token).unwrap().is_empty());
+
+        // The deleting commit leads to the last content
+        let before = get_commit_before_deletion(&repo, "src/gone.txt", &second).unwrap();
+        assert_eq!(before.as_ref(), Some(&first));
+        let content = get_file_content_at_commit(&repo, "src/gone.txt", &first).unwrap();
+        assert_eq!(content, vec!["content of src/gone.txt".to_string()]);
+        assert_eq!(get_commit_before_deletion(&repo, "src/gone.txt", &first).unwrap(), None);
+        assert_eq!(get_commit_before_deletion(&repo, "kept.txt", &second).unwrap(), None);
+
+        // The history of the deleted file starts at the commit that deleted it
+        let commits = get_commit_history_for_file(&repo, "src/gone.txt", None, HistoryMode::default()).unwrap();
+        let hashes: Vec<String> = commits.into_iter().map(|commit| commit.hash).collect();
+        assert_eq!(hashes, vec![second, first]);
+    }
+
+    #[test]
+    fn test_directory_history() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = init_test_repo(&temp_dir);
+        std::fs::create_dir(repo_path.join("src")).unwrap();
+        for file in ["src/a.txt", "src/b.txt", "other.txt"] {
+            std::fs::write(repo_path.join(file), format!("content of {}\n", file)).unwrap();
+        }
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-q", "-m", "Initial commit"]);
+        let first = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        std::fs::write(repo_path.join("other.txt"), "changed\n").unwrap();
+        run_git(repo_path, &["commit", "-q", "-am", "Change a file outside"]);
+
+        std::fs::write(repo_path.join("src/a.txt"), "changed\n").unwrap();
+        std::fs::write(repo_path.join("src/b.txt"), "changed\n").unwrap();
+        run_git(repo_path, &["commit", "-q", "-am", "Change both files"]);
+        let third = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        run_git(repo_path, &["mv", "src/a.txt", "src/c.txt"]);
+        run_git(repo_path, &["commit", "-q", "-m", "Rename a file"]);
+        let fourth = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        let commits = get_commit_history_for_file(&repo, "src", None, HistoryMode::default()).unwrap();
+        let history: Vec<(String, Option<usize>)> = commits
+            .into_iter()
+            .map(|commit| (commit.hash, commit.files_changed))
+            .collect();
+        assert_eq!(
+            history,
+            vec![(fourth, Some(1)), (third, Some(2)), (first, Some(2))]
+        );
+
+        // File histories do not count files
+        let commits = get_commit_history_for_file(&repo, "other.txt", None, HistoryMode::default()).unwrap();
+        assert!(commits.iter().all(|commit| commit.files_changed.is_none()));
+    }
+
+    #[test]
+    fn test_worktree_status() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = init_test_repo(&temp_dir);
+        std::fs::create_dir(repo_path.join("src")).unwrap();
+        for file in ["modified.txt", "staged.txt", "both.txt", "deleted.txt", "src/removed.txt"] {
+            std::fs::write(repo_path.join(file), "original\n").unwrap();
+        }
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-q", "-m", "Initial commit"]);
+
+        std::fs::write(repo_path.join("modified.txt"), "changed\n").unwrap();
+        std::fs::write(repo_path.join("staged.txt"), "changed\n").unwrap();
+        std::fs::write(repo_path.join("both.txt"), "staged\n").unwrap();
+        std::fs::write(repo_path.join("new.txt"), "new\n").unwrap();
+        run_git(repo_path, &["add", "staged.txt", "both.txt", "new.txt"]);
+        std::fs::write(repo_path.join("both.txt"), "staged and changed\n").unwrap();
+        std::fs::remove_file(repo_path.join("deleted.txt")).unwrap();
+        run_git(repo_path, &["rm", "-q", "src/removed.txt"]);
+        std::fs::write(repo_path.join("untracked.txt"), "untracked\n").unwrap();
+
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        let status = get_worktree_status(&repo).unwrap();
+
+        let sorted = |map: &HashMap<PathBuf, char>| {
+            let mut entries: Vec<(String, char)> = map
+                .iter()
+                .map(|(path, status)| (path.to_string_lossy().to_string(), *status))
+                .collect();
+            entries.sort();
+            entries
+        };
+        let entries = |list: &[(&str, char)]| -> Vec<(String, char)> {
+            list.iter().map(|(path, status)| (path.to_string(), *status)).collect()
+        };
+        assert_eq!(
+            sorted(&status.staged),
+            entries(&[("both.txt", 'M'), ("new.txt", 'A'), ("src/removed.txt", 'D'), ("staged.txt", 'M')])
+        );
+        assert_eq!(
+            sorted(&status.unstaged),
+            entries(&[("both.txt", 'M'), ("deleted.txt", 'D'), ("modified.txt", 'M'), ("untracked.txt", '?')])
+        );
+    }
+
+    #[test]
+    fn test_history_with_commit_graph() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = init_test_repo(&temp_dir);
+        // Both walks must really run
+        run_git(repo_path, &["config", "lineage.cache", "false"]);
+
+        std::fs::create_dir(repo_path.join("src")).unwrap();
+        let files = ["src/a.txt", "src/b.txt", "other.txt"];
+        for (round, file) in files.iter().cycle().take(7).enumerate() {
+            std::fs::write(repo_path.join(file), format!("{}\n", round)).unwrap();
+            run_git(repo_path, &["add", "."]);
+            run_git(repo_path, &["commit", "-q", "-m", &format!("Change {} ({})", file, round)]);
+        }
+        run_git(repo_path, &["checkout", "-q", "-b", "side", "HEAD~2"]);
+        std::fs::write(repo_path.join("src/b.txt"), "side\n").unwrap();
+        run_git(repo_path, &["commit", "-q", "-am", "Side change"]);
+        run_git(repo_path, &["checkout", "-q", "main"]);
+        run_git(repo_path, &["merge", "-q", "--no-ff", "-m", "Merge side", "side"]);
+
+        let histories = || -> Vec<Vec<String>> {
+            let repo = open_repository(repo_path).expect("Should open test repository");
+            let mut histories = Vec::new();
+            for path in files {
+                for mode in [HistoryMode::Simplified, HistoryMode::FullHistory, HistoryMode::FirstParent] {
+                    let commits = get_commit_history_for_file(&repo, path, None, mode).unwrap();
+                    histories.push(commits.into_iter().map(|c| c.hash).collect());
+                }
+            }
+            histories
+        };
+        let without_graph = histories();
+
+        run_git(repo_path, &["commit-graph", "write", "--reachable", "--changed-paths"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        let graph = CommitGraph::open(&repo).expect("Commit-graph should have been written");
+
+        // The Bloom filters tell commits that did not touch a file apart from those that did
+        let merge = graph.commit(&resolve_revision(&repo, None).unwrap()).unwrap();
+        assert_eq!(merge.parent_ids.len(), 2);
+        let last_change = graph.commit(&merge.parent_ids[0]).unwrap();
+        let position = last_change.graph_position.unwrap();
+        assert_eq!(graph.maybe_changed(position, "src/a.txt"), Some(true));
+        assert_eq!(graph.maybe_changed(position, "src/b.txt"), Some(false));
+        assert_eq!(graph.maybe_changed(position, "other.txt"), Some(false));
+
+        assert_eq!(histories(), without_graph);
+    }
+
+    #[test]
+    fn test_history_graph_links_listed_commits() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = init_test_repo(&temp_dir);
+        // The walk goes by commit date, so give every commit its own
+        let mut time = 1_700_000_000;
+        let mut commit = |args: &[&str]| {
+            time += 60;
+            let date = format!("@{} +0000", time);
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(repo_path)
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .status()
+                .expect("Failed to run git");
+            assert!(status.success());
+            run_git(repo_path, &["rev-parse", "HEAD"])
+        };
+
+        std::fs::write(repo_path.join("file.txt"), "a\nb\nc\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        let initial = commit(&["commit", "-q", "-m", "Initial"]);
+
+        // Both branches change the file, with an unrelated commit on the side branch in between
+        run_git(repo_path, &["checkout", "-q", "-b", "side"]);
+        std::fs::write(repo_path.join("other.txt"), "other\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        commit(&["commit", "-q", "-m", "Unrelated"]);
+        std::fs::write(repo_path.join("file.txt"), "a\nb\nside\n").unwrap();
+        let side = commit(&["commit", "-q", "-am", "Side change"]);
+        run_git(repo_path, &["checkout", "-q", "main"]);
+        std::fs::write(repo_path.join("file.txt"), "main\nb\nc\n").unwrap();
+        let main = commit(&["commit", "-q", "-am", "Main change"]);
+        let merge = commit(&["merge", "-q", "--no-ff", "-m", "Merge side", "side"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+
+        let commits = get_commit_history_for_file(&repo, "file.txt", None, HistoryMode::default()).unwrap();
+        let children = |hash: &str| -> Vec<(String, usize)> {
+            let commit = commits.iter().find(|c| c.hash == hash).expect("Commit should be listed");
+            let mut children = commit.graph_children.clone();
+            children.sort();
+            children
+        };
+
+        assert_eq!(commits.len(), 4);
+        assert!(children(&merge).is_empty());
+        assert_eq!(children(&main), vec![(merge.clone(), 0)]);
+        assert_eq!(children(&side), vec![(merge, 1)]);
+        // The unrelated commit is skipped, linking the side change straight to the initial commit
+        let mut expected = vec![(main, 0), (side, 0)];
+        expected.sort();
+        assert_eq!(children(&initial), expected);
+        assert_eq!(crate::graph::graph_rows(&commits), vec!["*-\\", "* |", "| *", "*-/"]);
+    }
+
+    #[test]
+    fn test_history_and_tree_at_revision() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+        run_git(repo_path, &["tag", "v1", &first]);
+
+        std::fs::write(repo_path.join("new.txt"), "new\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Third commit"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+
+        let commits = get_commit_history_for_file(&repo, "file.txt", Some("v1"), HistoryMode::default()).unwrap();
+        let hashes: Vec<_> = commits.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec![first.as_str()]);
+
+        let commits = get_commit_history_for_file(&repo, "file.txt", None, HistoryMode::default()).unwrap();
+        let hashes: Vec<_> = commits.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec![second.as_str(), first.as_str()]);
+
+        // Walking toward v1 there is nothing after the first commit
+        let next = find_next_change_for_line(&repo, "file.txt", Some("v1"), &first, 1).unwrap();
+        assert_eq!(next, None);
+
+        let paths = |revision| -> Vec<String> {
+            get_git_tree_entries(&repo, revision)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.path.to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(paths(Some("v1")), vec!["file.txt".to_string()]);
+        assert!(paths(None).contains(&"new.txt".to_string()));
+    }
+
+    #[test]
+    fn test_find_previous_change_for_line() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        // "c" was last changed by the first commit
+        let previous = find_previous_change_for_line(&repo, "file.txt", &second, 2).unwrap();
+        assert_eq!(previous, Some((first.clone(), 2)));
+
+        // "B" was changed by the second commit itself, so step back to where "b" came from
+        let previous = find_previous_change_for_line(&repo, "file.txt", &second, 1).unwrap();
+        assert_eq!(previous, Some((first.clone(), 1)));
+
+        // The initial commit has no earlier changes
+        let previous = find_previous_change_for_line(&repo, "file.txt", &first, 1).unwrap();
+        assert_eq!(previous, None);
+    }
+
+    /// Create a repo where `old.txt` is renamed to `new.txt` and then modified.
+    /// Returns the hashes of the (add, rename, modify) commits.
+    fn create_rename_test_repo(temp_dir: &tempfile::TempDir) -> (String, String, String) {
+        let repo_path = init_test_repo(temp_dir);
+
+        std::fs::write(repo_path.join("old.txt"), "one\ntwo\nthree\nfour\nfive\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Add old.txt"]);
+        let added = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        run_git(repo_path, &["mv", "old.txt", "new.txt"]);
+        run_git(repo_path, &["commit", "-m", "Rename to new.txt"]);
+        let renamed = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        std::fs::write(repo_path.join("new.txt"), "one\ntwo\nTHREE\nfour\nfive\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Modify new.txt"]);
+        let modified = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        (added, renamed, modified)
+    }
+
+    #[test]
+    fn test_commit_history_follows_renames() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (added, renamed, modified) = create_rename_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        let commits = get_commit_history_for_file(&repo, "new.txt", None, HistoryMode::default()).unwrap();
+        let hashes: Vec<&str> = commits.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec![modified.as_str(), renamed.as_str(), added.as_str()]);
+
+        // Each commit records the path the file had at that commit
+        let paths: Vec<Option<&str>> = commits.iter().map(|c| c.file_path.as_deref()).collect();
+        assert_eq!(paths, vec![Some("new.txt"), Some("new.txt"), Some("old.txt")]);
+
+        let (chunk, is_complete) = get_commit_history_chunk(&repo, "new.txt", None, HistoryMode::default(), 10, 1).unwrap();
+        assert!(is_complete);
+        assert_eq!(chunk.len(), 2);
+        assert_eq!(chunk[1].file_path.as_deref(), Some("old.txt"));
+
+        let mut streamed = Vec::new();
+        let token = tokio_util::sync::CancellationToken::new();
+        get_commit_history_streaming(&repo, "new.txt", None, HistoryMode::default(), |commit, _| {
+            streamed.push(commit);
+            true
+        }, &token).unwrap();
+        assert_eq!(streamed.len(), 3);
+        assert_eq!(streamed[2].file_path.as_deref(), Some("old.txt"));
+    }
+
+    #[test]
+    fn test_commit_history_follows_rename_on_one_side_of_merge() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = init_test_repo(&temp_dir);
+
+        let content: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(repo_path.join("old.txt"), &content).unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Add file"]);
+        let added = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        // The side branch renames the file while the mainline changes it under its old name
+        run_git(repo_path, &["checkout", "-q", "-b", "side"]);
+        run_git(repo_path, &["mv", "old.txt", "new.txt"]);
+        run_git(repo_path, &["commit", "-m", "Rename file"]);
+        let renamed = run_git(repo_path, &["rev-parse", "HEAD"]);
+        run_git(repo_path, &["checkout", "-q", "main"]);
+        std::fs::write(repo_path.join("old.txt"), format!("{}line 11\n", content)).unwrap();
+        run_git(repo_path, &["commit", "-am", "Change file"]);
+        let changed = run_git(repo_path, &["rev-parse", "HEAD"]);
+        run_git(repo_path, &["merge", "-q", "--no-ff", "-m", "Merge side", "side"]);
+        let merge = run_git(repo_path, &["rev-parse", "HEAD"]);
+        let repo = open_repository(repo_path).expect("Should open test repository");
+
+        let commits = get_commit_history_for_file(&repo, "new.txt", None, HistoryMode::FullHistory).unwrap();
+        let paths: HashMap<&str, Option<&str>> = commits
+            .iter()
+            .map(|c| (c.hash.as_str(), c.file_path.as_deref()))
+            .collect();
+
+        // Each side of the merge is followed under the name the file has there
+        assert_eq!(commits.len(), 4);
+        assert_eq!(paths[merge.as_str()], Some("new.txt"));
+        assert_eq!(paths[changed.as_str()], Some("old.txt"));
+        assert_eq!(paths[renamed.as_str()], Some("new.txt"));
+        assert_eq!(paths[added.as_str()], Some("old.txt"));
+    }
+
+    #[test]
+    fn test_find_rename_source() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (added, renamed, modified) = create_rename_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        assert_eq!(
+            find_rename_source(&repo, &renamed, &added, "new.txt").unwrap(),
+            Some("old.txt".to_string())
+        );
+        assert_eq!(find_rename_source(&repo, &modified, &renamed, "new.txt").unwrap(), None);
+    }
+
+    #[test]
+    fn test_blame_and_line_changes_follow_renames() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (added, renamed, modified) = create_rename_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        let blame = get_blame_at_commit(&repo, "new.txt", &modified).unwrap();
+        assert_eq!(blame[0].commit_hash, added);
+        assert_eq!(blame[0].original_path, "old.txt");
+        assert_eq!(blame[2].commit_hash, modified);
+
+        // Line "three" survives the rename and is changed afterwards
+        let next = find_next_change_for_line(&repo, "old.txt", None, &added, 2).unwrap();
+        assert_eq!(next, Some((modified.clone(), 2)));
+
+        let previous = find_previous_change_for_line(&repo, "new.txt", &renamed, 0).unwrap();
+        assert_eq!(previous, Some((added, 0)));
+    }
+
+    #[test]
+    fn test_get_line_range_history_streaming() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (added, renamed, modified) = create_rename_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+        let token = tokio_util::sync::CancellationToken::new();
+
+        let collect = |start_line: usize, end_line: usize| {
+            let mut commits = Vec::new();
+            get_line_range_history_streaming(&repo, "new.txt", &modified, start_line, end_line, |commit, _| {
+                commits.push(commit);
+                true
+            }, &token).unwrap();
+            commits
+        };
+
+        // "THREE" was changed after the rename and introduced with the file
+        let commits = collect(2, 2);
+        let hashes: Vec<&str> = commits.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec![modified.as_str(), added.as_str()]);
+        assert_eq!(commits[1].file_path.as_deref(), Some("old.txt"));
+
+        // "one" and "two" were never touched after being added; the pure rename is skipped
+        let commits = collect(0, 1);
+        let hashes: Vec<&str> = commits.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec![added.as_str()]);
+        assert!(!hashes.contains(&renamed.as_str()));
+    }
+
+    #[test]
+    fn test_get_parent_commits() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+
+        run_git(repo_path, &["checkout", "-q", "-b", "side", &first]);
+        std::fs::write(repo_path.join("other.txt"), "side\n").unwrap();
+        run_git(repo_path, &["add", "."]);
+        run_git(repo_path, &["commit", "-m", "Side commit"]);
+        let side = run_git(repo_path, &["rev-parse", "HEAD"]);
+        run_git(repo_path, &["checkout", "-q", "-"]);
+        run_git(repo_path, &["merge", "-q", "--no-ff", "-m", "Merge side", "side"]);
+        let merge = run_git(repo_path, &["rev-parse", "HEAD"]);
+
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        assert_eq!(get_parent_commits(&repo, &merge).unwrap(), vec![second.clone(), side]);
+        assert_eq!(get_parent_commits(&repo, &second).unwrap(), vec![first.clone()]);
+        assert!(get_parent_commits(&repo, &first).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_file_content_in_index_and_worktree() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        create_blame_test_repo(&temp_dir);
+        let repo_path = temp_dir.path();
+
+        std::fs::write(repo_path.join("file.txt"), "staged\n").unwrap();
+        run_git(repo_path, &["add", "file.txt"]);
+        std::fs::write(repo_path.join("file.txt"), "staged\nunstaged\n").unwrap();
+
+        let repo = open_repository(repo_path).expect("Should open test repository");
+        assert_eq!(get_file_content_in_index(&repo, "file.txt").unwrap(), vec!["staged"]);
+        assert_eq!(
+            get_file_content_in_worktree(&repo, "file.txt").unwrap(),
+            vec!["staged", "unstaged"]
+        );
+        assert!(get_file_content_in_index(&repo, "missing.txt").is_err());
+        assert!(get_file_content_in_worktree(&repo, "missing.txt").is_err());
+    }
+
+    #[test]
+    fn test_split_commit_message() {
+        let (subject, body, trailers) = split_commit_message(
+            "Fix parser\n\nExplain the fix.\nSecond line.\n\nMore detail.\n\nSigned-off-by: A <a@example.com>\nCo-authored-by: B <b@example.com>\n",
+        );
+        assert_eq!(subject, "Fix parser");
+        assert_eq!(body, "Explain the fix.\nSecond line.\n\nMore detail.");
+        assert_eq!(
+            trailers,
+            vec![
+                ("Signed-off-by".to_string(), "A <a@example.com>".to_string()),
+                ("Co-authored-by".to_string(), "B <b@example.com>".to_string()),
+            ]
+        );
+
+        // A last paragraph with a line that isn't a trailer stays part of the body
+        let (_, body, trailers) = split_commit_message("Subject\n\nReviewed-by: C\nbut not really\n");
+        assert_eq!(body, "Reviewed-by: C\nbut not really");
+        assert!(trailers.is_empty());
+
+        let (subject, body, trailers) = split_commit_message("Only a subject");
+        assert_eq!(subject, "Only a subject");
+        assert!(body.is_empty());
+        assert!(trailers.is_empty());
+    }
+
+    #[test]
+    fn test_parse_signature_time() {
+        assert_eq!(parse_signature_time("1751295482 -0400"), (1751295482, -4 * 3600));
+        assert_eq!(parse_signature_time("1751295482 +0530"), (1751295482, 5 * 3600 + 30 * 60));
+        assert_eq!(parse_signature_time("1751295482"), (1751295482, 0));
+        assert_eq!(parse_signature_time("garbage"), (0, 0));
+    }
+
+    #[test]
+    fn test_commit_info_metadata() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (first, second) = create_blame_test_repo(&temp_dir);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        let commits = get_commit_history_for_file(&repo, "file.txt", None, HistoryMode::default()).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, second);
+        assert_eq!(commits[0].subject, "Second commit");
+        assert_eq!(commits[0].author_email, "test@example.com");
+        assert_eq!(commits[0].committer, "Test User");
+        assert!(commits[0].author_time > 0);
+        assert_eq!(commits[0].parent_ids, vec![first.clone()]);
+        assert!(commits[1].parent_ids.is_empty());
+    }
+
+    #[test]
+    fn test_get_changed_files() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let (added, renamed, modified) = create_rename_test_repo(&temp_dir);
+        std::fs::write(temp_dir.path().join("other.txt"), "other\n").unwrap();
+        run_git(temp_dir.path(), &["add", "."]);
+        run_git(temp_dir.path(), &["commit", "-m", "Add other.txt"]);
+        let with_other = run_git(temp_dir.path(), &["rev-parse", "HEAD"]);
+        let repo = open_repository(temp_dir.path()).expect("Should open test repository");
+
+        let file = |path: &str, kind: FileChangeKind| ChangedFile {
+            path: path.to_string(),
+            kind,
+        };
+        assert_eq!(
+            get_changed_files(&repo, &added).unwrap(),
+            vec![file("old.txt", FileChangeKind::Added)]
+        );
+        assert_eq!(
+            get_changed_files(&repo, &renamed).unwrap(),
+            vec![file("new.txt", FileChangeKind::Renamed("old.txt".to_string()))]
+        );
+        assert_eq!(
+            get_changed_files(&repo, &modified).unwrap(),
+            vec![file("new.txt", FileChangeKind::Modified)]
+        );
+        assert_eq!(
+            get_changed_files(&repo, &with_other).unwrap(),
+            vec![file("other.txt", FileChangeKind::Added)]
+        );
+    }
 }
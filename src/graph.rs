@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use crate::app::CommitInfo;
+
+/// Lanes beyond this many are cut off so the graph cannot crowd out the commit list
+pub const MAX_GRAPH_LANES: usize = 8;
+
+/// Lay out a `git log --graph` style column for a history listed newest first.
+///
+/// Each commit gets one row: `*` marks the commit, `|` a lane passing by, and `/` or `\` a lane
+/// that ends in the commit or leaves it for another parent, joined to it by `-` where needed.
+/// The parents of a commit are the commits naming it in their `graph_children`, so parents that
+/// are not listed are already skipped. All rows are padded to the same width.
+pub fn graph_rows(commits: &[CommitInfo]) -> Vec<String> {
+    let mut parents: HashMap<&str, Vec<(usize, &str)>> = HashMap::new();
+    for commit in commits {
+        for (child, parent_index) in &commit.graph_children {
+            parents
+                .entry(child.as_str())
+                .or_default()
+                .push((*parent_index, commit.hash.as_str()));
+        }
+    }
+    for commit_parents in parents.values_mut() {
+        commit_parents.sort();
+    }
+
+    // Each lane holds the commit it leads down to
+    let mut lanes: Vec<Option<&str>> = Vec::new();
+    let mut rows = Vec::with_capacity(commits.len());
+
+    for commit in commits {
+        let hash = commit.hash.as_str();
+        let before: Vec<bool> = lanes.iter().map(Option::is_some).collect();
+
+        let column = match lanes.iter().position(|lane| *lane == Some(hash)) {
+            Some(column) => column,
+            None => free_lane(&mut lanes),
+        };
+
+        // Other lanes waiting for this commit end here
+        let mut joins = Vec::new();
+        for (index, lane) in lanes.iter_mut().enumerate() {
+            if index != column && *lane == Some(hash) {
+                *lane = None;
+                joins.push(index);
+            }
+        }
+
+        let commit_parents = parents.get(hash).map(Vec::as_slice).unwrap_or_default();
+        lanes[column] = commit_parents.first().map(|(_, parent)| *parent);
+
+        let mut forks = Vec::new();
+        for (_, parent) in commit_parents.iter().skip(1) {
+            let index = match lanes.iter().position(|lane| *lane == Some(*parent)) {
+                Some(index) => index,
+                None => {
+                    let index = free_lane(&mut lanes);
+                    lanes[index] = Some(*parent);
+                    index
+                }
+            };
+            forks.push(index);
+        }
+
+        rows.push(render_row(column, &before, &lanes, &joins, &forks));
+
+        while lanes.last() == Some(&None) {
+            lanes.pop();
+        }
+    }
+
+    let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    rows.into_iter()
+        .map(|row| format!("{:<width$}", row, width = width))
+        .collect()
+}
+
+/// Index of the first unused lane, opening a new one on the right if all are in use
+fn free_lane(lanes: &mut Vec<Option<&str>>) -> usize {
+    match lanes.iter().position(Option::is_none) {
+        Some(index) => index,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
+
+fn render_row(
+    column: usize,
+    before: &[bool],
+    after: &[Option<&str>],
+    joins: &[usize],
+    forks: &[usize],
+) -> String {
+    let lane_count = before.len().max(after.len()).min(MAX_GRAPH_LANES);
+    let mut cells = vec![' '; lane_count * 2];
+
+    for (index, cell) in cells.iter_mut().step_by(2).enumerate() {
+        let passes = before.get(index).copied().unwrap_or(false)
+            && after.get(index).is_some_and(Option::is_some);
+        if passes {
+            *cell = '|';
+        }
+    }
+
+    let mut connect = |index: usize, to_right: char, to_left: char| {
+        if index >= lane_count {
+            return;
+        }
+        let (from, to) = (column.min(index) * 2 + 1, column.max(index) * 2);
+        for cell in &mut cells[from..to] {
+            if *cell == ' ' {
+                *cell = '-';
+            }
+        }
+        if cells[index * 2] != '|' {
+            cells[index * 2] = if index > column { to_right } else { to_left };
+        }
+    };
+    for &index in joins {
+        connect(index, '/', '\\');
+    }
+    for &index in forks {
+        connect(index, '\\', '/');
+    }
+
+    // A commit in a lane that is cut off is marked in the last one shown
+    cells[column.min(lane_count - 1) * 2] = '*';
+
+    cells.into_iter().collect::<String>().trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(hash: &str, children: &[(&str, usize)]) -> CommitInfo {
+        CommitInfo {
+            hash: hash.to_string(),
+            graph_children: children
+                .iter()
+                .map(|(child, index)| (child.to_string(), *index))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_linear_history_is_a_single_lane() {
+        let commits = vec![
+            commit("c", &[]),
+            commit("b", &[("c", 0)]),
+            commit("a", &[("b", 0)]),
+        ];
+        assert_eq!(graph_rows(&commits), vec!["*", "*", "*"]);
+    }
+
+    #[test]
+    fn test_merged_branch_gets_its_own_lane() {
+        // a - b - m
+        //  \     /
+        //   side
+        let commits = vec![
+            commit("m", &[]),
+            commit("side", &[("m", 1)]),
+            commit("b", &[("m", 0)]),
+            commit("a", &[("b", 0), ("side", 0)]),
+        ];
+        assert_eq!(graph_rows(&commits), vec!["*-\\", "| *", "* |", "*-/"]);
+    }
+
+    #[test]
+    fn test_commits_without_listed_parents_free_their_lane() {
+        // Two tips whose common history has not been loaded yet share the first lane
+        let commits = vec![commit("x", &[]), commit("y", &[])];
+        assert_eq!(graph_rows(&commits), vec!["*", "*"]);
+    }
+
+    #[test]
+    fn test_lanes_are_capped() {
+        // A merge of more parents than fit; the extra lanes are not drawn, but their commits are
+        let mut commits = vec![commit("m", &[])];
+        for index in 0..MAX_GRAPH_LANES + 2 {
+            commits.push(commit(&format!("p{}", index), &[("m", index)]));
+        }
+        let rows = graph_rows(&commits);
+        assert!(rows.iter().all(|row| row.chars().count() <= MAX_GRAPH_LANES * 2));
+        assert!(rows.iter().all(|row| row.contains('*')));
+    }
+}
@@ -1,16 +1,22 @@
 pub mod app;
 pub mod async_task;
+pub mod cache;
 pub mod cli;
 pub mod command;
+pub mod commit_graph;
+pub mod diff;
 pub mod error;
 pub mod event;
 pub mod executor;
 pub mod git_utils;
+pub mod graph;
 pub mod headless_backend;
 pub mod line_mapping;
 pub mod main_lib;
+pub mod memory_cache;
 pub mod navigator;
 pub mod screenshot;
+pub mod syntax;
 pub mod test_config;
 pub mod test_runner;
 pub mod theme;
@@ -1,10 +1,14 @@
+use crate::cache::{self, CacheKind};
+use crate::memory_cache;
 use gix::Repository;
 use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Instant;
 
 /// Represents the mapping of lines from one version of a file to another
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LineMapping {
     /// Maps old line numbers to new line numbers (0-based indexing)
     /// None means the line was deleted in the new version
@@ -107,6 +111,48 @@ impl LineMapping {
         new_line.min(self.new_file_size.saturating_sub(1))
     }
 
+    /// Map a line to its position in the new version, or, if it was changed or deleted,
+    /// to the position right after the nearest preceding line that survived
+    pub fn map_line_to_change_position(&self, old_line: usize) -> usize {
+        if let Some(mapped) = self.map_line(old_line) {
+            return mapped;
+        }
+
+        let position = (0..old_line.min(self.old_file_size))
+            .rev()
+            .find_map(|line| self.map_line(line))
+            .map(|mapped| mapped + 1)
+            .unwrap_or(0);
+
+        position.min(self.new_file_size.saturating_sub(1))
+    }
+
+    /// Map a range of new lines (inclusive) back to the old lines it was derived from.
+    ///
+    /// Edges of the range that were changed extend to the nearest surviving lines around them,
+    /// so a rewritten range maps to the lines it replaced.
+    /// Returns None if the whole range was inserted in the new version.
+    pub fn reverse_map_range(&self, new_start: usize, new_end: usize) -> Option<(usize, usize)> {
+        let old_start = (0..=new_start.min(self.new_file_size.saturating_sub(1)))
+            .rev()
+            .find_map(|line| {
+                self.reverse_map_line(line)
+                    .map(|old| if line == new_start { old } else { old + 1 })
+            })
+            .unwrap_or(0);
+        let old_end_exclusive = (new_end..self.new_file_size)
+            .find_map(|line| {
+                self.reverse_map_line(line)
+                    .map(|old| if line == new_end { old + 1 } else { old })
+            })
+            .unwrap_or(self.old_file_size);
+
+        if old_start >= old_end_exclusive {
+            return None;
+        }
+        Some((old_start, old_end_exclusive - 1))
+    }
+
     /// Find exact content match for a line between commits
     /// Returns Some(line_number) if exactly one match is found, None otherwise
     pub fn find_exact_content_match(
@@ -318,13 +364,14 @@ pub enum LineMappingError {
     DiffFailed { reason: String },
 }
 
-/// Compute line mapping between two commits for a specific file
+/// Compute line mapping between two commits for a specific file. Mappings are shared with the
+/// in-memory cache rather than copied out of it.
 pub fn map_lines_between_commits(
     repo: &Repository,
     from_commit: &str,
     to_commit: &str,
     file_path: &Path,
-) -> std::result::Result<LineMapping, LineMappingError> {
+) -> std::result::Result<Arc<LineMapping>, LineMappingError> {
     let start_time = Instant::now();
     debug!(
         "🕐 map_lines_between_commits: Creating mapping for {:?} from {} to {}",
@@ -341,7 +388,24 @@ pub fn map_lines_between_commits(
         );
         info!("🕐 map_lines_between_commits: Completed (same commit) for {:?} in {:?}", 
              file_path, start_time.elapsed());
-        return Ok(LineMapping::identity(line_count));
+        return Ok(Arc::new(LineMapping::identity(line_count)));
+    }
+
+    // The mapping only depends on the two versions of the file, so it is cached by blob ids
+    let old_blob_id = find_file_entry(repo, from_commit, file_path)?.object_id();
+    let new_blob_id = find_file_entry(repo, to_commit, file_path)?.object_id();
+    if let Some(mapping) = memory_cache::line_mapping(old_blob_id, new_blob_id) {
+        debug!("🕐 map_lines_between_commits: Reused mapping for {:?} from {} to {} in {:?}",
+              file_path, &from_commit[..8], &to_commit[..8], start_time.elapsed());
+        return Ok(mapping);
+    }
+    let cache_key = format!("{}:{}", old_blob_id, new_blob_id);
+    if let Some(mapping) = cache::load::<LineMapping>(repo, CacheKind::LineMapping, &cache_key) {
+        let mapping = Arc::new(mapping);
+        memory_cache::store_line_mapping(old_blob_id, new_blob_id, mapping.clone());
+        info!("🕐 map_lines_between_commits: Loaded cached mapping for {:?} from {} to {} in {:?}",
+             file_path, &from_commit[..8], &to_commit[..8], start_time.elapsed());
+        return Ok(mapping);
     }
 
     // Get file content at both commits
@@ -350,19 +414,57 @@ pub fn map_lines_between_commits(
     let new_content = get_file_content_at_commit(repo, to_commit, file_path)?;
     debug!("🕐 map_lines_between_commits: Content retrieval took: {:?}", content_start.elapsed());
 
+    let mapping = Arc::new(map_lines_between_contents(&old_content, &new_content));
+    cache::store(repo, CacheKind::LineMapping, &cache_key, &*mapping);
+    memory_cache::store_line_mapping(old_blob_id, new_blob_id, mapping.clone());
+
+    info!("🕐 map_lines_between_commits: Completed for {:?} from {} to {} - {} -> {} lines in {:?}", 
+         file_path, &from_commit[..8], &to_commit[..8], mapping.old_file_size, mapping.new_file_size, start_time.elapsed());
+
+    Ok(mapping)
+}
+
+/// Compute line mapping between two blobs, sharing it through the in-memory cache so walks that
+/// revisit the same pair of file versions only diff them once
+pub fn map_lines_between_blobs(
+    repo: &Repository,
+    old_blob_id: gix::ObjectId,
+    new_blob_id: gix::ObjectId,
+) -> std::result::Result<Arc<LineMapping>, LineMappingError> {
+    if let Some(mapping) = memory_cache::line_mapping(old_blob_id, new_blob_id) {
+        return Ok(mapping);
+    }
+
+    let load = |blob_id| {
+        memory_cache::blob_data(repo, blob_id).map_err(|e| LineMappingError::DiffFailed {
+            reason: e.to_string(),
+        })
+    };
+    let old_data = load(old_blob_id)?;
+    let new_data = load(new_blob_id)?;
+    let mapping = Arc::new(map_lines_between_contents(
+        &String::from_utf8_lossy(&old_data),
+        &String::from_utf8_lossy(&new_data),
+    ));
+    memory_cache::store_line_mapping(old_blob_id, new_blob_id, mapping.clone());
+    Ok(mapping)
+}
+
+/// Compute line mapping between two versions of a file's content
+pub fn map_lines_between_contents(old_content: &str, new_content: &str) -> LineMapping {
     let old_lines: Vec<&str> = old_content.lines().collect();
     let new_lines: Vec<&str> = new_content.lines().collect();
 
     debug!(
-        "map_lines_between_commits: Old content has {} lines, new content has {} lines",
+        "map_lines_between_contents: Old content has {} lines, new content has {} lines",
         old_lines.len(),
         new_lines.len()
     );
 
     // Use similar crate for diffing (already in dependencies)
     let diff_start = Instant::now();
-    let diff = similar::TextDiff::from_lines(&old_content, &new_content);
-    debug!("🕐 map_lines_between_commits: Diff computation took: {:?}", diff_start.elapsed());
+    let diff = similar::TextDiff::from_lines(old_content, new_content);
+    debug!("🕐 map_lines_between_contents: Diff computation took: {:?}", diff_start.elapsed());
 
     let mut mapping = LineMapping::new(old_lines.len(), new_lines.len());
     let mapping_start = Instant::now();
@@ -400,27 +502,20 @@ pub fn map_lines_between_commits(
     }
 
     debug!(
-        "🕐 map_lines_between_commits: Diff analysis - {} equal, {} deleted, {} inserted",
+        "🕐 map_lines_between_contents: Diff analysis - {} equal, {} deleted, {} inserted",
         equal_count, delete_count, insert_count
     );
-    debug!("🕐 map_lines_between_commits: Mapping construction took: {:?}", mapping_start.elapsed());
-    
-    info!("🕐 map_lines_between_commits: Completed for {:?} from {} to {} - {} -> {} lines in {:?}", 
-         file_path, &from_commit[..8], &to_commit[..8], old_lines.len(), new_lines.len(), start_time.elapsed());
+    debug!("🕐 map_lines_between_contents: Mapping construction took: {:?}", mapping_start.elapsed());
 
-    Ok(mapping)
+    mapping
 }
 
-/// Get file content at a specific commit
-fn get_file_content_at_commit(
-    repo: &Repository,
+/// Find the tree entry of a file at a specific commit
+fn find_file_entry<'repo>(
+    repo: &'repo Repository,
     commit_hash: &str,
     file_path: &Path,
-) -> std::result::Result<String, LineMappingError> {
-    let start_time = Instant::now();
-    debug!("🕐 get_file_content_at_commit: Starting for file: {:?} at commit: {}", 
-           file_path, &commit_hash[..8]);
-    
+) -> std::result::Result<gix::object::tree::Entry<'repo>, LineMappingError> {
     // Find the commit object by hash
     let oid = gix::ObjectId::from_hex(commit_hash.as_bytes())
         .map_err(|_| LineMappingError::ObjectNotFound(commit_hash.to_string()))?;
@@ -437,27 +532,38 @@ fn get_file_content_at_commit(
     })?;
 
     // Navigate to the file in the tree
-    let file_entry = tree
-        .lookup_entry_by_path(file_path)
+    tree.lookup_entry_by_path(file_path)
         .map_err(|_| LineMappingError::FileNotFound {
             path: file_path.to_string_lossy().to_string(),
         })?
         .ok_or_else(|| LineMappingError::FileNotFound {
             path: file_path.to_string_lossy().to_string(),
-        })?;
+        })
+}
+
+/// Get file content at a specific commit
+fn get_file_content_at_commit(
+    repo: &Repository,
+    commit_hash: &str,
+    file_path: &Path,
+) -> std::result::Result<String, LineMappingError> {
+    let start_time = Instant::now();
+    debug!("🕐 get_file_content_at_commit: Starting for file: {:?} at commit: {}", 
+           file_path, &commit_hash[..8]);
+    
+    let file_entry = find_file_entry(repo, commit_hash, file_path)?;
 
     // Get the blob content
-    let blob = file_entry
-        .object()
-        .map_err(|e| LineMappingError::DiffFailed {
-            reason: e.to_string(),
-        })?
-        .try_into_blob()
-        .map_err(|_| LineMappingError::BinaryFile {
+    if !file_entry.mode().is_blob_or_symlink() {
+        return Err(LineMappingError::BinaryFile {
             path: file_path.to_string_lossy().to_string(),
-        })?;
-
-    let content_bytes = blob.data.clone();
+        });
+    }
+    let content_bytes = memory_cache::blob_data(repo, file_entry.object_id()).map_err(|e| {
+        LineMappingError::DiffFailed {
+            reason: e.to_string(),
+        }
+    })?;
 
     // Check if file is binary (contains null bytes)
     if content_bytes.contains(&0) {
@@ -467,9 +573,11 @@ fn get_file_content_at_commit(
     }
 
     // Convert to string
-    let result = String::from_utf8(content_bytes).map_err(|_| LineMappingError::BinaryFile {
-        path: file_path.to_string_lossy().to_string(),
-    });
+    let result = std::str::from_utf8(&content_bytes)
+        .map(str::to_string)
+        .map_err(|_| LineMappingError::BinaryFile {
+            path: file_path.to_string_lossy().to_string(),
+        });
     
     match &result {
         Ok(content) => {
@@ -584,6 +692,39 @@ mod tests {
         assert_eq!(mapping.find_nearest_mapped_line(2, 3), Some(1)); // nearest is line 3 -> 1
     }
 
+    #[test]
+    fn test_map_line_to_change_position() {
+        let mapping = map_lines_between_contents("a\nb\nc\n", "a\nB\nc\nd\n");
+
+        assert_eq!(mapping.map_line_to_change_position(0), 0); // unchanged
+        assert_eq!(mapping.map_line_to_change_position(1), 1); // replaced by "B"
+        assert_eq!(mapping.map_line_to_change_position(2), 2); // unchanged
+
+        // Deleting the first lines lands on the top of the file
+        let mapping = map_lines_between_contents("x\ny\nz\n", "z\n");
+        assert_eq!(mapping.map_line_to_change_position(0), 0);
+        assert_eq!(mapping.map_line_to_change_position(1), 0);
+
+        // Deleting the last lines stays within the new file
+        let mapping = map_lines_between_contents("x\ny\nz\n", "x\n");
+        assert_eq!(mapping.map_line_to_change_position(2), 0);
+    }
+
+    #[test]
+    fn test_reverse_map_range() {
+        // old: a b c d e -> new: a B c x d e (b changed, x inserted)
+        let mapping = map_lines_between_contents("a\nb\nc\nd\ne\n", "a\nB\nc\nx\nd\ne\n");
+
+        // Untouched lines map one to one
+        assert_eq!(mapping.reverse_map_range(4, 5), Some((3, 4)));
+        // A changed line maps to the line it replaced
+        assert_eq!(mapping.reverse_map_range(1, 1), Some((1, 1)));
+        // A range containing an insertion shrinks to its surviving lines
+        assert_eq!(mapping.reverse_map_range(2, 4), Some((2, 3)));
+        // A purely inserted range has no origin
+        assert_eq!(mapping.reverse_map_range(3, 3), None);
+    }
+
     #[test]
     fn test_same_commit_mapping() {
         let (_temp_dir, repo) = create_test_repo();
@@ -638,6 +779,33 @@ mod tests {
         assert_eq!(mapping.reverse_map_line(3), Some(2)); // new line 3 from old line 2
     }
 
+    #[test]
+    fn test_repeated_mapping_is_shared() {
+        let (_temp_dir, repo) = create_test_repo();
+        let repo_path = _temp_dir.path();
+        let commit1 = commit_file(repo_path, "test.txt", "a\nb\n", "Initial commit");
+        let commit2 = commit_file(repo_path, "test.txt", "a\nx\nb\n", "Add line");
+
+        let first = map_lines_between_commits(&repo, &commit1, &commit2, Path::new("test.txt")).unwrap();
+        let second = map_lines_between_commits(&repo, &commit1, &commit2, Path::new("test.txt")).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn test_repeated_blob_mapping_is_shared() {
+        let (_temp_dir, repo) = create_test_repo();
+        let repo_path = _temp_dir.path();
+        let commit1 = commit_file(repo_path, "test.txt", "a\nb\n", "Initial commit");
+        let commit2 = commit_file(repo_path, "test.txt", "a\ny\nb\n", "Add line");
+        let old_blob_id = find_file_entry(&repo, &commit1, Path::new("test.txt")).unwrap().object_id();
+        let new_blob_id = find_file_entry(&repo, &commit2, Path::new("test.txt")).unwrap().object_id();
+
+        let first = map_lines_between_blobs(&repo, old_blob_id, new_blob_id).unwrap();
+        let second = map_lines_between_blobs(&repo, old_blob_id, new_blob_id).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(*first, map_lines_between_contents("a\nb\n", "a\ny\nb\n"));
+    }
+
     #[test]
     fn test_line_deletion() {
         let (_temp_dir, repo) = create_test_repo();
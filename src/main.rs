@@ -11,17 +11,23 @@ use tokio::sync::mpsc;
 
 mod app;
 mod async_task;
+mod cache;
 mod cli;
 mod command;
+mod commit_graph;
+mod diff;
 mod error;
 mod event;
 mod executor;
 mod git_utils;
+mod graph;
 mod headless_backend;
 mod line_mapping;
 mod main_lib;
+mod memory_cache;
 mod navigator;
 mod screenshot;
+mod syntax;
 mod test_config;
 mod test_runner;
 mod theme;
@@ -56,7 +62,7 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command.unwrap_or(Commands::Run) {
-        Commands::Run => run_interactive().await,
+        Commands::Run => run_interactive(cli.rev).await,
         Commands::Screenshot {
             config,
             output,
@@ -84,6 +90,10 @@ async fn main() -> Result<()> {
             )?;
             Ok(())
         }
+        Commands::Contains { revision } => {
+            main_lib::print_containing_refs(&revision)?;
+            Ok(())
+        }
         Commands::SaveState { output } => {
             main_lib::save_current_state(output.as_deref()).await?;
             Ok(())
@@ -156,12 +166,23 @@ async fn run_headless_test(
 
     // Load initial data (same as interactive mode)
     log::info!("📤 headless: Sending LoadFileTree task");
-    if let Err(e) = task_sender.send(Task::LoadFileTree).await {
+    let task = Task::LoadFileTree {
+        revision: app.revision.clone(),
+    };
+    if let Err(e) = task_sender.send(task).await {
         log::error!("📤 headless: Failed to send LoadFileTree task: {}", e);
         app.ui.status_message = format!("Failed to load file tree: {}", e);
     } else {
         log::info!("📤 headless: LoadFileTree task sent successfully");
     }
+    // Refs decorate the history
+    if let Err(e) = task_sender.send(Task::LoadRefs).await {
+        log::error!("📤 headless: Failed to send LoadRefs task: {}", e);
+    }
+    // Changed files are marked in the tree
+    if let Err(e) = task_sender.send(Task::LoadWorktreeStatus).await {
+        log::error!("📤 headless: Failed to send LoadWorktreeStatus task: {}", e);
+    }
 
     // Give some time for initial loading
     tokio::time::sleep(Duration::from_millis(100)).await;
@@ -193,13 +214,20 @@ async fn run_headless_test(
     }
 }
 
-async fn run_interactive() -> Result<()> {
+async fn run_interactive(revision: Option<String>) -> Result<()> {
     // Initialize Git repository
     let repo =
         git_utils::open_repository(".").map_err(|e| GitLineageError::from(e.to_string()))?;
 
+    // Fail before touching the terminal if the revision does not exist
+    if revision.is_some() {
+        git_utils::resolve_revision(&repo, revision.as_deref())
+            .map_err(|e| GitLineageError::from(e.to_string()))?;
+    }
+
     // Initialize application state
     let mut app = App::new(repo);
+    app.revision = revision;
 
     // Setup terminal
     enable_raw_mode()?;
@@ -219,13 +247,28 @@ async fn run_interactive() -> Result<()> {
 
     // Load initial data
     log::info!("📤 main: Sending LoadFileTree task");
-    if let Err(e) = task_sender.send(Task::LoadFileTree).await {
+    let task = Task::LoadFileTree {
+        revision: app.revision.clone(),
+    };
+    if let Err(e) = task_sender.send(task).await {
         log::error!("📤 main: Failed to send LoadFileTree task: {}", e);
         app.ui.status_message = format!("Failed to load file tree: {}", e);
     } else {
         log::info!("📤 main: LoadFileTree task sent successfully");
         app.start_background_task();
     }
+    // Refs decorate the history
+    if let Err(e) = task_sender.send(Task::LoadRefs).await {
+        log::error!("📤 main: Failed to send LoadRefs task: {}", e);
+    } else {
+        app.start_background_task();
+    }
+    // Changed files are marked in the tree
+    if let Err(e) = task_sender.send(Task::LoadWorktreeStatus).await {
+        log::error!("📤 main: Failed to send LoadWorktreeStatus task: {}", e);
+    } else {
+        app.start_background_task();
+    }
 
     // Event-driven main application loop
     #[derive(Debug)]
@@ -302,6 +345,10 @@ async fn run_interactive() -> Result<()> {
                     );
                     app.complete_background_task();
                     main_lib::handle_task_result(&mut app, result);
+                    // A newly selected commit may need its blame and changed files loaded
+                    event::request_blame_if_needed(&mut app, &task_sender);
+                    event::request_changed_files_if_needed(&mut app, &task_sender);
+                    event::load_history_for_pending_jump(&mut app, &task_sender);
                     // Render immediately when background task completes
                     app.navigator.build_view_model();
                     terminal.draw(|f| ui::draw(f, &mut app))?;
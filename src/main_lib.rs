@@ -21,27 +21,51 @@ pub fn handle_task_result(app: &mut App, result: TaskResult) {
     }
 
     match result {
-        TaskResult::FileTreeLoaded { files } => {
+        TaskResult::FileTreeLoaded { files, revision } => {
+            // A tree for a revision that is no longer selected is stale
+            if revision != app.revision {
+                app.ui.status_message = "Async file tree result ignored (revision changed)".to_string();
+                return;
+            }
+
+            // Keep the selected file or directory when the tree is reloaded for another revision
+            let previous_file = app
+                .get_history_path()
+                .filter(|path| files.find_node(path).is_some());
+
             // Initialize the new navigator with the file tree
             app.navigator.handle_event(crate::navigator::NavigatorEvent::EndSearch).unwrap(); // Reset to browse mode
             app.navigator = crate::navigator::NavigatorState::new(files);
-            app.navigator
-                .handle_event(crate::navigator::NavigatorEvent::NavigateDown)
-                .unwrap(); // Select first item
+            // The working tree status only applies to the files at HEAD
+            if let (None, Some(status)) = (&app.revision, &app.worktree_status) {
+                app.navigator.set_worktree_status(status.clone());
+            }
+            match previous_file {
+                Some(path) => {
+                    app.navigator.reveal(path);
+                }
+                None => {
+                    app.navigator
+                        .handle_event(crate::navigator::NavigatorEvent::NavigateDown)
+                        .unwrap(); // Select first item
+                }
+            }
 
             app.ui.status_message = "File tree loaded".to_string();
         }
         TaskResult::CommitHistoryLoaded { file_path, commits } => {
-            // Race condition protection: Only apply commits if they're for the currently active file
+            // Race condition protection: Only apply commits if they're for the currently selected file
+            // or directory and the whole-file history is being shown
             let is_still_relevant = app
-                .get_active_file()
+                .get_history_path()
                 .as_ref()
                 .map(|active_path| active_path.to_string_lossy() == file_path)
-                .unwrap_or(false);
+                .unwrap_or(false)
+                && app.history.line_range.is_none();
 
             if is_still_relevant {
                 let commit_count = commits.len();
-                app.history.commit_list = commits;
+                app.history.set_commits(commits);
                 // Reset commit list selection when new commits are loaded
                 app.history.selected_commit_index = if commit_count == 0 { None } else { Some(0) };
                 app.ui.status_message = if commit_count == 0 {
@@ -65,17 +89,19 @@ pub fn handle_task_result(app: &mut App, result: TaskResult) {
             is_complete,
             chunk_offset,
         } => {
-            // Race condition protection: Only apply commits if they're for the currently active file
+            // Race condition protection: Only apply commits if they're for the currently selected file
+            // or directory and the whole-file history is being shown
             let is_still_relevant = app
-                .get_active_file()
+                .get_history_path()
                 .as_ref()
                 .map(|active_path| active_path.to_string_lossy() == file_path)
-                .unwrap_or(false);
+                .unwrap_or(false)
+                && app.history.line_range.is_none();
 
             if is_still_relevant {
                 if chunk_offset == 0 {
                     // First chunk - replace entire list and auto-load content
-                    app.history.commit_list = commits;
+                    app.history.set_commits(commits);
                     app.history.next_chunk_offset = app.history.commit_list.len();
 
                     // Reset commit list selection when new commits are loaded
@@ -88,13 +114,18 @@ pub fn handle_task_result(app: &mut App, result: TaskResult) {
                     }
                 } else {
                     // Subsequent chunks - append to existing list
-                    app.history.commit_list.extend(commits);
+                    app.history.append_commits(commits);
                     app.history.next_chunk_offset = app.history.commit_list.len();
                 }
 
                 app.history.history_complete = is_complete;
                 app.history.is_loading_more = false;
 
+                // Complete a jump that was waiting for more history
+                if resolve_pending_jump(app) {
+                    return;
+                }
+
                 let commit_count = app.history.commit_list.len();
                 app.ui.status_message = if commit_count == 0 {
                     "No commits found for this file".to_string()
@@ -113,30 +144,73 @@ pub fn handle_task_result(app: &mut App, result: TaskResult) {
             commit,
             total_commits_so_far,
         } => {
-            // Race condition protection: Only apply commits if they're for the currently active file
+            // Race condition protection: Only apply commits if they're for the currently selected file
+            // or directory and the whole-file history is being shown
+            let is_still_relevant = app
+                .get_history_path()
+                .as_ref()
+                .map(|active_path| active_path.to_string_lossy() == file_path)
+                .unwrap_or(false)
+                && app.history.line_range.is_none();
+
+            if is_still_relevant {
+                add_found_commit(app, commit, total_commits_so_far);
+            }
+        }
+        TaskResult::LineRangeCommitFound {
+            file_path,
+            line_range,
+            commit,
+            total_commits_so_far,
+        } => {
+            // Race condition protection: Only apply commits for the line range currently shown
             let is_still_relevant = app
                 .get_active_file()
                 .as_ref()
                 .map(|active_path| active_path.to_string_lossy() == file_path)
-                .unwrap_or(false);
+                .unwrap_or(false)
+                && app.history.line_range.as_ref() == Some(&line_range);
 
             if is_still_relevant {
-                // Add the new commit to the list
-                app.history.commit_list.push(commit);
+                add_found_commit(app, commit, total_commits_so_far);
+            }
+        }
+        TaskResult::LineRangeHistoryComplete {
+            file_path,
+            line_range,
+            total_commits,
+        } => {
+            // Race condition protection: Only apply if still relevant
+            let is_still_relevant = app
+                .get_active_file()
+                .as_ref()
+                .map(|active_path| active_path.to_string_lossy() == file_path)
+                .unwrap_or(false)
+                && app.history.line_range.as_ref() == Some(&line_range);
 
-                // If this is the first commit, auto-select it and load content
-                if total_commits_so_far == 1 {
-                    app.history.selected_commit_index = Some(0);
-                    crate::event::update_code_inspector_for_commit_no_diff(app);
+            if is_still_relevant {
+                app.history.history_complete = true;
+                app.history.is_loading_more = false;
+
+                if app.history.pending_jump.take().is_some() {
+                    app.ui.status_message = "Change found but commit not in history".to_string();
+                    return;
                 }
 
-                // Update status message with current progress
-                let filename = app
-                    .get_active_file()
-                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
-                    .unwrap_or_default();
-                app.ui.status_message =
-                    format!("{} loaded ({} commits found...)", filename, total_commits_so_far);
+                app.ui.status_message = if total_commits == 0 {
+                    format!(
+                        "No commits found for lines {}-{}",
+                        line_range.start_line + 1,
+                        line_range.end_line + 1
+                    )
+                } else {
+                    format!(
+                        "Lines {}-{} loaded ({} commits)",
+                        line_range.start_line + 1,
+                        line_range.end_line + 1,
+                        total_commits
+                    )
+                };
             }
         }
         TaskResult::CommitHistoryComplete {
@@ -145,17 +219,23 @@ pub fn handle_task_result(app: &mut App, result: TaskResult) {
         } => {
             // Race condition protection: Only apply if still relevant
             let is_still_relevant = app
-                .get_active_file()
+                .get_history_path()
                 .as_ref()
                 .map(|active_path| active_path.to_string_lossy() == file_path)
-                .unwrap_or(false);
+                .unwrap_or(false)
+                && app.history.line_range.is_none();
 
             if is_still_relevant {
                 app.history.history_complete = true;
                 app.history.is_loading_more = false;
 
+                if app.history.pending_jump.take().is_some() {
+                    app.ui.status_message = "Change found but commit not in history".to_string();
+                    return;
+                }
+
                 let filename = app
-                    .get_active_file()
+                    .get_history_path()
                     .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
                     .unwrap_or_default();
 
@@ -166,58 +246,263 @@ pub fn handle_task_result(app: &mut App, result: TaskResult) {
                 };
             }
         }
-        TaskResult::NextChangeFound { commit_hash } => {
-            // Find the commit in the list and select it
-            if let Some(index) = app
-                .history
-                .commit_list
-                .iter()
-                .position(|c| c.hash == commit_hash)
-            {
-                app.history.selected_commit_index = Some(index);
-                app.ui.active_panel = crate::app::PanelFocus::History;
-                app.ui.status_message = "Found next change".to_string();
-            } else {
-                app.ui.status_message = "Next change found but commit not in history".to_string();
-            }
+        TaskResult::NextChangeFound {
+            commit_hash,
+            line_number,
+        } => {
+            apply_change_jump(app, commit_hash, line_number, "next");
         }
         TaskResult::NextChangeNotFound => {
             app.ui.status_message = "No subsequent changes found for this line".to_string();
         }
+        TaskResult::PreviousChangeFound {
+            commit_hash,
+            line_number,
+        } => {
+            apply_change_jump(app, commit_hash, line_number, "previous");
+        }
+        TaskResult::PreviousChangeNotFound => {
+            app.ui.status_message = "No earlier changes found for this line".to_string();
+        }
         TaskResult::DiffGenerated {
             file_path,
             current_commit,
-            parent_commit,
+            base,
+            base_commit,
             diff_lines,
         } => {
             // Race condition protection: Only apply diff if it's for the currently active file and commit
             let is_still_relevant = app
-                .get_active_file()
-                .as_ref()
-                .map(|active_path| active_path.to_string_lossy() == file_path)
+                .get_file_path_at_commit(&current_commit)
+                .map(|path| path.to_string_lossy() == file_path)
                 .unwrap_or(false)
                 && app
                     .history
                     .selected_commit_hash
                     .as_ref()
                     .map(|hash| hash == &current_commit)
-                    .unwrap_or(false);
+                    .unwrap_or(false)
+                && app.inspector.diff_base == base;
 
             if is_still_relevant {
-                app.inspector.diff_lines = Some(diff_lines);
-                app.inspector.parent_commit_hash = Some(parent_commit);
-                app.ui.status_message = "Diff view loaded".to_string();
+                app.inspector.set_diff_lines(Some(diff_lines));
+                app.inspector.base_commit_hash = base_commit;
+                app.ensure_inspector_cursor_visible();
+                app.ui.status_message = format!("Diff view loaded (against {})", base.describe());
             } else {
                 // Async result is stale - ignore it
                 app.ui.status_message = "Async diff result ignored (context changed)".to_string();
             }
         }
+        TaskResult::BlameLoaded {
+            file_path,
+            commit_hash,
+            blame,
+        } => {
+            // Race condition protection: Only apply blame if it's for the currently active file and commit
+            let is_still_relevant = app
+                .get_file_path_at_commit(&commit_hash)
+                .map(|path| path.to_string_lossy() == file_path)
+                .unwrap_or(false)
+                && app
+                    .history
+                    .selected_commit_hash
+                    .as_ref()
+                    .map(|hash| hash == &commit_hash)
+                    .unwrap_or(false);
+
+            let pending_blame_jump = app
+                .inspector
+                .pending_blame_jump
+                .take_if(|jump| jump.commit_hash == commit_hash);
+
+            if is_still_relevant {
+                app.inspector.current_blame = Some(blame);
+                app.inspector.blame_commit_hash = Some(commit_hash);
+                app.ui.status_message = "Blame loaded".to_string();
+
+                // Complete a blame jump that was waiting for this blame
+                if let Some(line_number) = pending_blame_jump.and_then(|jump| jump.line_number) {
+                    crate::event::finish_blame_jump(app, line_number);
+                }
+            } else {
+                // Async result is stale - ignore it
+                app.ui.status_message = "Async blame result ignored (context changed)".to_string();
+            }
+        }
+        TaskResult::BlameFailed {
+            file_path,
+            commit_hash,
+            message,
+        } => {
+            // Only the blame that is being waited for is affected; pressing 'b' again retries it
+            if app.inspector.blame_commit_hash.as_ref() == Some(&commit_hash)
+                && app.inspector.current_blame.is_none()
+            {
+                app.inspector.clear_blame();
+                app.inspector.show_blame = false;
+            }
+            app.ui.status_message = format!("Error loading blame for {}: {}", file_path, message);
+        }
+        TaskResult::ChangedFilesLoaded {
+            commit_hash,
+            changed_files,
+        } => {
+            // Race condition protection: Only apply the files if they're for the latest requested commit
+            if app.history.changed_files_commit.as_ref() == Some(&commit_hash) {
+                app.ui.status_message = format!(
+                    "Commit {} changed {} file(s)",
+                    &commit_hash[..8.min(commit_hash.len())],
+                    changed_files.len()
+                );
+                app.history.changed_files = Some(changed_files);
+                crate::event::show_directory_changes(app);
+            } else {
+                // Async result is stale - ignore it
+                app.ui.status_message = "Async changed files result ignored (context changed)".to_string();
+            }
+        }
+        TaskResult::ContainingRefsLoaded {
+            commit_hash,
+            containing_refs,
+        } => {
+            // Race condition protection: Only apply the refs if they're for the latest requested commit
+            if app.history.containing_refs_commit.as_ref() == Some(&commit_hash) {
+                app.history.containing_refs = Some(containing_refs);
+            } else {
+                // Async result is stale - ignore it
+                app.ui.status_message = "Async containing refs result ignored (context changed)".to_string();
+            }
+        }
+        TaskResult::ContainingRefsCancelled { commit_hash } => {
+            // Only cancelled when another commit was selected, which requested its own refs
+            log::debug!("Containing refs search for {} cancelled", &commit_hash[..8.min(commit_hash.len())]);
+        }
+        TaskResult::RefsLoaded { refs } => {
+            app.set_refs(&refs);
+            // The picker may have been closed while the refs were loading
+            if let Some(picker) = app.ui.ref_picker.as_mut() {
+                picker.refs = Some(refs);
+                picker.selected = 0;
+            }
+        }
+        TaskResult::WorktreeStatusLoaded { status } => {
+            let changed: std::collections::HashSet<_> =
+                status.staged.keys().chain(status.unstaged.keys()).collect();
+            app.ui.status_message = format!("Working tree status: {} changed files", changed.len());
+            if app.revision.is_none() {
+                app.navigator.set_worktree_status(status.clone());
+            }
+            app.worktree_status = Some(status);
+        }
+        TaskResult::DeletedFilesLoaded {
+            revision,
+            deleted_files,
+        } => {
+            // The files deleted before a revision that is no longer selected are stale
+            if revision != app.revision {
+                return;
+            }
+            app.navigator.set_deleted_files(&deleted_files);
+            app.ui.status_message = format!("Found {} deleted files", deleted_files.len());
+            app.deleted_files = Some(deleted_files);
+        }
+        TaskResult::DeletedFilesFailed { revision, message } => {
+            if revision != app.revision {
+                return;
+            }
+            // Opening the folder again retries the search
+            app.deleted_files = None;
+            app.ui.status_message = format!("Error looking for deleted files: {}", message);
+        }
         TaskResult::Error { message } => {
             app.ui.status_message = format!("Error: {}", message);
         }
     }
 }
 
+/// Append a streamed commit to the history list
+fn add_found_commit(app: &mut App, commit: crate::app::CommitInfo, total_commits_so_far: usize) {
+    // Add the new commit to the list
+    app.history.append_commits([commit]);
+
+    // If this is the first commit, auto-select it and load content
+    if total_commits_so_far == 1 {
+        app.history.selected_commit_index = Some(0);
+        crate::event::update_code_inspector_for_commit_no_diff(app);
+    }
+
+    // Complete a jump that was waiting for this commit to arrive
+    if resolve_pending_jump(app) {
+        return;
+    }
+
+    // Update status message with current progress
+    let filename = app
+        .get_active_file()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    app.ui.status_message = match &app.history.line_range {
+        Some(line_range) => format!(
+            "{} lines {}-{} ({} commits found...)",
+            filename,
+            line_range.start_line + 1,
+            line_range.end_line + 1,
+            total_commits_so_far
+        ),
+        None => format!("{} loaded ({} commits found...)", filename, total_commits_so_far),
+    };
+}
+
+/// Complete a pending jump once its commit is part of the history list
+fn resolve_pending_jump(app: &mut App) -> bool {
+    let Some(jump) = app.history.pending_jump.clone() else {
+        return false;
+    };
+
+    if !crate::event::jump_to_commit_line(app, &jump.commit_hash, jump.line_number) {
+        return false;
+    }
+
+    app.history.pending_jump = None;
+    app.ui.status_message = format!(
+        "Jumped to {} (line {})",
+        &jump.commit_hash[..8.min(jump.commit_hash.len())],
+        app.inspector.cursor_line + 1
+    );
+    true
+}
+
+/// Select the commit found by a next/previous change search, or remember it until it is streamed in
+fn apply_change_jump(app: &mut App, commit_hash: String, line_number: usize, direction: &str) {
+    if crate::event::jump_to_commit_line(app, &commit_hash, Some(line_number)) {
+        let short_hash = app
+            .history
+            .selected_commit_index
+            .and_then(|index| app.history.commit_list.get(index))
+            .map(|c| c.short_hash.clone())
+            .unwrap_or_default();
+        app.ui.status_message = format!(
+            "Found {} change in {} (line {})",
+            direction,
+            short_hash,
+            app.inspector.cursor_line + 1
+        );
+    } else if !app.history.history_complete {
+        app.ui.status_message = format!(
+            "Found {} change in {}, waiting for history to load...",
+            direction,
+            &commit_hash[..8.min(commit_hash.len())]
+        );
+        app.history.pending_jump = Some(crate::app::PendingJump {
+            commit_hash,
+            line_number: Some(line_number),
+        });
+    } else {
+        app.ui.status_message = format!("{} change found but commit not in history", direction);
+    }
+}
+
 pub fn execute_command(
     config_path: &str,
     command_str: &str,
@@ -284,6 +569,36 @@ pub fn execute_command(
     Ok(())
 }
 
+pub fn print_containing_refs(revision: &str) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let repo = crate::git_utils::open_repository(".").map_err(|e| e.to_string())?;
+    let commit_id = crate::git_utils::resolve_revision(&repo, Some(revision)).map_err(|e| e.to_string())?;
+    let commit_hash = commit_id.to_string();
+    let containing = crate::git_utils::find_refs_containing(
+        &repo,
+        &commit_hash,
+        &tokio_util::sync::CancellationToken::new(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    match containing.earliest_tag() {
+        Some(tag) => println!("Commit {} first released in {} ({})", &commit_hash[..8], tag.name, tag.date),
+        None => println!("Commit {} is not contained in any tag", &commit_hash[..8]),
+    }
+
+    for (heading, refs) in [("Tags", &containing.tags), ("Branches", &containing.branches)] {
+        if refs.is_empty() {
+            continue;
+        }
+        let name_width = refs.iter().map(|r| r.name.len()).max().unwrap_or(0);
+        println!("{} ({}):", heading, refs.len());
+        for r in refs {
+            println!("  {:<width$}  {}", r.name, r.date, width = name_width);
+        }
+    }
+
+    Ok(())
+}
+
 pub async fn save_current_state(output_path: Option<&str>) -> Result<()> {
     // Initialize Git repository - use open instead of discover to get the right error type
     let repo = gix::open(".").map_err(|e| crate::error::GitLineageError::from(e))?;
@@ -292,7 +607,7 @@ pub async fn save_current_state(output_path: Option<&str>) -> Result<()> {
     let mut app = App::new(repo);
 
     // Load the file tree directly
-    match crate::async_task::load_file_tree(".").await {
+    match crate::async_task::load_file_tree(".", None).await {
         Ok(tree) => {
             app.navigator = crate::navigator::NavigatorState::new(tree);
             // Automatically select the first item in the tree
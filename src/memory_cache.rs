@@ -0,0 +1,160 @@
+use crate::line_mapping::LineMapping;
+use gix::{ObjectId, Repository};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+/// Total size of the blob contents kept in memory
+const MAX_BLOB_BYTES: usize = 64 * 1024 * 1024;
+
+/// Line mappings kept in memory
+const MAX_LINE_MAPPINGS: usize = 512;
+
+lazy_static::lazy_static! {
+    // Objects are addressed by their content, so entries are valid whichever repository
+    // handle they were read through
+    static ref BLOBS: Mutex<Lru<ObjectId, Arc<[u8]>>> = Mutex::new(Lru::new(MAX_BLOB_BYTES));
+    static ref LINE_MAPPINGS: Mutex<Lru<(ObjectId, ObjectId), Arc<LineMapping>>> =
+        Mutex::new(Lru::new(MAX_LINE_MAPPINGS));
+}
+
+/// A least recently used cache whose entries each count with a weight towards a maximum
+struct Lru<K, V> {
+    /// Value, weight and last use of each entry
+    entries: HashMap<K, (V, usize, u64)>,
+    /// Entries by last use, oldest first
+    uses: BTreeMap<u64, K>,
+    next_use: u64,
+    weight: usize,
+    max_weight: usize,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Lru<K, V> {
+    fn new(max_weight: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            uses: BTreeMap::new(),
+            next_use: 0,
+            weight: 0,
+            max_weight,
+        }
+    }
+
+    /// Look up an entry, making it the most recently used
+    fn get(&mut self, key: &K) -> Option<V> {
+        let (value, _, last_use) = self.entries.get_mut(key)?;
+        self.uses.remove(last_use);
+        *last_use = self.next_use;
+        self.uses.insert(self.next_use, key.clone());
+        self.next_use += 1;
+        Some(value.clone())
+    }
+
+    /// Add an entry, evicting the least recently used ones until the weights fit again.
+    /// An entry heavier than the maximum on its own is not kept.
+    fn insert(&mut self, key: K, value: V, weight: usize) {
+        self.remove(&key);
+        if weight > self.max_weight {
+            return;
+        }
+        while self.weight + weight > self.max_weight {
+            let Some((_, oldest)) = self.uses.pop_first() else {
+                break;
+            };
+            if let Some((_, oldest_weight, _)) = self.entries.remove(&oldest) {
+                self.weight -= oldest_weight;
+            }
+        }
+        self.uses.insert(self.next_use, key.clone());
+        self.entries.insert(key, (value, weight, self.next_use));
+        self.next_use += 1;
+        self.weight += weight;
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some((_, weight, last_use)) = self.entries.remove(key) {
+            self.uses.remove(&last_use);
+            self.weight -= weight;
+        }
+    }
+}
+
+/// The contents of a blob, read from the repository only if it is not in memory already
+pub fn blob_data(
+    repo: &Repository,
+    id: ObjectId,
+) -> Result<Arc<[u8]>, Box<dyn std::error::Error + Send + Sync>> {
+    if let Some(data) = BLOBS.lock().unwrap().get(&id) {
+        return Ok(data);
+    }
+
+    let blob = repo.find_object(id)?.try_into_blob()?;
+    let data: Arc<[u8]> = Arc::from(blob.detach().data);
+    BLOBS.lock().unwrap().insert(id, data.clone(), data.len());
+    Ok(data)
+}
+
+/// The line mapping between two blobs, if it has been computed before
+pub fn line_mapping(old_blob: ObjectId, new_blob: ObjectId) -> Option<Arc<LineMapping>> {
+    LINE_MAPPINGS.lock().unwrap().get(&(old_blob, new_blob))
+}
+
+pub fn store_line_mapping(old_blob: ObjectId, new_blob: ObjectId, mapping: Arc<LineMapping>) {
+    LINE_MAPPINGS
+        .lock()
+        .unwrap()
+        .insert((old_blob, new_blob), mapping, 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_evicts_least_recently_used() {
+        let mut lru = Lru::new(3);
+        lru.insert("a", 1, 1);
+        lru.insert("b", 2, 1);
+        lru.insert("c", 3, 1);
+
+        // Using "a" makes "b" the oldest
+        assert_eq!(lru.get(&"a"), Some(1));
+        lru.insert("d", 4, 1);
+        assert_eq!(lru.get(&"b"), None);
+        assert_eq!(lru.get(&"a"), Some(1));
+        assert_eq!(lru.entries.len(), 3);
+
+        // A heavy entry pushes out as many as needed; one too heavy is not kept at all
+        lru.insert("e", 5, 2);
+        assert_eq!((lru.get(&"c"), lru.get(&"d")), (None, None));
+        assert_eq!((lru.get(&"a"), lru.get(&"e")), (Some(1), Some(5)));
+        lru.insert("f", 6, 4);
+        assert_eq!(lru.get(&"f"), None);
+        assert_eq!(lru.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_lru_replaces_existing_entries() {
+        let mut lru = Lru::new(2);
+        lru.insert("a", 1, 2);
+        lru.insert("a", 2, 2);
+        assert_eq!(lru.get(&"a"), Some(2));
+        assert_eq!(lru.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_blob_data_is_shared() {
+        let repo = crate::git_utils::open_repository(".").expect("Should open repository");
+        let head_tree = repo.head_commit().unwrap().tree().unwrap();
+        let id = head_tree
+            .lookup_entry_by_path("Cargo.toml")
+            .unwrap()
+            .expect("Cargo.toml should be tracked")
+            .object_id();
+
+        let first = blob_data(&repo, id).unwrap();
+        let second = blob_data(&repo, id).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(std::str::from_utf8(&first).unwrap().contains("[package]"));
+    }
+}
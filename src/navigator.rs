@@ -5,7 +5,7 @@
 
 use crate::tree::{FileTree, TreeNode};
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Events that can be sent to the navigator
 #[derive(Debug, Clone, PartialEq)]
@@ -33,6 +33,7 @@ pub struct VisibleItem {
     pub is_expanded: bool,
     pub is_dir: bool,
     pub git_status: Option<char>,
+    pub staged_status: Option<char>,
 }
 
 /// View model for rendering the navigator
@@ -245,6 +246,49 @@ impl NavigatorState {
             .unwrap_or(false)
     }
 
+    /// Show the staged and unstaged changes of the working tree on the files
+    pub fn set_worktree_status(&mut self, status: crate::git_utils::WorktreeStatus) {
+        self.tree.set_worktree_status(status);
+        // Untracked files may be gone, and the selection with them
+        self.ensure_valid_selection();
+        // Cached search items carry the old status
+        if let Some(results) = &self.cached_search_results {
+            self.cached_search_visible_items = Some(self.get_search_visible_items(results, &self.selection));
+        }
+        self.invalidate_view_model();
+    }
+
+    /// Select a file, expanding the directories that contain it.
+    /// Returns false if the file is not in the tree.
+    pub fn reveal(&mut self, path: PathBuf) -> bool {
+        if self.tree.find_node(&path).is_none() {
+            return false;
+        }
+        let ancestors: Vec<PathBuf> = path
+            .ancestors()
+            .skip(1)
+            .filter(|ancestor| !ancestor.as_os_str().is_empty())
+            .map(|ancestor| ancestor.to_path_buf())
+            .collect();
+        for directory in ancestors.into_iter().rev() {
+            let _ = self.handle_event(NavigatorEvent::SelectFile(directory));
+            let _ = self.handle_event(NavigatorEvent::ExpandSelected);
+        }
+        let _ = self.handle_event(NavigatorEvent::SelectFile(path));
+        true
+    }
+
+    /// Check if a directory is expanded
+    pub fn is_expanded(&self, path: &Path) -> bool {
+        self.expanded.contains(path)
+    }
+
+    /// List the files deleted in the history in the "Deleted files" folder
+    pub fn set_deleted_files(&mut self, deleted_files: &[crate::git_utils::DeletedFile]) {
+        self.tree.set_deleted_files(deleted_files);
+        self.invalidate_view_model();
+    }
+
     /// Build view model for rendering (with caching)
     pub fn build_view_model(&mut self) -> &NavigatorViewModel {
         let current_hash = self.compute_state_hash();
@@ -431,6 +475,10 @@ impl NavigatorState {
     /// Recursively collect all file paths from tree nodes
     fn collect_all_paths(&self, nodes: &[TreeNode], paths: &mut Vec<PathBuf>) {
         for node in nodes {
+            // Deleted files are only listed in their folder, search covers the files of the tree
+            if node.path == Path::new(crate::tree::DELETED_FILES_PATH) {
+                continue;
+            }
             // Only collect files, not directories
             if !node.is_dir {
                 paths.push(node.path.clone());
@@ -470,6 +518,7 @@ impl NavigatorState {
             is_expanded,
             is_dir: node.is_dir,
             git_status: node.git_status,
+            staged_status: node.staged_status,
         });
 
         // If directory is expanded, show children
@@ -588,6 +637,7 @@ impl NavigatorState {
             is_expanded,
             is_dir: node.is_dir,
             git_status: node.git_status,
+            staged_status: node.staged_status,
         });
 
         // If directory is expanded, show children
@@ -1028,6 +1078,48 @@ mod tests {
         assert_eq!(utils_item.unwrap().depth, 1);
     }
     
+    #[test]
+    fn test_worktree_only_files_are_listed() {
+        let mut navigator = NavigatorState::new(create_test_tree());
+        let mut status = crate::git_utils::WorktreeStatus::default();
+        status.unstaged.insert(PathBuf::from("src/new/module.rs"), '?');
+        status.unstaged.insert(PathBuf::from("notes.txt"), '?');
+        navigator.set_worktree_status(status);
+
+        navigator.handle_event(NavigatorEvent::ToggleExpanded(PathBuf::from("src"))).unwrap();
+        navigator.handle_event(NavigatorEvent::ToggleExpanded(PathBuf::from("src/new"))).unwrap();
+        let items: Vec<(PathBuf, Option<char>)> = navigator
+            .build_view_model()
+            .items
+            .iter()
+            .map(|item| (item.path.clone(), item.git_status))
+            .collect();
+
+        // In tree order, directories first, and with their status
+        let expected = [
+            ("src", Some('M')),
+            ("src/new", Some('M')),
+            ("src/new/module.rs", Some('?')),
+            ("src/utils", None),
+            ("src/lib.rs", None),
+            ("src/main.rs", None),
+            ("Cargo.toml", None),
+            ("README.md", None),
+            ("notes.txt", Some('?')),
+        ];
+        let expected: Vec<(PathBuf, Option<char>)> =
+            expected.iter().map(|(path, status)| (PathBuf::from(path), *status)).collect();
+        assert_eq!(items, expected);
+
+        // The files go again once they are no longer in the status
+        navigator.set_worktree_status(crate::git_utils::WorktreeStatus::default());
+        assert!(!navigator
+            .build_view_model()
+            .items
+            .iter()
+            .any(|item| item.path == Path::new("src/new")));
+    }
+
     #[test]
     fn test_start_search_shows_browsing_items() {
         let tree = create_test_tree();
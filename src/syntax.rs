@@ -0,0 +1,346 @@
+use gix::ObjectId;
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+use syntect::easy::ScopeRangeIterator;
+use syntect::highlighting::ScopeSelectors;
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+
+/// Number of highlighted blobs kept around; enough for the current file, its diff parent and
+/// a few neighbouring commits while stepping through history
+const MAX_CACHED_BLOBS: usize = 16;
+
+lazy_static::lazy_static! {
+    static ref SYNTAX_SET: SyntaxSet = SyntaxSet::load_defaults_newlines();
+
+    /// Scope selectors for each token kind, most specific matches win
+    static ref TOKEN_SELECTORS: Vec<(ScopeSelectors, TokenKind)> = [
+        ("comment", TokenKind::Comment),
+        ("string, constant.character", TokenKind::String),
+        ("constant.numeric, constant.language", TokenKind::Constant),
+        ("keyword, storage.modifier, storage.type", TokenKind::Keyword),
+        ("entity.name.function, support.function, variable.function", TokenKind::Function),
+        ("entity.name.type, entity.name.struct, entity.name.enum, entity.name.class, support.type, support.class", TokenKind::Type),
+    ]
+    .into_iter()
+    .map(|(selector, kind)| (ScopeSelectors::from_str(selector).expect("valid scope selector"), kind))
+    .collect();
+}
+
+/// Syntactic category of a token, mapped to colors by the theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Plain,
+    Comment,
+    String,
+    Constant,
+    Keyword,
+    Function,
+    Type,
+}
+
+/// A highlighted piece of a line, as a byte range into the line's text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+/// Highlighting progress for one blob. Lines are highlighted lazily up to the furthest line
+/// that has been displayed, keeping the parser state so scrolling further resumes where it stopped.
+#[derive(Debug)]
+struct HighlightedBlob {
+    syntax_name: String,
+    parse_state: ParseState,
+    scope_stack: ScopeStack,
+    lines: Vec<Vec<Token>>,
+}
+
+/// Identifies content in the highlight cache: its git blob id and the grammar picked for it.
+/// Building a key hashes the whole content, so it is built once per loaded content and kept.
+#[derive(Debug, Clone)]
+pub struct HighlightKey {
+    blob_id: ObjectId,
+    syntax: &'static SyntaxReference,
+}
+
+impl HighlightKey {
+    /// Key for `lines`, the content of `file_path`
+    pub fn new<S: AsRef<str>>(file_path: Option<&Path>, lines: &[S]) -> Self {
+        Self {
+            blob_id: blob_id_of_lines(lines),
+            syntax: find_syntax(file_path, lines.first().map(|line| line.as_ref())),
+        }
+    }
+}
+
+/// Cache of per-token highlighting keyed by the blob id of the highlighted content
+#[derive(Debug, Default)]
+pub struct HighlightCache {
+    blobs: HashMap<ObjectId, HighlightedBlob>,
+    /// Use order, used to evict the least recently used blob when the cache is full
+    order: VecDeque<ObjectId>,
+}
+
+impl HighlightCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Highlight `lines` (the content identified by `key`) up to line `end` (exclusive) and return
+    /// the tokens of all lines highlighted so far, which always covers `0..end` clamped to the content.
+    pub fn highlight<S: AsRef<str>>(
+        &mut self,
+        key: &HighlightKey,
+        lines: &[S],
+        end: usize,
+    ) -> &[Vec<Token>] {
+        let blob_id = key.blob_id;
+        let is_stale = self
+            .blobs
+            .get(&blob_id)
+            .is_some_and(|blob| blob.syntax_name != key.syntax.name);
+        if self.blobs.contains_key(&blob_id) {
+            self.order.retain(|id| *id != blob_id);
+        }
+        if is_stale || !self.blobs.contains_key(&blob_id) {
+            if self.order.len() >= MAX_CACHED_BLOBS {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.blobs.remove(&oldest);
+                }
+            }
+            self.blobs.insert(
+                blob_id,
+                HighlightedBlob {
+                    syntax_name: key.syntax.name.clone(),
+                    parse_state: ParseState::new(key.syntax),
+                    scope_stack: ScopeStack::new(),
+                    lines: Vec::new(),
+                },
+            );
+        }
+        self.order.push_back(blob_id);
+
+        let blob = self.blobs.get_mut(&blob_id).expect("blob was just inserted");
+        let end = end.min(lines.len());
+        while blob.lines.len() < end {
+            let line = lines[blob.lines.len()].as_ref();
+            let tokens = highlight_line(&mut blob.parse_state, &mut blob.scope_stack, line);
+            blob.lines.push(tokens);
+        }
+
+        &blob.lines
+    }
+
+    /// Tokens of the lines of the content identified by `key` highlighted so far
+    pub fn highlighted(&self, key: &HighlightKey) -> &[Vec<Token>] {
+        self.blobs
+            .get(&key.blob_id)
+            .filter(|blob| blob.syntax_name == key.syntax.name)
+            .map_or(&[], |blob| blob.lines.as_slice())
+    }
+}
+
+/// Split a line into tokens using the parser state carried over from the previous line
+fn highlight_line(parse_state: &mut ParseState, scope_stack: &mut ScopeStack, line: &str) -> Vec<Token> {
+    // The bundled grammars expect lines to end in a newline
+    let line_with_newline = format!("{}\n", line);
+    let ops = match parse_state.parse_line(&line_with_newline, &SYNTAX_SET) {
+        Ok(ops) => ops,
+        Err(e) => {
+            log::warn!("Syntax highlighting failed, showing line as plain text: {}", e);
+            return vec![Token {
+                range: 0..line.len(),
+                kind: TokenKind::Plain,
+            }];
+        }
+    };
+
+    let mut tokens: Vec<Token> = Vec::new();
+    for (range, op) in ScopeRangeIterator::new(&ops, &line_with_newline) {
+        // The op yielded with a range applies before it
+        if let Err(e) = scope_stack.apply(op) {
+            log::warn!("Syntax highlighting scope error: {:?}", e);
+        }
+        let range = range.start.min(line.len())..range.end.min(line.len());
+        if !range.is_empty() {
+            let kind = token_kind(scope_stack);
+            match tokens.last_mut() {
+                Some(last) if last.kind == kind && last.range.end == range.start => {
+                    last.range.end = range.end;
+                }
+                _ => tokens.push(Token { range, kind }),
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Token kind for the innermost scopes, picking the best matching selector
+fn token_kind(scope_stack: &ScopeStack) -> TokenKind {
+    TOKEN_SELECTORS
+        .iter()
+        .filter_map(|(selector, kind)| {
+            selector
+                .does_match(scope_stack.as_slice())
+                .map(|power| (power, *kind))
+        })
+        .max_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(_, kind)| kind)
+        .unwrap_or(TokenKind::Plain)
+}
+
+/// Pick a grammar by file extension or name, then by the first line (e.g. a shebang)
+fn find_syntax(file_path: Option<&Path>, first_line: Option<&str>) -> &'static SyntaxReference {
+    let syntax_set: &'static SyntaxSet = &SYNTAX_SET;
+    file_path
+        .and_then(|path| {
+            path.extension()
+                .and_then(|extension| syntax_set.find_syntax_by_extension(&extension.to_string_lossy()))
+                .or_else(|| {
+                    path.file_name()
+                        .and_then(|name| syntax_set.find_syntax_by_extension(&name.to_string_lossy()))
+                })
+        })
+        .or_else(|| first_line.and_then(|line| syntax_set.find_syntax_by_first_line(line)))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text())
+}
+
+/// Git blob id of the content, with each line terminated by a newline
+fn blob_id_of_lines<S: AsRef<str>>(lines: &[S]) -> ObjectId {
+    let size: usize = lines.iter().map(|line| line.as_ref().len() + 1).sum();
+    let mut hasher = gix::hash::hasher(gix::hash::Kind::Sha1);
+    hasher.update(&gix::objs::encode::loose_header(gix::objs::Kind::Blob, size as u64));
+    for line in lines {
+        hasher.update(line.as_ref().as_bytes());
+        hasher.update(b"\n");
+    }
+    hasher
+        .try_finalize()
+        .unwrap_or_else(|_| ObjectId::null(gix::hash::Kind::Sha1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_of<'a>(line: &'a str, tokens: &[Token]) -> Vec<(&'a str, TokenKind)> {
+        tokens
+            .iter()
+            .map(|token| (&line[token.range.clone()], token.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_highlights_tokens_not_whole_lines() {
+        let lines = vec![
+            "fn main() {".to_string(),
+            "    let name = \"world\"; // greet".to_string(),
+            "}".to_string(),
+        ];
+        let mut cache = HighlightCache::new();
+        let key = HighlightKey::new(Some(Path::new("src/main.rs")), &lines);
+        let highlighted = cache.highlight(&key, &lines, lines.len());
+
+        assert_eq!(highlighted.len(), 3);
+        let first = kinds_of(&lines[0], &highlighted[0]);
+        assert!(first.contains(&("fn", TokenKind::Keyword)));
+        assert!(first.contains(&("main", TokenKind::Function)));
+
+        let second = kinds_of(&lines[1], &highlighted[1]);
+        assert!(second.contains(&("let", TokenKind::Keyword)));
+        assert!(second.iter().any(|(text, kind)| text.contains("world") && *kind == TokenKind::String));
+        assert!(second.iter().any(|(text, kind)| text.contains("greet") && *kind == TokenKind::Comment));
+        assert!(second.iter().any(|(text, kind)| text.contains("name") && *kind == TokenKind::Plain));
+    }
+
+    #[test]
+    fn test_tokens_cover_the_whole_line() {
+        let lines = vec!["    let x = 42; /* multi".to_string(), "line */ let y = 1;".to_string()];
+        let mut cache = HighlightCache::new();
+        let key = HighlightKey::new(Some(Path::new("lib.rs")), &lines);
+        let highlighted = cache.highlight(&key, &lines, lines.len());
+
+        for (line, tokens) in lines.iter().zip(highlighted) {
+            let covered: String = tokens.iter().map(|token| &line[token.range.clone()]).collect();
+            assert_eq!(&covered, line);
+        }
+        // The block comment carries over to the next line
+        assert_eq!(kinds_of(&lines[1], &highlighted[1])[0], ("line */", TokenKind::Comment));
+    }
+
+    #[test]
+    fn test_highlights_lazily_and_reuses_blob() {
+        let lines: Vec<String> = (0..100).map(|i| format!("let x{} = {};", i, i)).collect();
+        let key = HighlightKey::new(Some(Path::new("a.rs")), &lines);
+        let mut cache = HighlightCache::new();
+
+        assert_eq!(cache.highlight(&key, &lines, 10).len(), 10);
+        assert_eq!(cache.highlight(&key, &lines, 50).len(), 50);
+        assert_eq!(cache.highlight(&key, &lines, 500).len(), 100);
+        assert_eq!(cache.highlighted(&key).len(), 100);
+        assert_eq!(cache.blobs.len(), 1);
+
+        // Same content under another commit shares the cache entry
+        let same = lines.clone();
+        cache.highlight(&HighlightKey::new(Some(Path::new("a.rs")), &same), &same, 100);
+        assert_eq!(cache.blobs.len(), 1);
+
+        let other = vec!["let y = 1;".to_string()];
+        cache.highlight(&HighlightKey::new(Some(Path::new("a.rs")), &other), &other, 1);
+        assert_eq!(cache.blobs.len(), 2);
+
+        // Another grammar for the same content highlights it again
+        let plain = HighlightKey::new(Some(Path::new("a.unknown")), &lines);
+        assert!(cache.highlighted(&plain).is_empty());
+        cache.highlight(&plain, &lines, 1);
+        assert_eq!(cache.highlighted(&plain).len(), 1);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used_blobs() {
+        let mut cache = HighlightCache::new();
+        let first = vec!["let first = 0;".to_string()];
+        let first_key = HighlightKey::new(Some(Path::new("a.rs")), &first);
+        cache.highlight(&first_key, &first, 1);
+        for i in 0..MAX_CACHED_BLOBS + 5 {
+            let lines = vec![format!("let x = {};", i)];
+            cache.highlight(&HighlightKey::new(Some(Path::new("a.rs")), &lines), &lines, 1);
+            // Content drawn every frame stays cached
+            cache.highlight(&first_key, &first, 1);
+        }
+        assert_eq!(cache.blobs.len(), MAX_CACHED_BLOBS);
+        assert_eq!(cache.highlighted(&first_key).len(), 1);
+    }
+
+    #[test]
+    fn test_syntax_selection() {
+        assert_eq!(find_syntax(Some(Path::new("src/main.rs")), None).name, "Rust");
+        assert_eq!(find_syntax(Some(Path::new("script.py")), None).name, "Python");
+        assert_eq!(find_syntax(Some(Path::new("bin/run")), Some("#!/bin/bash\n")).name, "Bourne Again Shell (bash)");
+        assert_eq!(find_syntax(Some(Path::new("notes.unknown")), Some("hello")).name, "Plain Text");
+        assert_eq!(find_syntax(None, None).name, "Plain Text");
+    }
+
+    #[test]
+    fn test_plain_text_is_a_single_token() {
+        let lines = vec!["it's \"quoted\" text".to_string()];
+        let mut cache = HighlightCache::new();
+        let key = HighlightKey::new(Some(Path::new("README.unknown")), &lines);
+        let highlighted = cache.highlight(&key, &lines, 1);
+
+        assert_eq!(highlighted[0], vec![Token { range: 0..lines[0].len(), kind: TokenKind::Plain }]);
+    }
+
+    #[test]
+    fn test_blob_id_matches_git() {
+        // `printf 'hello\nworld\n' | git hash-object --stdin`
+        let lines = vec!["hello", "world"];
+        assert_eq!(
+            blob_id_of_lines(&lines).to_string(),
+            "94954abda49de8615a048f8d2e64b5de848e27a1"
+        );
+    }
+}
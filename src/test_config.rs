@@ -1,10 +1,17 @@
-use crate::app::{CommitInfo, PanelFocus};
+use crate::app::{
+    BlameLine, ChangedFile, CommitInfo, ContainingRefs, DiffBase, HistoryMode, LineRange, PanelFocus,
+    RefInfo,
+};
+use crate::navigator::{NavigatorEvent, NavigatorState};
 use crate::tree::{FileTree, TreeNode};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestConfig {
+    /// Revision the file tree and histories are rooted at (None for HEAD)
+    #[serde(default)]
+    pub revision: Option<String>,
     pub active_panel: PanelFocus,
     pub file_tree: FileTree,
     pub selected_file_navigator_index: Option<usize>,
@@ -18,6 +25,29 @@ pub struct TestConfig {
     pub inspector_scroll_vertical: u16,
     pub inspector_scroll_horizontal: u16,
     pub show_diff_view: bool,
+    #[serde(default)]
+    pub diff_side_by_side: bool,
+    #[serde(default)]
+    pub diff_base: DiffBase,
+    #[serde(default)]
+    pub show_blame: bool,
+    #[serde(default)]
+    pub current_blame: Option<Vec<BlameLine>>,
+    #[serde(default)]
+    pub selection_anchor: Option<usize>,
+    #[serde(default)]
+    pub line_range: Option<LineRange>,
+    #[serde(default)]
+    pub show_commit_details: bool,
+    #[serde(default)]
+    pub history_mode: HistoryMode,
+    #[serde(default)]
+    pub changed_files: Option<Vec<ChangedFile>>,
+    #[serde(default)]
+    pub containing_refs: Option<ContainingRefs>,
+    /// References decorating the commits they point at
+    #[serde(default)]
+    pub refs: Vec<RefInfo>,
     pub status_message: String,
     pub is_loading: bool,
     pub selected_commit_hash: Option<String>,
@@ -47,6 +77,7 @@ impl Default for TestConfig {
         file_tree.root.push(cargo_toml);
 
         Self {
+            revision: None,
             active_panel: PanelFocus::Navigator,
             file_tree,
             selected_file_navigator_index: Some(0),
@@ -59,6 +90,8 @@ impl Default for TestConfig {
                     author: "John Doe".to_string(),
                     date: "2 hours ago".to_string(),
                     subject: "Add new feature".to_string(),
+                    file_path: None,
+                    ..Default::default()
                 },
                 CommitInfo {
                     hash: "b2c3d4e5f6789012345678901234567890abcdef".to_string(),
@@ -66,6 +99,8 @@ impl Default for TestConfig {
                     author: "Jane Smith".to_string(),
                     date: "1 day ago".to_string(),
                     subject: "Fix bug in parser".to_string(),
+                    file_path: None,
+                    ..Default::default()
                 },
                 CommitInfo {
                     hash: "c3d4e5f6789012345678901234567890abcdef01".to_string(),
@@ -73,6 +108,8 @@ impl Default for TestConfig {
                     author: "Bob Johnson".to_string(),
                     date: "3 days ago".to_string(),
                     subject: "Initial commit".to_string(),
+                    file_path: None,
+                    ..Default::default()
                 },
             ],
             selected_commit_index: Some(0),
@@ -92,6 +129,17 @@ impl Default for TestConfig {
             inspector_scroll_vertical: 0,
             inspector_scroll_horizontal: 0,
             show_diff_view: false,
+            diff_side_by_side: false,
+            diff_base: DiffBase::default(),
+            show_blame: false,
+            current_blame: None,
+            selection_anchor: None,
+            line_range: None,
+            show_commit_details: false,
+            history_mode: HistoryMode::default(),
+            changed_files: None,
+            containing_refs: None,
+            refs: Vec::new(),
             status_message: "Ready".to_string(),
             is_loading: false,
             selected_commit_hash: Some("a1b2c3d4e5f6789012345678901234567890abcd".to_string()),
@@ -112,9 +160,33 @@ impl TestConfig {
         Ok(())
     }
 
+    /// The file navigator with the configured search and selection applied
+    pub fn navigator(&self) -> NavigatorState {
+        let mut navigator = NavigatorState::new(self.file_tree.clone());
+
+        // Set search mode if specified in test config
+        if self.in_search_mode {
+            let _ = navigator.handle_event(NavigatorEvent::StartSearch);
+            if !self.search_query.is_empty() {
+                let _ = navigator.handle_event(NavigatorEvent::UpdateSearchQuery(self.search_query.clone()));
+            }
+        }
+
+        // Set initial selection if we have a selected index
+        if let Some(index) = self.selected_file_navigator_index {
+            // Navigate down to the selected index
+            for _ in 0..index {
+                let _ = navigator.handle_event(NavigatorEvent::NavigateDown);
+            }
+        }
+
+        navigator
+    }
+
     pub fn from_app(app: &mut crate::app::App) -> Self {
         let view_model = app.navigator.build_view_model();
         TestConfig {
+            revision: app.revision.clone(),
             active_panel: app.ui.active_panel.clone(),
             file_tree: crate::tree::FileTree::new(), // TODO: Add public getter for navigator tree
             selected_file_navigator_index: Some(view_model.cursor_position),
@@ -128,6 +200,21 @@ impl TestConfig {
             inspector_scroll_vertical: app.inspector.scroll_vertical,
             inspector_scroll_horizontal: app.inspector.scroll_horizontal,
             show_diff_view: app.inspector.show_diff_view,
+            diff_side_by_side: app.inspector.diff_side_by_side,
+            diff_base: app.inspector.diff_base.clone(),
+            show_blame: app.inspector.show_blame,
+            current_blame: app.inspector.current_blame.clone(),
+            selection_anchor: app.inspector.selection_anchor,
+            line_range: app.history.line_range.clone(),
+            show_commit_details: app.history.show_commit_details,
+            history_mode: app.history.mode,
+            changed_files: app.history.changed_files.clone(),
+            containing_refs: app.history.containing_refs.clone(),
+            refs: {
+                let mut refs: Vec<RefInfo> = app.refs_by_commit.values().flatten().cloned().collect();
+                refs.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| a.name.cmp(&b.name)));
+                refs
+            },
             status_message: app.ui.status_message.clone(),
             is_loading: app.ui.is_loading,
             selected_commit_hash: app.history.selected_commit_hash.clone(),
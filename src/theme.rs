@@ -15,6 +15,7 @@ pub struct Theme {
     pub file_git_added: Color,
     pub file_git_deleted: Color,
     pub file_git_untracked: Color,
+    pub file_git_staged: Color,
     pub file_default: Color,
     pub search_text: Color,
 
@@ -24,24 +25,36 @@ pub struct Theme {
     pub commit_date: Color,
     pub commit_selected_bg: Color,
     pub commit_selected_fg: Color,
+    pub commit_diff_base: Color,
+    pub commit_graph: Color,
+    pub commit_trailer: Color,
+    pub ref_head: Color,
+    pub ref_branch: Color,
+    pub ref_remote: Color,
+    pub ref_tag: Color,
 
     // Code inspector
     pub line_numbers: Color,
     pub line_numbers_current: Color,
     pub code_background_current: Color,
     pub code_foreground_current: Color,
+    pub code_background_selected: Color,
     pub syntax_keyword: Color,
     pub syntax_string: Color,
     pub syntax_comment: Color,
+    pub syntax_constant: Color,
+    pub syntax_function: Color,
+    pub syntax_type: Color,
     pub code_default: Color,
+    pub blame_gutter: Color,
     
     // Diff view colors
     pub diff_added_fg: Color,
     pub diff_added_bg: Color,
+    pub diff_added_emphasis_bg: Color,
     pub diff_removed_fg: Color,
     pub diff_removed_bg: Color,
-    pub diff_modified_fg: Color,
-    pub diff_modified_bg: Color,
+    pub diff_removed_emphasis_bg: Color,
 
     // Status bar
     pub status_bar_bg: Color,
@@ -68,6 +81,7 @@ impl Default for Theme {
             file_git_added: Color::Green,
             file_git_deleted: Color::Red,
             file_git_untracked: Color::Magenta,
+            file_git_staged: Color::Green,
             file_default: Color::Reset,
             search_text: Color::Gray,
 
@@ -77,24 +91,36 @@ impl Default for Theme {
             commit_date: Color::Blue,
             commit_selected_bg: Color::White,
             commit_selected_fg: Color::Black,
+            commit_diff_base: Color::Magenta,
+            commit_graph: Color::DarkGray,
+            commit_trailer: Color::Cyan,
+            ref_head: Color::LightCyan,
+            ref_branch: Color::LightGreen,
+            ref_remote: Color::LightRed,
+            ref_tag: Color::LightYellow,
 
             // Code inspector
             line_numbers: Color::Blue,
             line_numbers_current: Color::Yellow,
             code_background_current: Color::White,
             code_foreground_current: Color::Black,
+            code_background_selected: Color::DarkGray,
             syntax_keyword: Color::Magenta,
             syntax_string: Color::Green,
             syntax_comment: Color::Yellow,
+            syntax_constant: Color::LightRed,
+            syntax_function: Color::LightBlue,
+            syntax_type: Color::Cyan,
             code_default: Color::Reset,
+            blame_gutter: Color::DarkGray,
             
             // Diff view colors
             diff_added_fg: Color::Green,
-            diff_added_bg: Color::Reset,
+            diff_added_bg: Color::Rgb(0, 48, 0),
+            diff_added_emphasis_bg: Color::Rgb(0, 112, 0),
             diff_removed_fg: Color::Red,
-            diff_removed_bg: Color::Reset,
-            diff_modified_fg: Color::Yellow,
-            diff_modified_bg: Color::Reset,
+            diff_removed_bg: Color::Rgb(64, 0, 0),
+            diff_removed_emphasis_bg: Color::Rgb(144, 0, 0),
 
             // Status bar
             status_bar_bg: Color::DarkGray,
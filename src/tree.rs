@@ -2,7 +2,11 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
-use crate::git_utils::{self, GitTreeEntry};
+use crate::git_utils::{self, DeletedFile, GitTreeEntry};
+
+/// Path of the virtual folder that lists the files deleted in the history. Git never tracks
+/// anything below `.git`, so it cannot clash with a real path.
+pub const DELETED_FILES_PATH: &str = ".git/deleted";
 
 /// Represents a single node in the file tree
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -10,7 +14,13 @@ pub struct TreeNode {
     pub name: String,
     pub path: PathBuf,
     pub is_dir: bool,
+    /// Unstaged change of a file as in `git status --short` ('M', 'D', 'U' or '?');
+    /// for a directory, 'M' if any file below it has one
     pub git_status: Option<char>,
+    /// Change of a file staged in the index ('A', 'M' or 'D');
+    /// for a directory, 'M' if any file below it has one
+    #[serde(default)]
+    pub staged_status: Option<char>,
     pub is_expanded: bool,
     pub children: Vec<TreeNode>,
     pub parent_path: Option<PathBuf>,
@@ -24,6 +34,7 @@ impl TreeNode {
             path: path.clone(),
             is_dir,
             git_status: None,
+            staged_status: None,
             is_expanded: false,
             children: Vec::new(),
             parent_path: path.parent().map(|p| p.to_path_buf()),
@@ -135,8 +146,14 @@ impl TreeNode {
 pub struct FileTree {
     pub root: Vec<TreeNode>,
     pub git_status_map: HashMap<PathBuf, char>,
+    #[serde(default)]
+    pub staged_status_map: HashMap<PathBuf, char>,
     #[serde(skip)]
     pub repo_root: PathBuf,
+    /// Nodes added for files that are only in the working tree and the directories they are in,
+    /// which are replaced with the working tree status
+    #[serde(skip)]
+    worktree_only_nodes: Vec<PathBuf>,
 }
 
 impl Default for FileTree {
@@ -151,12 +168,19 @@ impl FileTree {
         Self {
             root: Vec::new(),
             git_status_map: HashMap::new(),
+            staged_status_map: HashMap::new(),
             repo_root: PathBuf::new(),
+            worktree_only_nodes: Vec::new(),
         }
     }
 
     /// Build tree from a directory path using Git HEAD tree
     pub fn from_directory<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        Self::from_revision(path, None)
+    }
+
+    /// Build the tree of the repository at `path` as of `revision` (HEAD if None)
+    pub fn from_revision<P: AsRef<Path>>(path: P, revision: Option<&str>) -> Result<Self, std::io::Error> {
         let start_time = Instant::now();
         let path_ref = path.as_ref();
         log::info!(
@@ -169,7 +193,7 @@ impl FileTree {
 
         // Use Git tree walking instead of filesystem walking
         let scan_start = Instant::now();
-        tree.scan_git_tree(path_ref)?;
+        tree.scan_git_tree(path_ref, revision)?;
         log::debug!(
             "🕐 FileTree::from_directory: Git tree scan took: {:?}",
             scan_start.elapsed()
@@ -188,7 +212,7 @@ impl FileTree {
     // Old filesystem-based scanning methods removed - now using Git tree walking
 
     /// Scan Git tree and build the file tree structure
-    fn scan_git_tree(&mut self, repo_path: &Path) -> Result<(), std::io::Error> {
+    fn scan_git_tree(&mut self, repo_path: &Path, revision: Option<&str>) -> Result<(), std::io::Error> {
         let start_time = Instant::now();
         log::debug!("🕐 scan_git_tree: Starting Git tree scan for: {:?}", repo_path);
 
@@ -200,8 +224,8 @@ impl FileTree {
             )
         })?;
 
-        // Get all Git tree entries
-        let git_entries = git_utils::get_git_tree_entries(&repo).map_err(|e| {
+        // Get all Git tree entries; the files only in the working tree come with its status
+        let git_entries = git_utils::get_git_tree_entries(&repo, revision).map_err(|e| {
             std::io::Error::new(
                 std::io::ErrorKind::Other,
                 format!("Failed to walk Git tree: {}", e),
@@ -217,6 +241,14 @@ impl FileTree {
         // Build tree structure from Git entries
         let build_start = Instant::now();
         self.build_tree_from_git_entries(git_entries)?;
+        // Filled in once the deleted files have been looked up, which takes a walk of the history.
+        // Without a commit there is no history to look at.
+        if git_utils::resolve_revision(&repo, revision).is_ok() {
+            self.root.push(TreeNode::new_dir(
+                "Deleted files".to_string(),
+                PathBuf::from(DELETED_FILES_PATH),
+            ));
+        }
         log::debug!(
             "🕐 scan_git_tree: Tree building from Git entries took: {:?}",
             build_start.elapsed()
@@ -335,26 +367,134 @@ impl FileTree {
         self.apply_git_status_to_tree();
     }
 
+    /// Set the staged and unstaged changes of the working tree
+    /// Show the working tree status on the files. Files that are untracked or newly added to the
+    /// index are added to the tree, in place of the ones of the previous status.
+    pub fn set_worktree_status(&mut self, status: git_utils::WorktreeStatus) {
+        for path in std::mem::take(&mut self.worktree_only_nodes) {
+            self.remove_node(&path);
+        }
+        let mut new_files: Vec<&PathBuf> = status
+            .unstaged
+            .iter()
+            .filter(|(_, status)| **status == '?')
+            .chain(status.staged.iter().filter(|(_, status)| **status == 'A'))
+            .map(|(path, _)| path)
+            .collect();
+        new_files.sort();
+        for path in new_files {
+            self.add_worktree_only_file(path);
+        }
+
+        self.staged_status_map = status.staged;
+        self.git_status_map = status.unstaged;
+        self.apply_git_status_to_tree();
+    }
+
+    /// Add a file that is not in the tree, with the directories it is in
+    fn add_worktree_only_file(&mut self, path: &Path) {
+        let dirs: Vec<&Path> = path
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| !dir.as_os_str().is_empty())
+            .collect();
+        for dir in dirs.into_iter().rev() {
+            if self.add_node(TreeNode::new_dir(file_name(dir), dir.to_path_buf())) {
+                self.worktree_only_nodes.push(dir.to_path_buf());
+            }
+        }
+        if self.add_node(TreeNode::new_file(file_name(path), path.to_path_buf())) {
+            self.worktree_only_nodes.push(path.to_path_buf());
+        }
+    }
+
+    /// Add a node to its directory, or to the top level before the "Deleted files" folder.
+    /// Returns false if the directory already has a node at that path.
+    fn add_node(&mut self, node: TreeNode) -> bool {
+        match node.path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            Some(parent) => match self.find_node_mut(parent) {
+                Some(parent) if parent.is_dir && parent.find_child(&node.path).is_none() => {
+                    parent.add_child(node);
+                    true
+                }
+                _ => false,
+            },
+            None => {
+                if self.root.iter().any(|root| root.path == node.path) {
+                    return false;
+                }
+                let end = self
+                    .root
+                    .iter()
+                    .position(|root| root.path == Path::new(DELETED_FILES_PATH))
+                    .unwrap_or(self.root.len());
+                // Directories first, then files, both alphabetically
+                let index = self.root[..end]
+                    .partition_point(|root| (!root.is_dir, &root.name) < (!node.is_dir, &node.name));
+                self.root.insert(index, node);
+                true
+            }
+        }
+    }
+
+    fn remove_node(&mut self, path: &Path) {
+        match path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            Some(parent) => {
+                if let Some(parent) = self.find_node_mut(parent) {
+                    parent.remove_child(path);
+                }
+            }
+            None => self.root.retain(|root| root.path != path),
+        }
+    }
+
     /// Apply git status to all nodes in the tree
     fn apply_git_status_to_tree(&mut self) {
-        let git_status_map = self.git_status_map.clone();
         for node in &mut self.root {
-            Self::apply_git_status_to_node_static(node, &git_status_map);
+            Self::apply_git_status_to_node_static(node, &self.git_status_map, &self.staged_status_map);
         }
     }
 
-    /// Recursively apply git status to a node and its children (static version)
+    /// Recursively apply git status to a node and its children (static version).
+    /// Directories are marked as changed if any file below them is.
     fn apply_git_status_to_node_static(
         node: &mut TreeNode,
         git_status_map: &HashMap<PathBuf, char>,
+        staged_status_map: &HashMap<PathBuf, char>,
     ) {
-        if let Some(&status) = git_status_map.get(&node.path) {
-            node.git_status = Some(status);
+        if !node.is_dir {
+            node.git_status = git_status_map.get(&node.path).copied();
+            node.staged_status = staged_status_map.get(&node.path).copied();
+            return;
         }
 
+        let (mut unstaged, mut staged) = (false, false);
         for child in &mut node.children {
-            Self::apply_git_status_to_node_static(child, git_status_map);
+            Self::apply_git_status_to_node_static(child, git_status_map, staged_status_map);
+            unstaged |= child.git_status.is_some();
+            staged |= child.staged_status.is_some();
         }
+        node.git_status = unstaged.then_some('M');
+        node.staged_status = staged.then_some('M');
+    }
+
+    /// List files deleted in the history in the "Deleted files" folder, under their full path
+    /// and the commit that deleted them. Files that are in the tree anyway are left out.
+    pub fn set_deleted_files(&mut self, deleted_files: &[DeletedFile]) {
+        let folder_path = Path::new(DELETED_FILES_PATH);
+        let Some(index) = self.root.iter().position(|node| node.path == folder_path) else {
+            return;
+        };
+        let mut folder = self.root.remove(index);
+        folder.children = deleted_files
+            .iter()
+            .filter(|file| self.find_node(Path::new(&file.path)).is_none())
+            .map(|file| {
+                let name = format!("{} (deleted in {})", file.path, &file.commit_hash[..8]);
+                TreeNode::new_file(name, PathBuf::from(&file.path))
+            })
+            .collect();
+        self.root.insert(index, folder);
     }
 
     /// Find a node by path
@@ -508,7 +648,7 @@ impl FileTree {
             stats.files += 1;
         }
 
-        if node.git_status.is_some() {
+        if !node.is_dir && (node.git_status.is_some() || node.staged_status.is_some()) {
             stats.files_with_git_status += 1;
         }
 
@@ -521,6 +661,12 @@ impl FileTree {
     }
 }
 
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
 /// Statistics about the file tree
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct TreeStats {
@@ -694,6 +840,37 @@ mod tests {
         assert_eq!(node.git_status, Some('M'));
     }
 
+    #[test]
+    fn test_file_tree_worktree_status_marks_directories() {
+        let mut tree = FileTree::new();
+        let mut src = TreeNode::new_dir("src".to_string(), PathBuf::from("src"));
+        let mut nested = TreeNode::new_dir("nested".to_string(), PathBuf::from("src/nested"));
+        nested.add_child(TreeNode::new_file("a.rs".to_string(), PathBuf::from("src/nested/a.rs")));
+        src.add_child(nested);
+        src.add_child(TreeNode::new_file("b.rs".to_string(), PathBuf::from("src/b.rs")));
+        tree.root.push(src);
+        tree.root.push(TreeNode::new_dir("docs".to_string(), PathBuf::from("docs")));
+
+        let mut status = git_utils::WorktreeStatus::default();
+        status.staged.insert(PathBuf::from("src/nested/a.rs"), 'M');
+        status.unstaged.insert(PathBuf::from("src/b.rs"), 'D');
+        tree.set_worktree_status(status);
+
+        let statuses = |tree: &FileTree, path: &str| {
+            let node = tree.find_node(Path::new(path)).unwrap();
+            (node.staged_status, node.git_status)
+        };
+        assert_eq!(statuses(&tree, "src/nested/a.rs"), (Some('M'), None));
+        assert_eq!(statuses(&tree, "src/nested"), (Some('M'), None));
+        assert_eq!(statuses(&tree, "src"), (Some('M'), Some('M')));
+        assert_eq!(statuses(&tree, "docs"), (None, None));
+
+        // A refreshed status clears files that are no longer changed
+        tree.set_worktree_status(git_utils::WorktreeStatus::default());
+        assert_eq!(statuses(&tree, "src"), (None, None));
+        assert_eq!(statuses(&tree, "src/b.rs"), (None, None));
+    }
+
     #[test]
     fn test_file_tree_search() {
         // Search functionality is now in FileTreeState, not FileTree
@@ -785,6 +962,86 @@ mod tests {
         assert!(not_found.is_none());
     }
 
+    #[test]
+    fn test_tree_shows_working_tree_files() {
+        use crate::git_utils::tests::{init_test_repo, run_git};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let temp_path = init_test_repo(&temp_dir);
+        std::fs::create_dir_all(temp_path.join("src")).unwrap();
+        std::fs::write(temp_path.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(temp_path.join("src/old.rs"), "// old").unwrap();
+        std::fs::write(temp_path.join(".gitignore"), "*.log\n").unwrap();
+        run_git(temp_path, &["add", "."]);
+        run_git(temp_path, &["commit", "-m", "Initial commit"]);
+
+        std::fs::remove_file(temp_path.join("src/old.rs")).unwrap();
+        std::fs::create_dir_all(temp_path.join("src/new")).unwrap();
+        std::fs::write(temp_path.join("src/new/module.rs"), "// new").unwrap();
+        std::fs::write(temp_path.join("notes.txt"), "notes").unwrap();
+        std::fs::write(temp_path.join("debug.log"), "ignored").unwrap();
+
+        // Files only on disk come with the working tree status
+        let repo = git_utils::open_repository(temp_path).unwrap();
+        let mut tree = FileTree::from_directory(temp_path).unwrap();
+        assert!(tree.find_node(Path::new("notes.txt")).is_none());
+        tree.set_worktree_status(git_utils::get_worktree_status(&repo).unwrap());
+        let status = |tree: &FileTree, path: &str| tree.find_node(Path::new(path)).map(|node| node.git_status);
+
+        assert_eq!(status(&tree, "src/main.rs"), Some(None));
+        assert_eq!(status(&tree, "src/old.rs"), Some(Some('D')));
+        assert_eq!(status(&tree, "src/new/module.rs"), Some(Some('?')));
+        assert_eq!(status(&tree, "notes.txt"), Some(Some('?')));
+        assert_eq!(status(&tree, ".gitignore"), Some(None));
+        assert_eq!(status(&tree, "debug.log"), None);
+        assert!(tree.find_node(Path::new("src/new")).unwrap().is_dir);
+        assert_eq!(status(&tree, "src"), Some(Some('M')));
+        assert_eq!(tree.root.last().unwrap().path, Path::new(DELETED_FILES_PATH));
+
+        // A refreshed status replaces the files only on disk
+        std::fs::remove_file(temp_path.join("notes.txt")).unwrap();
+        std::fs::remove_dir_all(temp_path.join("src/new")).unwrap();
+        std::fs::write(temp_path.join("src/added.rs"), "// added").unwrap();
+        run_git(temp_path, &["add", "src/added.rs"]);
+        tree.set_worktree_status(git_utils::get_worktree_status(&repo).unwrap());
+        assert!(tree.find_node(Path::new("notes.txt")).is_none());
+        assert!(tree.find_node(Path::new("src/new")).is_none());
+        assert_eq!(tree.find_node(Path::new("src/added.rs")).unwrap().staged_status, Some('A'));
+        assert_eq!(status(&tree, "src/main.rs"), Some(None));
+
+        // Other revisions only show their own files
+        let tree = FileTree::from_revision(temp_path, Some("HEAD")).unwrap();
+        assert!(tree.find_node(Path::new("notes.txt")).is_none());
+        assert_eq!(tree.find_node(Path::new("src/old.rs")).unwrap().git_status, None);
+    }
+
+    #[test]
+    fn test_deleted_files_folder() {
+        let mut tree = FileTree::new();
+        tree.root.push(TreeNode::new_file("old.rs".to_string(), PathBuf::from("old.rs")));
+        tree.root.push(TreeNode::new_dir(
+            "Deleted files".to_string(),
+            PathBuf::from(DELETED_FILES_PATH),
+        ));
+
+        let deleted = |path: &str| DeletedFile {
+            path: path.to_string(),
+            commit_hash: "0123456789abcdef0123456789abcdef01234567".to_string(),
+        };
+        tree.set_deleted_files(&[deleted("old.rs"), deleted("src/gone.rs")]);
+
+        // Files that are back in the tree are not listed again
+        let folder = tree.find_node(Path::new(DELETED_FILES_PATH)).unwrap();
+        assert_eq!(folder.children.len(), 1);
+        let gone = tree.find_node(Path::new("src/gone.rs")).unwrap();
+        assert_eq!(gone.name, "src/gone.rs (deleted in 01234567)");
+        assert!(!gone.is_dir);
+
+        // Setting them again replaces the list
+        tree.set_deleted_files(&[]);
+        assert!(tree.find_node(Path::new("src/gone.rs")).is_none());
+    }
+
     #[test]
     fn test_tree_loading_determinism() {
         use std::time::Instant;
@@ -1262,7 +1519,8 @@ mod tests {
             .expect("Failed to commit files");
 
         let tree = FileTree::from_directory(special_dir.path()).unwrap();
-        assert_eq!(tree.root.len(), 3);
+        // The three files and the "Deleted files" folder
+        assert_eq!(tree.root.len(), 4);
 
         let names: Vec<String> = tree.root.iter().map(|n| n.name.clone()).collect();
         assert!(names.contains(&"file with spaces.txt".to_string()));
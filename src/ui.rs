@@ -2,13 +2,17 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::Style,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 use std::path::PathBuf;
 
-use crate::app::{App, PanelFocus};
-use crate::theme::get_theme;
+use crate::app::{
+    revision_label, App, ChangedFile, ContainingRefs, DiffBase, DiffSides, FileChangeKind, HistoryMode, PanelFocus, RefInfo,
+    RefKind, RefPickerState,
+};
+use crate::syntax::{HighlightCache, HighlightKey, Token, TokenKind};
+use crate::theme::{get_theme, Theme};
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
@@ -31,6 +35,105 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
     draw_commit_history(frame, app, left_chunks[1]);
     draw_code_inspector(frame, app, chunks[1]);
     draw_status_bar(frame, app, status_chunks[1]);
+
+    if let Some(picker) = &app.ui.ref_picker {
+        draw_ref_picker(frame, picker, frame.area());
+    }
+}
+
+fn draw_ref_picker(frame: &mut Frame, picker: &RefPickerState, area: Rect) {
+    let theme = get_theme();
+    let width = area.width.min(80);
+    let height = area.height.min(20);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let block = Block::default()
+        .title(" Switch revision ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.active_border));
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    let mut prompt = vec![
+        Span::styled("> ", Style::default().fg(theme.panel_title)),
+        Span::styled(picker.query.clone(), Style::default().fg(theme.search_text)),
+    ];
+    // Enter takes the query itself when it resolves, so show where it leads
+    if let Some(commit) = picker.query_commit.as_ref().filter(|_| picker.uses_query()) {
+        prompt.push(Span::styled(
+            format!("  → {} (↓: pick a ref)", &commit[..8.min(commit.len())]),
+            Style::default().fg(theme.commit_hash),
+        ));
+    }
+    let prompt = Line::from(prompt);
+    frame.render_widget(Paragraph::new(prompt), chunks[0]);
+    frame.set_cursor_position((
+        chunks[0].x + 2 + picker.query.chars().count() as u16,
+        chunks[0].y,
+    ));
+
+    let refs = picker.filtered_refs();
+    if picker.refs.is_none() || refs.is_empty() {
+        let message = if picker.refs.is_none() {
+            "Loading refs..."
+        } else {
+            "No matching refs - Enter resolves the text as a revision"
+        };
+        let paragraph =
+            Paragraph::new(message).style(Style::default().fg(theme.panel_title));
+        frame.render_widget(paragraph, chunks[1]);
+        return;
+    }
+
+    let name_width = refs.iter().map(|r| r.name.chars().count()).max().unwrap_or(0);
+    let items: Vec<ListItem> = refs
+        .iter()
+        .map(|r| {
+            let kind = match r.kind {
+                RefKind::Head => "head",
+                RefKind::Branch => "branch",
+                RefKind::RemoteBranch => "remote",
+                RefKind::Tag => "tag",
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<width$} ", r.name, width = name_width),
+                    Style::default().fg(theme.file_default),
+                ),
+                Span::styled(format!("{:<7}", kind), Style::default().fg(theme.commit_trailer)),
+                Span::styled(
+                    format!("{} ", &r.commit_hash[..8.min(r.commit_hash.len())]),
+                    Style::default().fg(theme.commit_hash),
+                ),
+                Span::styled(r.date.clone(), Style::default().fg(theme.commit_date)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .bg(theme.commit_selected_bg)
+                .fg(theme.commit_selected_fg),
+        )
+        .highlight_symbol(">> ");
+
+    let mut list_state = ListState::default();
+    if !picker.uses_query() {
+        list_state.select(Some(picker.selected.min(refs.len() - 1)));
+    }
+    frame.render_stateful_widget(list, chunks[1], &mut list_state);
 }
 
 fn draw_file_navigator(frame: &mut Frame, app: &mut App, area: Rect) {
@@ -46,6 +149,8 @@ fn draw_file_navigator(frame: &mut Frame, app: &mut App, area: Rect) {
 
     let title = if view_model.is_searching || !view_model.search_query.is_empty() {
         format!(" File Navigator (Search: {}) ", view_model.search_query)
+    } else if app.revision.is_some() {
+        format!(" File Navigator @ {} ", revision_label(app.revision.as_deref()))
     } else {
         " File Navigator ".to_string()
     };
@@ -78,49 +183,52 @@ fn draw_file_navigator(frame: &mut Frame, app: &mut App, area: Rect) {
         .items
         .iter()
         .map(|item| {
-            let status_char = match item.git_status {
-                Some('M') => 'M',
-                Some('A') => 'A',
-                Some('D') => 'D',
-                Some('?') => '?',
-                _ => ' ',
+            let status_style = |status: Option<char>| match status {
+                Some('M') => Style::default().fg(theme.file_git_modified),
+                Some('A') => Style::default().fg(theme.file_git_added),
+                Some('D') | Some('U') => Style::default().fg(theme.file_git_deleted),
+                Some('?') => Style::default().fg(theme.file_git_untracked),
+                _ => Style::default().fg(theme.file_default),
             };
+            let indent = " ".repeat(item.depth * 2);
 
-            let display_name = if item.is_dir {
+            // Files show their staged and unstaged change like `git status --short`;
+            // directories are marked if anything below them changed
+            let spans = if item.is_dir {
                 let expand_char = if item.is_expanded { "▼" } else { "▶" };
-                if item.depth == 0 {
-                    format!("{} {}", expand_char, item.name)
-                } else {
-                    format!(
-                        "{}{} {}",
-                        " ".repeat(item.depth * 2),
-                        expand_char,
-                        item.name
-                    )
-                }
+                let has_changes = item.git_status.is_some() || item.staged_status.is_some();
+                let directory_style = Style::default()
+                    .fg(theme.file_directory)
+                    .add_modifier(ratatui::style::Modifier::BOLD);
+                vec![
+                    Span::styled(format!("{}{}", indent, expand_char), directory_style),
+                    Span::styled(
+                        if has_changes { "•" } else { " " },
+                        Style::default().fg(theme.file_git_modified),
+                    ),
+                    Span::styled(format!(" {}", item.name), directory_style),
+                ]
             } else {
-                if item.depth == 0 {
-                    if status_char == ' ' {
-                        format!("  {}", item.name)
-                    } else {
-                        format!("{} {}", status_char, item.name)
-                    }
-                } else {
-                    if status_char == ' ' {
-                        format!("{}  {}", " ".repeat(item.depth * 2), item.name)
-                    } else {
-                        format!(
-                            "{}{} {}",
-                            " ".repeat(item.depth * 2),
-                            status_char,
-                            item.name
-                        )
-                    }
-                }
+                vec![
+                    Span::raw(indent),
+                    Span::styled(
+                        item.staged_status.unwrap_or(' ').to_string(),
+                        Style::default().fg(theme.file_git_staged),
+                    ),
+                    Span::styled(
+                        item.git_status.unwrap_or(' ').to_string(),
+                        status_style(item.git_status),
+                    ),
+                    Span::styled(
+                        format!(" {}", item.name),
+                        status_style(item.git_status.or(item.staged_status)),
+                    ),
+                ]
             };
 
             let line = if item.is_selected {
                 // Highlight selected item
+                let display_name: String = spans.iter().map(|span| span.content.as_ref()).collect();
                 let content_width = (area.width as usize).saturating_sub(2);
                 let display_len = display_name.chars().count();
                 let padding_needed = content_width.saturating_sub(display_len);
@@ -133,20 +241,7 @@ fn draw_file_navigator(frame: &mut Frame, app: &mut App, area: Rect) {
                         .add_modifier(ratatui::style::Modifier::BOLD),
                 )])
             } else {
-                let style = if item.is_dir {
-                    Style::default()
-                        .fg(theme.file_directory)
-                        .add_modifier(ratatui::style::Modifier::BOLD)
-                } else {
-                    match item.git_status {
-                        Some('M') => Style::default().fg(theme.file_git_modified),
-                        Some('A') => Style::default().fg(theme.file_git_added),
-                        Some('D') => Style::default().fg(theme.file_git_deleted),
-                        Some('?') => Style::default().fg(theme.file_git_untracked),
-                        _ => Style::default().fg(theme.file_default),
-                    }
-                };
-                Line::from(vec![Span::styled(display_name, style)])
+                Line::from(spans)
             };
 
             ListItem::new(line)
@@ -159,8 +254,20 @@ fn draw_file_navigator(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut list_state);
 }
 
-fn draw_commit_history(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_commit_history(frame: &mut Frame, app: &mut App, area: Rect) {
     let theme = get_theme();
+
+    // The commit details take the lower half of the panel
+    let area = if app.history.show_commit_details {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(area);
+        draw_commit_details(frame, app, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
     let is_active = app.ui.active_panel == PanelFocus::History;
     let border_style = if is_active {
         Style::default().fg(theme.active_border)
@@ -168,8 +275,21 @@ fn draw_commit_history(frame: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(theme.inactive_border)
     };
 
-    let title = if let Some(path) = app.get_active_file() {
-        let filename = path.file_name().unwrap_or_default().to_string_lossy();
+    let title = if let Some(path) = app.get_history_path() {
+        let mut filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if app.get_active_file().is_none() {
+            filename.push('/');
+        }
+        if let Some(line_range) = &app.history.line_range {
+            filename = format!(
+                "{}, lines {}-{}",
+                filename,
+                line_range.start_line + 1,
+                line_range.end_line + 1
+            );
+        } else if app.history.mode != HistoryMode::default() {
+            filename = format!("{}, {}", filename, app.history.mode.describe());
+        }
         if app.history.is_loading_more && !app.history.history_complete {
             format!(" Commit History ({}) - Loading... ", filename)
         } else if !app.history.history_complete {
@@ -195,19 +315,46 @@ fn draw_commit_history(frame: &mut Frame, app: &App, area: Rect) {
         .border_style(border_style);
 
     if app.history.commit_list.is_empty() {
-        let paragraph = Paragraph::new("Select a file to view its history")
+        let message = if app.get_history_path().is_none() {
+            "Select a file or directory to view its history"
+        } else if app.ui.is_loading {
+            "Loading history..."
+        } else {
+            "No commits found"
+        };
+        let paragraph = Paragraph::new(message)
             .block(block)
             .style(Style::default().fg(theme.panel_title));
         frame.render_widget(paragraph, area);
         return;
     }
 
+    let active_file = app
+        .get_history_path()
+        .map(|path| path.to_string_lossy().to_string());
+    let base_commit = match &app.inspector.diff_base {
+        DiffBase::Commit(hash) => Some(hash),
+        _ => None,
+    };
+    let graph = app
+        .history
+        .graph
+        .get_or_insert_with(|| crate::graph::graph_rows(&app.history.commit_list))
+        .as_slice();
     let mut items: Vec<ListItem> = app
         .history
         .commit_list
         .iter()
-        .map(|commit| {
-            let line = Line::from(vec![
+        .zip(graph)
+        .map(|(commit, graph_row)| {
+            let mut spans = vec![
+                Span::styled(graph_row.as_str(), Style::default().fg(theme.commit_graph)),
+                Span::raw(" "),
+            ];
+            if base_commit == Some(&commit.hash) {
+                spans.push(Span::styled("◆ ", Style::default().fg(theme.commit_diff_base)));
+            }
+            spans.extend([
                 Span::styled(
                     &commit.short_hash,
                     Style::default().fg(theme.commit_hash),
@@ -220,9 +367,28 @@ fn draw_commit_history(frame: &mut Frame, app: &App, area: Rect) {
                     Style::default().fg(theme.commit_author),
                 ),
                 Span::raw(" "),
-                Span::raw(&commit.subject),
             ]);
-            ListItem::new(line)
+            if let Some(refs) = app.refs_by_commit.get(&commit.hash) {
+                spans.extend(ref_decoration_spans(refs, &theme));
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::raw(&commit.subject));
+            if let Some(files_changed) = commit.files_changed {
+                let files = if files_changed == 1 { "file" } else { "files" };
+                spans.push(Span::styled(
+                    format!(" ({} {})", files_changed, files),
+                    Style::default().fg(theme.commit_date),
+                ));
+            }
+            // Show the old name for commits made before the file was renamed
+            if let Some(path) = commit.file_path.as_ref().filter(|path| Some(*path) != active_file.as_ref()) {
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    format!("[{}]", path),
+                    Style::default().fg(theme.commit_date),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -256,6 +422,250 @@ fn draw_commit_history(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut list_state);
 }
 
+/// Most refs listed on one line of the commit details before the rest are summarized
+const MAX_LISTED_REFS: usize = 5;
+
+/// The earliest release and the branches containing the selected commit
+fn containing_refs_lines(containing: Option<&ContainingRefs>, theme: &Theme) -> Vec<Line<'static>> {
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(theme.panel_title));
+    let Some(containing) = containing else {
+        return vec![Line::from(vec![label("Released:  "), label("looking up...")])];
+    };
+
+    let released = match containing.earliest_tag() {
+        Some(tag) => {
+            let mut spans = vec![
+                label("Released:  "),
+                Span::styled(tag.name.clone(), Style::default().fg(theme.ref_tag)),
+            ];
+            if containing.tags.len() > 1 {
+                spans.push(Span::styled(
+                    format!(" (first of {} tags)", containing.tags.len()),
+                    Style::default().fg(theme.panel_title),
+                ));
+            }
+            Line::from(spans)
+        }
+        None => Line::from(vec![label("Released:  "), label("not in any tag")]),
+    };
+
+    let mut branches = vec![label("Branches:  ")];
+    if containing.branches.is_empty() {
+        branches.push(label("none"));
+    }
+    for (i, branch) in containing.branches.iter().take(MAX_LISTED_REFS).enumerate() {
+        if i > 0 {
+            branches.push(Span::raw(", "));
+        }
+        let color = if branch.kind == RefKind::RemoteBranch {
+            theme.ref_remote
+        } else {
+            theme.ref_branch
+        };
+        branches.push(Span::styled(branch.name.clone(), Style::default().fg(color)));
+    }
+    if containing.branches.len() > MAX_LISTED_REFS {
+        branches.push(Span::styled(
+            format!(" +{} more", containing.branches.len() - MAX_LISTED_REFS),
+            Style::default().fg(theme.panel_title),
+        ));
+    }
+
+    vec![released, Line::from(branches)]
+}
+
+/// Decorations like `git log --decorate`: `(HEAD, main, origin/main, tag: v1.0)`
+fn ref_decoration_spans<'a>(refs: &'a [RefInfo], theme: &Theme) -> Vec<Span<'a>> {
+    let mut spans = vec![Span::styled("(", Style::default().fg(theme.commit_date))];
+    for (i, r) in refs.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(", ", Style::default().fg(theme.commit_date)));
+        }
+        let (label, color) = match r.kind {
+            RefKind::Head => (r.name.clone(), theme.ref_head),
+            RefKind::Branch => (r.name.clone(), theme.ref_branch),
+            RefKind::RemoteBranch => (r.name.clone(), theme.ref_remote),
+            RefKind::Tag => (format!("tag: {}", r.name), theme.ref_tag),
+        };
+        spans.push(Span::styled(label, Style::default().fg(color)));
+    }
+    spans.push(Span::styled(")", Style::default().fg(theme.commit_date)));
+    spans
+}
+
+fn draw_commit_details(frame: &mut Frame, app: &App, area: Rect) {
+    let theme = get_theme();
+    let border_style = if app.ui.active_panel == PanelFocus::History {
+        Style::default().fg(theme.active_border)
+    } else {
+        Style::default().fg(theme.inactive_border)
+    };
+
+    let commit = app
+        .history
+        .selected_commit_index
+        .and_then(|index| app.history.commit_list.get(index));
+    let Some(commit) = commit else {
+        let paragraph = Paragraph::new("No commit selected")
+            .block(Block::default().title(" Commit ").borders(Borders::ALL).border_style(border_style))
+            .style(Style::default().fg(theme.panel_title));
+        frame.render_widget(paragraph, area);
+        return;
+    };
+
+    let block = Block::default()
+        .title(format!(" Commit {} ", commit.short_hash))
+        .borders(Borders::ALL)
+        .border_style(border_style);
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(theme.panel_title));
+    let mut lines = vec![Line::from(vec![
+        label("Author:    "),
+        Span::styled(
+            format!("{} <{}>", commit.author, commit.author_email),
+            Style::default().fg(theme.commit_author),
+        ),
+        Span::raw(" "),
+        Span::styled(
+            format_signature_date(commit.author_time, commit.author_utc_offset),
+            Style::default().fg(theme.commit_date),
+        ),
+    ])];
+
+    // Only show the committer when someone else applied the commit, or applied it later
+    let committed_separately = !commit.committer.is_empty()
+        && (commit.committer != commit.author
+            || commit.committer_email != commit.author_email
+            || commit.committer_time != commit.author_time);
+    if committed_separately {
+        lines.push(Line::from(vec![
+            label("Committer: "),
+            Span::styled(
+                format!("{} <{}>", commit.committer, commit.committer_email),
+                Style::default().fg(theme.commit_author),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                format_signature_date(commit.committer_time, commit.committer_utc_offset),
+                Style::default().fg(theme.commit_date),
+            ),
+        ]));
+    }
+
+    let parents = if commit.parent_ids.is_empty() {
+        Span::raw("none (root commit)")
+    } else {
+        Span::styled(
+            commit
+                .parent_ids
+                .iter()
+                .map(|id| id.chars().take(8).collect::<String>())
+                .collect::<Vec<_>>()
+                .join(" "),
+            Style::default().fg(theme.commit_hash),
+        )
+    };
+    lines.push(Line::from(vec![label("Parents:   "), parents]));
+    lines.extend(containing_refs_lines(app.history.containing_refs.as_ref(), &theme));
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        commit.subject.clone(),
+        Style::default().add_modifier(ratatui::style::Modifier::BOLD),
+    )));
+    if !commit.body.is_empty() {
+        lines.push(Line::from(""));
+        lines.extend(commit.body.lines().map(|line| Line::from(line.to_string())));
+    }
+    if !commit.trailers.is_empty() {
+        lines.push(Line::from(""));
+        lines.extend(commit.trailers.iter().map(|(key, value)| {
+            Line::from(vec![
+                Span::styled(format!("{}: ", key), Style::default().fg(theme.commit_trailer)),
+                Span::raw(value.clone()),
+            ])
+        }));
+    }
+
+    lines.push(Line::from(""));
+    match &app.history.changed_files {
+        Some(changed_files) => {
+            let current_path = commit.file_path.as_deref();
+            let other_files: Vec<_> = changed_files
+                .iter()
+                .filter(|file| Some(file.path.as_str()) != current_path)
+                .collect();
+            lines.push(Line::from(Span::styled(
+                format!("Other files changed ({}):", other_files.len()),
+                Style::default().fg(theme.panel_title),
+            )));
+            lines.extend(other_files.into_iter().map(|file| changed_file_line(file, &theme)));
+        }
+        None => lines.push(Line::from(label("Loading changed files..."))),
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(ratatui::widgets::Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// The files a commit changed below the directory whose history is shown, one per row
+fn draw_directory_changes(frame: &mut Frame, app: &App, area: Rect, block: Block) {
+    let theme = get_theme();
+    let lines: Vec<Line> = app
+        .inspector
+        .directory_changes
+        .iter()
+        .enumerate()
+        .skip(app.inspector.scroll_vertical as usize)
+        .take((area.height - 2) as usize) // Account for borders
+        .map(|(row, file)| {
+            let line = changed_file_line(file, &theme);
+            if row == app.inspector.cursor_line {
+                line.style(
+                    Style::default()
+                        .bg(theme.code_background_current)
+                        .fg(theme.code_foreground_current),
+                )
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .scroll((0, app.inspector.scroll_horizontal));
+    frame.render_widget(paragraph, area);
+}
+
+/// A file in the commit details, with a git-style status letter
+fn changed_file_line<'a>(file: &'a ChangedFile, theme: &Theme) -> Line<'a> {
+    let (status, color) = match file.kind {
+        FileChangeKind::Added => ("A", theme.file_git_added),
+        FileChangeKind::Modified => ("M", theme.file_git_modified),
+        FileChangeKind::Deleted => ("D", theme.file_git_deleted),
+        FileChangeKind::Renamed(_) => ("R", theme.file_git_modified),
+    };
+    let path = match &file.kind {
+        FileChangeKind::Renamed(from) => format!("{} → {}", from, file.path),
+        _ => file.path.clone(),
+    };
+    Line::from(vec![
+        Span::styled(format!("  {} ", status), Style::default().fg(color)),
+        Span::raw(path),
+    ])
+}
+
+/// Format a signature time in the timezone it was recorded in, e.g. "2024-05-01 14:03 +0200"
+fn format_signature_date(time: i64, utc_offset: i32) -> String {
+    chrono::FixedOffset::east_opt(utc_offset)
+        .and_then(|offset| chrono::TimeZone::timestamp_opt(&offset, time, 0).single())
+        .map(|datetime| datetime.format("%Y-%m-%d %H:%M %z").to_string())
+        .unwrap_or_default()
+}
+
 fn draw_code_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
     let theme = get_theme();
     let is_active = app.ui.active_panel == PanelFocus::Inspector;
@@ -268,17 +678,47 @@ fn draw_code_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
     // Update the visible height in the app state
     app.inspector.visible_height = area.height as usize;
 
-    // Create a more informative title
-    let title = if app.inspector.show_diff_view {
-        " Code Inspector (Diff View) ".to_string()
-    } else if let (Some(file_path), Some(commit_hash)) =
-        (app.get_active_file().as_ref(), &app.history.selected_commit_hash)
+    // Blame is only shown once it has been loaded for the selected commit
+    let blame = if app.inspector.show_blame
+        && app.inspector.blame_commit_hash == app.history.selected_commit_hash
     {
+        app.inspector.current_blame.as_ref()
+    } else {
+        None
+    };
+
+    // Create a more informative title
+    let diff_base = if app.inspector.diff_base == DiffBase::default() {
+        String::new()
+    } else {
+        format!(" vs {}", app.inspector.diff_base.describe())
+    };
+    let title = if app.inspector.show_diff_view && app.inspector.diff_side_by_side {
+        format!(" Code Inspector (Side-by-Side Diff{}) ", diff_base)
+    } else if app.inspector.show_diff_view {
+        format!(" Code Inspector (Diff View{}) ", diff_base)
+    } else if app.inspector.show_blame && blame.is_none() {
+        " Code Inspector (Loading blame...) ".to_string()
+    } else if let (Some(file_path), Some(commit_hash)) = (
+        app.history
+            .selected_commit_hash
+            .as_ref()
+            .and_then(|hash| app.get_file_path_at_commit(hash)),
+        &app.history.selected_commit_hash,
+    ) {
         format!(
             " Code Inspector - {} @ {} ",
             file_path.file_name().unwrap_or_default().to_string_lossy(),
             &commit_hash[..8]
         )
+    } else if let (Some(directory), Some(commit_hash)) =
+        (app.get_history_directory(), &app.history.selected_commit_hash)
+    {
+        format!(
+            " Code Inspector - Changed below {}/ @ {} ",
+            directory.display(),
+            &commit_hash[..8]
+        )
     } else {
         " Code Inspector ".to_string()
     };
@@ -288,11 +728,22 @@ fn draw_code_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
         .borders(Borders::ALL)
         .border_style(border_style);
 
+    if !app.inspector.directory_changes.is_empty() {
+        draw_directory_changes(frame, app, area, block);
+        return;
+    }
+
     if app.inspector.current_content.is_empty() && !app.inspector.show_diff_view {
-        let message = if app.get_active_file().is_none() {
+        let message = if app.get_history_path().is_none() {
             "Select a file to view its content"
         } else if app.history.selected_commit_hash.is_none() {
             "Select a commit to view file content at that point"
+        } else if app.get_history_directory().is_some() {
+            if app.history.changed_files.is_none() {
+                "Loading changed files..."
+            } else {
+                "No files changed below the directory"
+            }
         } else if app.ui.is_loading {
             "Loading file content..."
         } else {
@@ -308,10 +759,25 @@ fn draw_code_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // Check if we should render diff view
     if app.inspector.show_diff_view && app.inspector.diff_lines.is_some() {
-        draw_diff_view(frame, app, area, block);
+        if app.inspector.diff_side_by_side {
+            draw_side_by_side_diff(frame, app, area, block);
+        } else {
+            draw_diff_view(frame, app, area, block);
+        }
         return;
     }
 
+    let now = chrono::Utc::now().timestamp();
+    let selection = app.inspector.selected_range();
+    let file_path = displayed_file_path(app);
+    let visible_end = app.inspector.scroll_vertical as usize + (area.height - 2) as usize;
+    let content = &app.inspector.current_content;
+    let key = app
+        .inspector
+        .content_highlight_key
+        .get_or_insert_with(|| HighlightKey::new(file_path.as_deref(), content));
+    let highlighted = app.inspector.highlight_cache.highlight(key, content, visible_end);
+
     // Enhanced content display with syntax-aware styling
     let content_lines: Vec<Line> = app
         .inspector
@@ -322,42 +788,61 @@ fn draw_code_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
         .take((area.height - 2) as usize) // Account for borders
         .map(|(line_num, line)| {
             let line_number = format!("{:4} ", line_num + 1);
+            let tokens = highlighted.get(line_num);
 
-            // Basic syntax highlighting for common file types
-            let line_style = get_line_style(line, &app.get_active_file());
+            let mut spans = Vec::new();
+            if app.inspector.show_blame {
+                spans.extend(blame_gutter_spans(
+                    blame,
+                    line_num,
+                    app.inspector.scroll_vertical as usize,
+                    now,
+                ));
+            }
+            let gutter_width: usize = spans.iter().map(|span| span.width()).sum();
 
             if line_num == app.inspector.cursor_line {
                 // Calculate content width and add padding for full-width highlighting
                 let content_width = (area.width as usize).saturating_sub(2); // Account for borders
                 let line_number_width = line_number.len();
                 let content_len = line.chars().count();
-                let total_used = line_number_width + content_len;
+                let total_used = gutter_width + line_number_width + content_len;
                 let padding_needed = content_width.saturating_sub(total_used);
 
-                Line::from(vec![
-                    Span::styled(
-                        line_number,
-                        Style::default()
-                            .fg(theme.line_numbers_current)
-                            .bg(theme.code_background_current)
-                            .add_modifier(ratatui::style::Modifier::BOLD),
-                    ),
-                    Span::styled(
-                        format!("{}{}", line, " ".repeat(padding_needed)),
-                        line_style
-                            .bg(theme.code_background_current)
-                            .fg(theme.code_foreground_current),
-                    ),
-                ])
+                spans.push(Span::styled(
+                    line_number,
+                    Style::default()
+                        .fg(theme.line_numbers_current)
+                        .bg(theme.code_background_current)
+                        .add_modifier(ratatui::style::Modifier::BOLD),
+                ));
+                spans.push(Span::styled(
+                    format!("{}{}", line, " ".repeat(padding_needed)),
+                    Style::default()
+                        .bg(theme.code_background_current)
+                        .fg(theme.code_foreground_current),
+                ));
+            } else if selection.is_some_and(|(start, end)| (start..=end).contains(&line_num)) {
+                spans.push(Span::styled(
+                    line_number,
+                    Style::default()
+                        .fg(theme.line_numbers_current)
+                        .bg(theme.code_background_selected),
+                ));
+                spans.extend(highlighted_spans(
+                    line,
+                    tokens,
+                    Style::default().bg(theme.code_background_selected),
+                ));
             } else {
-                Line::from(vec![
-                    Span::styled(
-                        line_number,
-                        Style::default().fg(theme.line_numbers),
-                    ),
-                    Span::styled(line, line_style),
-                ])
+                spans.push(Span::styled(
+                    line_number,
+                    Style::default().fg(theme.line_numbers),
+                ));
+                spans.extend(highlighted_spans(line, tokens, Style::default()));
             }
+
+            Line::from(spans)
         })
         .collect();
 
@@ -371,8 +856,22 @@ fn draw_code_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
 /// Draw the diff view in the code inspector
 fn draw_diff_view(frame: &mut Frame, app: &mut App, area: Rect, block: Block) {
     let theme = get_theme();
+    let file_path = displayed_file_path(app);
     
     if let Some(diff_lines) = &app.inspector.diff_lines {
+        let visible_rows = app.inspector.scroll_vertical as usize
+            ..app.inspector.scroll_vertical as usize + (area.height - 2) as usize;
+        let sides = app
+            .inspector
+            .diff_sides
+            .get_or_insert_with(|| DiffSides::new(file_path.as_deref(), diff_lines));
+        let (old_tokens, new_tokens) = diff_side_tokens(
+            &mut app.inspector.highlight_cache,
+            sides,
+            diff_lines,
+            visible_rows.clone(),
+        );
+
         let content_lines: Vec<Line> = diff_lines
             .iter()
             .enumerate()
@@ -389,28 +888,11 @@ fn draw_diff_view(frame: &mut Frame, app: &mut App, area: Rect, block: Block) {
                     (None, None) => "          ".to_string(),
                 };
                 
-                // Get base syntax highlighting for the line
-                let line_style = get_line_style(&diff_line.content, &app.get_active_file());
-                
-                // Apply diff-specific styling
-                let (prefix, diff_style) = match diff_line.line_type {
-                    crate::app::DiffLineType::Added => (
-                        "+",
-                        line_style.fg(theme.diff_added_fg).bg(theme.diff_added_bg)
-                    ),
-                    crate::app::DiffLineType::Removed => (
-                        "-",
-                        line_style.fg(theme.diff_removed_fg).bg(theme.diff_removed_bg)
-                    ),
-                    crate::app::DiffLineType::Modified => (
-                        "~",
-                        line_style.fg(theme.diff_modified_fg).bg(theme.diff_modified_bg)
-                    ),
-                    crate::app::DiffLineType::Unchanged => (
-                        " ",
-                        line_style
-                    ),
-                };
+                let (prefix, diff_style) = diff_marker_style(&diff_line.line_type, &theme);
+                let tokens = diff_line
+                    .new_line_num
+                    .and_then(|num| new_tokens.get(num - 1))
+                    .or_else(|| diff_line.old_line_num.and_then(|num| old_tokens.get(num - 1)));
                 
                 // Strip trailing newline if present
                 let content = diff_line.content.trim_end_matches('\n');
@@ -440,16 +922,15 @@ fn draw_diff_view(frame: &mut Frame, app: &mut App, area: Rect, block: Block) {
                         ),
                     ])
                 } else {
-                    Line::from(vec![
+                    let mut spans = vec![
                         Span::styled(
                             line_number,
                             Style::default().fg(theme.line_numbers),
                         ),
-                        Span::styled(
-                            format!("{}{}", prefix, content),
-                            diff_style,
-                        ),
-                    ])
+                        Span::styled(prefix, diff_style),
+                    ];
+                    spans.extend(diff_content_spans(content, tokens, diff_line, diff_style, &theme));
+                    Line::from(spans)
                 }
             })
             .collect();
@@ -462,69 +943,356 @@ fn draw_diff_view(frame: &mut Frame, app: &mut App, area: Rect, block: Block) {
     }
 }
 
-/// Basic syntax highlighting based on file content and extension
-fn get_line_style(line: &str, file_path: &Option<PathBuf>) -> Style {
+/// Draw the diff with the parent on the left and the selected commit on the right.
+///
+/// Both sides render the same aligned rows, so they always scroll together.
+fn draw_side_by_side_diff(frame: &mut Frame, app: &mut App, area: Rect, block: Block) {
+    let theme = get_theme();
+    let file_path = displayed_file_path(app);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(diff_lines) = &app.inspector.diff_lines else {
+        return;
+    };
+
+    let rows = crate::app::side_by_side_rows(diff_lines);
+    let visible_rows: Vec<(usize, crate::app::SideBySideRow)> = rows
+        .into_iter()
+        .enumerate()
+        .skip(app.inspector.scroll_vertical as usize)
+        .take(inner.height as usize)
+        .collect();
+    let sides = app
+        .inspector
+        .diff_sides
+        .get_or_insert_with(|| DiffSides::new(file_path.as_deref(), diff_lines));
+    let (old_tokens, new_tokens) = diff_side_tokens(
+        &mut app.inspector.highlight_cache,
+        sides,
+        diff_lines,
+        visible_rows
+            .iter()
+            .flat_map(|(_, row)| row.left.into_iter().chain(row.right)),
+    );
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Fill(1), Constraint::Length(1), Constraint::Fill(1)])
+        .split(inner);
+
+    let mut left_lines = Vec::new();
+    let mut right_lines = Vec::new();
+    for (row_idx, row) in &visible_rows {
+        let is_cursor = *row_idx == app.inspector.cursor_line;
+        let left = row.left.map(|idx| &diff_lines[idx]);
+        let right = row.right.map(|idx| &diff_lines[idx]);
+
+        left_lines.push(side_by_side_cell(
+            left,
+            left.and_then(|line| line.old_line_num),
+            left.and_then(|line| line.old_line_num).and_then(|num| old_tokens.get(num - 1)),
+            is_cursor,
+            columns[0].width as usize,
+            &theme,
+        ));
+        right_lines.push(side_by_side_cell(
+            right,
+            right.and_then(|line| line.new_line_num),
+            right.and_then(|line| line.new_line_num).and_then(|num| new_tokens.get(num - 1)),
+            is_cursor,
+            columns[2].width as usize,
+            &theme,
+        ));
+    }
+
+    let separator: Vec<Line> = (0..inner.height).map(|_| Line::from("│")).collect();
+
+    frame.render_widget(
+        Paragraph::new(left_lines).scroll((0, app.inspector.scroll_horizontal)),
+        columns[0],
+    );
+    frame.render_widget(
+        Paragraph::new(separator).style(Style::default().fg(theme.inactive_border)),
+        columns[1],
+    );
+    frame.render_widget(
+        Paragraph::new(right_lines).scroll((0, app.inspector.scroll_horizontal)),
+        columns[2],
+    );
+}
+
+/// One side of a side-by-side diff row; `None` leaves the row empty to keep hunks aligned
+fn side_by_side_cell<'a>(
+    diff_line: Option<&'a crate::app::DiffLine>,
+    line_num: Option<usize>,
+    tokens: Option<&Vec<Token>>,
+    is_cursor: bool,
+    width: usize,
+    theme: &Theme,
+) -> Line<'a> {
+    let Some(diff_line) = diff_line else {
+        return if is_cursor {
+            Line::from(Span::styled(
+                " ".repeat(width),
+                Style::default().bg(theme.code_background_current),
+            ))
+        } else {
+            Line::default()
+        };
+    };
+
+    let line_number = match line_num {
+        Some(num) => format!("{:4} ", num),
+        None => "     ".to_string(),
+    };
+    let (prefix, diff_style) = diff_marker_style(&diff_line.line_type, theme);
+    let content = diff_line.content.trim_end_matches('\n');
+
+    if is_cursor {
+        let used = line_number.len() + 1 + content.chars().count();
+        return Line::from(vec![
+            Span::styled(
+                line_number,
+                Style::default()
+                    .fg(theme.line_numbers_current)
+                    .bg(theme.code_background_current)
+                    .add_modifier(ratatui::style::Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("{}{}{}", prefix, content, " ".repeat(width.saturating_sub(used))),
+                diff_style
+                    .fg(theme.code_foreground_current)
+                    .bg(theme.code_background_current)
+                    .add_modifier(ratatui::style::Modifier::BOLD),
+            ),
+        ]);
+    }
+
+    let mut spans = vec![
+        Span::styled(line_number, Style::default().fg(theme.line_numbers)),
+        Span::styled(prefix, diff_style),
+    ];
+    spans.extend(diff_content_spans(content, tokens, diff_line, diff_style, theme));
+    Line::from(spans)
+}
+
+/// Marker and style for a diff line; the syntax highlighting is layered on its background
+fn diff_marker_style(line_type: &crate::app::DiffLineType, theme: &Theme) -> (&'static str, Style) {
+    match line_type {
+        crate::app::DiffLineType::Added => (
+            "+",
+            Style::default().fg(theme.diff_added_fg).bg(theme.diff_added_bg),
+        ),
+        crate::app::DiffLineType::Removed => (
+            "-",
+            Style::default().fg(theme.diff_removed_fg).bg(theme.diff_removed_bg),
+        ),
+        crate::app::DiffLineType::Unchanged => (" ", Style::default().fg(theme.code_default)),
+    }
+}
+
+/// Width of the blame gutter: short hash, initials, age and a separator
+const BLAME_GUTTER_WIDTH: usize = 8 + 1 + 2 + 1 + 4 + 3;
+
+/// Build the blame gutter for a line of the code inspector.
+///
+/// Only the first line of a run of lines from the same commit shows the commit details;
+/// the following lines show a continuation marker so the group reads as one block.
+fn blame_gutter_spans<'a>(
+    blame: Option<&'a Vec<crate::app::BlameLine>>,
+    line_num: usize,
+    first_visible_line: usize,
+    now: i64,
+) -> Vec<Span<'a>> {
     let theme = get_theme();
-    let trimmed = line.trim();
 
-    // Comments (works for most languages)
-    if trimmed.starts_with("//") || trimmed.starts_with('#') || trimmed.starts_with("/*") {
-        return Style::default().fg(theme.syntax_comment);
+    let Some(blame_line) = blame.and_then(|blame| blame.get(line_num)) else {
+        return vec![Span::styled(
+            format!("{:width$}", "", width = BLAME_GUTTER_WIDTH),
+            Style::default().fg(theme.blame_gutter),
+        )];
+    };
+
+    let starts_group = line_num == first_visible_line
+        || line_num == 0
+        || blame
+            .and_then(|blame| blame.get(line_num - 1))
+            .map(|previous| previous.commit_hash != blame_line.commit_hash)
+            .unwrap_or(true);
+
+    if starts_group {
+        vec![
+            Span::styled(
+                format!("{:8.8}", blame_line.short_hash),
+                Style::default().fg(theme.commit_hash),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                format!("{:2}", author_initials(&blame_line.author)),
+                Style::default().fg(theme.commit_author),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                format!("{:>4}", format_relative_age(blame_line.timestamp, now)),
+                Style::default().fg(theme.commit_date),
+            ),
+            Span::styled(" │ ", Style::default().fg(theme.blame_gutter)),
+        ]
+    } else {
+        vec![Span::styled(
+            format!("{:<width$} │ ", "  ┆", width = BLAME_GUTTER_WIDTH - 3),
+            Style::default().fg(theme.blame_gutter),
+        )]
     }
+}
+
+/// Initials of an author name, e.g. "Jane Smith" -> "JS"
+fn author_initials(author: &str) -> String {
+    author
+        .split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .take(2)
+        .flat_map(|c| c.to_uppercase())
+        .collect()
+}
 
-    // Strings (basic detection)
-    if trimmed.contains('"') || trimmed.contains('\'') {
-        return Style::default().fg(theme.syntax_string);
+/// Compact relative age of a timestamp, e.g. "5m", "3h", "2d", "4w", "7mo", "2y"
+fn format_relative_age(timestamp: i64, now: i64) -> String {
+    let seconds = (now - timestamp).max(0);
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+
+    if minutes < 1 {
+        "now".to_string()
+    } else if hours < 1 {
+        format!("{}m", minutes)
+    } else if days < 1 {
+        format!("{}h", hours)
+    } else if days < 14 {
+        format!("{}d", days)
+    } else if days < 60 {
+        format!("{}w", days / 7)
+    } else if days < 365 {
+        format!("{}mo", days / 30)
+    } else {
+        format!("{}y", days / 365)
     }
+}
 
-    // Keywords based on file extension
-    if let Some(path) = file_path {
-        if let Some(extension) = path.extension() {
-            match extension.to_string_lossy().as_ref() {
-                "rs" => {
-                    if trimmed.starts_with("use ")
-                        || trimmed.starts_with("pub ")
-                        || trimmed.starts_with("fn ")
-                        || trimmed.starts_with("struct ")
-                        || trimmed.starts_with("enum ")
-                        || trimmed.starts_with("impl ")
-                    {
-                        return Style::default()
-                            .fg(theme.syntax_keyword)
-                            .add_modifier(ratatui::style::Modifier::BOLD);
-                    }
-                }
-                "js" | "ts" => {
-                    if trimmed.starts_with("function ")
-                        || trimmed.starts_with("const ")
-                        || trimmed.starts_with("let ")
-                        || trimmed.starts_with("var ")
-                        || trimmed.starts_with("import ")
-                        || trimmed.starts_with("export ")
-                    {
-                        return Style::default()
-                            .fg(theme.syntax_keyword)
-                            .add_modifier(ratatui::style::Modifier::BOLD);
-                    }
-                }
-                "py" => {
-                    if trimmed.starts_with("def ")
-                        || trimmed.starts_with("class ")
-                        || trimmed.starts_with("import ")
-                        || trimmed.starts_with("from ")
-                    {
-                        return Style::default()
-                            .fg(theme.syntax_keyword)
-                            .add_modifier(ratatui::style::Modifier::BOLD);
-                    }
-                }
-                _ => {}
+/// Path of the displayed file at the selected commit, which selects the syntax highlighting
+fn displayed_file_path(app: &App) -> Option<PathBuf> {
+    app.history
+        .selected_commit_hash
+        .as_ref()
+        .and_then(|hash| app.get_file_path_at_commit(hash))
+        .or_else(|| app.get_active_file())
+}
+
+/// Highlighting of both sides of the diff, indexed by old and new line number minus one,
+/// covering at least the visible diff lines (given by index).
+///
+/// Each side of the diff is highlighted as a whole file so multi-line constructs like block
+/// comments are colored correctly; the cache makes the new side reuse the inspector's highlighting.
+fn diff_side_tokens<'a>(
+    cache: &'a mut HighlightCache,
+    sides: &DiffSides,
+    diff_lines: &[crate::app::DiffLine],
+    visible: impl IntoIterator<Item = usize>,
+) -> (&'a [Vec<Token>], &'a [Vec<Token>]) {
+    let (mut old_end, mut new_end) = (0, 0);
+    for diff_line in visible.into_iter().filter_map(|idx| diff_lines.get(idx)) {
+        old_end = old_end.max(diff_line.old_line_num.unwrap_or(0));
+        new_end = new_end.max(diff_line.new_line_num.unwrap_or(0));
+    }
+
+    cache.highlight(&sides.old_key, &sides.old, old_end);
+    cache.highlight(&sides.new_key, &sides.new, new_end);
+    let cache: &'a HighlightCache = cache;
+    (cache.highlighted(&sides.old_key), cache.highlighted(&sides.new_key))
+}
+
+/// Spans for a line of code colored per token on top of `base`, which provides the background
+fn highlighted_spans<'a>(line: &'a str, tokens: Option<&Vec<Token>>, base: Style) -> Vec<Span<'a>> {
+    emphasized_spans(line, tokens, base, &[], base)
+}
+
+/// Spans for the content of a diff line: syntax colors on the diff background, with the words
+/// that changed against the paired line on a stronger background
+fn diff_content_spans<'a>(
+    content: &'a str,
+    tokens: Option<&Vec<Token>>,
+    diff_line: &crate::app::DiffLine,
+    diff_style: Style,
+    theme: &Theme,
+) -> Vec<Span<'a>> {
+    let base = Style::default().bg(diff_style.bg.unwrap_or(ratatui::style::Color::Reset));
+    let emphasis = match diff_line.line_type {
+        crate::app::DiffLineType::Added => Style::default().bg(theme.diff_added_emphasis_bg),
+        crate::app::DiffLineType::Removed => Style::default().bg(theme.diff_removed_emphasis_bg),
+        // Unchanged lines are not paired, so they have no changed words
+        crate::app::DiffLineType::Unchanged => base,
+    };
+    emphasized_spans(content, tokens, base, &diff_line.changed_ranges, emphasis)
+}
+
+/// Spans colored per token, split further where `emphasis` ranges start and end so those
+/// parts of the line use `emphasis_base` instead of `base`
+fn emphasized_spans<'a>(
+    line: &'a str,
+    tokens: Option<&Vec<Token>>,
+    base: Style,
+    emphasis: &[std::ops::Range<usize>],
+    emphasis_base: Style,
+) -> Vec<Span<'a>> {
+    let theme = get_theme();
+    let whole_line = [Token {
+        range: 0..line.len(),
+        kind: TokenKind::Plain,
+    }];
+    let tokens = tokens.map_or(&whole_line[..], |tokens| tokens.as_slice());
+
+    let mut spans = Vec::new();
+    for token in tokens {
+        let mut pos = token.range.start;
+        while pos < token.range.end {
+            let emphasized = emphasis.iter().find(|range| range.contains(&pos));
+            let next = match emphasized {
+                Some(range) => range.end,
+                None => emphasis
+                    .iter()
+                    .map(|range| range.start)
+                    .filter(|&start| start > pos)
+                    .min()
+                    .unwrap_or(token.range.end),
+            }
+            .min(token.range.end);
+
+            let style = if emphasized.is_some() { emphasis_base } else { base };
+            if let Some(text) = line.get(pos..next) {
+                spans.push(Span::styled(text, style.patch(token_style(token.kind, &theme))));
             }
+            pos = next;
         }
     }
 
-    // Default style
-    Style::default().fg(theme.code_default)
+    spans
+}
+
+/// Theme colors for each kind of token
+fn token_style(kind: TokenKind, theme: &Theme) -> Style {
+    match kind {
+        TokenKind::Plain => Style::default().fg(theme.code_default),
+        TokenKind::Comment => Style::default().fg(theme.syntax_comment),
+        TokenKind::String => Style::default().fg(theme.syntax_string),
+        TokenKind::Constant => Style::default().fg(theme.syntax_constant),
+        TokenKind::Keyword => Style::default()
+            .fg(theme.syntax_keyword)
+            .add_modifier(ratatui::style::Modifier::BOLD),
+        TokenKind::Function => Style::default().fg(theme.syntax_function),
+        TokenKind::Type => Style::default().fg(theme.syntax_type),
+    }
 }
 
 fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
@@ -536,9 +1304,10 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     };
 
     let help_text = match app.ui.active_panel {
-        PanelFocus::Navigator => "Tab: Switch panel | 1/2/3: Direct panel focus | []: Older/Younger commit | /: Search | ↑↓: Navigate | →←: Expand/Collapse",
-        PanelFocus::History => "Tab: Switch panel | 1/2/3: Direct panel focus | []: Older/Younger commit | ↑↓: Navigate | Enter: Select commit",
-        PanelFocus::Inspector => "Tab: Switch panel | 1/2/3: Direct panel focus | []: Older/Younger commit | ↑↓: Navigate | p: Previous change | n: Next change | d: Toggle diff",
+        PanelFocus::Navigator => "Tab: Switch panel | 1/2/3: Direct panel focus | []: Older/Younger commit | /: Search | r: Revision | R: Refresh status | ↑↓: Navigate | →←: Expand/Collapse",
+        PanelFocus::History => "Tab: Switch panel | 1/2/3: Direct panel focus | []: Older/Younger commit | ↑↓: Navigate | Enter: Select commit | m: Mark diff base | i: Details | f: History mode | t: Browse commit tree",
+        PanelFocus::Inspector if app.get_history_directory().is_some() => "Tab: Switch panel | 1/2/3: Direct panel focus | []: Older/Younger commit | ↑↓: Navigate | Enter: Open file at this commit",
+        PanelFocus::Inspector => "Tab: Switch panel | 1/2/3: Direct panel focus | []: Older/Younger commit | ↑↓: Navigate | p: Previous change | n: Next change | d: Toggle diff | s: Side-by-side diff | w: Diff worktree/index | P: Merge parent | b: Toggle blame | B: Blame commit | v: Select lines | L: Line history",
     };
 
     let status_line = Line::from(vec![
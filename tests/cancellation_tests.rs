@@ -1,5 +1,5 @@
 use git_lineage::async_task::{Task, TaskResult, run_worker};
-use git_lineage::app::App;
+use git_lineage::app::{App, HistoryMode};
 use git_lineage::git_utils::get_commit_history_streaming;
 use serial_test::serial;
 use std::fs;
@@ -111,6 +111,8 @@ async fn test_streaming_task_cancellation_immediate() {
     let result = get_commit_history_streaming(
         &repo,
         &file_path,
+        None,
+        HistoryMode::default(),
         |_commit, _total| {
             callback_count += 1;
             true
@@ -153,6 +155,8 @@ async fn test_streaming_task_cancellation_during_processing() {
     let result = get_commit_history_streaming(
         &repo,
         &file_path,
+        None,
+        HistoryMode::default(),
         |_commit, _total| {
             callback_count += 1;
             true
@@ -193,6 +197,8 @@ async fn test_async_worker_streaming_task_cancellation() {
     let cancellation_token = CancellationToken::new();
     let task = Task::LoadCommitHistoryStreaming {
         file_path: file_path.clone(),
+        revision: None,
+        mode: HistoryMode::default(),
         cancellation_token: cancellation_token.clone(),
     };
 
@@ -383,6 +389,8 @@ async fn test_cancellation_stops_processing_non_matching_commits() {
     let result = get_commit_history_streaming(
         &repo,
         "target.rs",
+        None,
+        HistoryMode::default(),
         |_commit, _total| {
             callback_count += 1;
             true
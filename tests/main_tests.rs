@@ -1,4 +1,7 @@
-use git_lineage::app::{App, CommitInfo, PanelFocus};
+use git_lineage::app::{
+    App, BlameLine, ChangedFile, CommitInfo, DiffBase, FileChangeKind, LineRange, PanelFocus,
+    ContainingRefs, RefInfo, RefKind, RefPickerState,
+};
 use git_lineage::async_task::TaskResult;
 use git_lineage::cli::{Cli, Commands};
 use git_lineage::test_config::TestConfig;
@@ -108,6 +111,8 @@ fn create_test_app() -> App {
         author: "Test Author".to_string(),
         date: "2023-01-01".to_string(),
         subject: "Test commit".to_string(),
+        file_path: None,
+        ..Default::default()
     }];
 
     app
@@ -138,6 +143,20 @@ mod cli_integration {
         assert_eq!(cli.command, None); // Defaults to Run
     }
 
+    #[test]
+    fn test_cli_parsing_contains_command() {
+        let cli = Cli::try_parse_from(["git-lineage", "contains", "abc123"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Some(Commands::Contains {
+                revision: "abc123".to_string()
+            })
+        );
+
+        // The revision is required
+        assert!(Cli::try_parse_from(["git-lineage", "contains"]).is_err());
+    }
+
     #[test]
     fn test_cli_parsing_screenshot_command() {
         let temp_dir = TempDir::new().unwrap();
@@ -243,7 +262,7 @@ mod task_result_handling {
         let test_node = TreeNode::new_file("test.rs".to_string(), PathBuf::from("test.rs"));
         tree.root.push(test_node);
 
-        let result = TaskResult::FileTreeLoaded { files: tree };
+        let result = TaskResult::FileTreeLoaded { files: tree, revision: None };
 
         git_lineage::main_lib::handle_task_result(&mut app, result);
 
@@ -256,6 +275,46 @@ mod task_result_handling {
         assert!(app.ui.status_message.contains("File tree loaded"));
     }
 
+    #[test]
+    fn test_handle_file_tree_loaded_for_other_revision_is_ignored() {
+        let mut app = create_test_app();
+        app.revision = Some("v2".to_string());
+        app.ui.is_loading = true;
+        let items_before = app.navigator.build_view_model().items.len();
+
+        let mut tree = FileTree::new();
+        tree.root.push(TreeNode::new_file("old.rs".to_string(), PathBuf::from("old.rs")));
+        let result = TaskResult::FileTreeLoaded {
+            files: tree,
+            revision: Some("v1".to_string()),
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.navigator.build_view_model().items.len(), items_before);
+        assert!(app.ui.status_message.contains("ignored"));
+    }
+
+    #[test]
+    fn test_handle_file_tree_loaded_keeps_selected_file() {
+        let mut app = create_test_app();
+
+        let mut tree = FileTree::new();
+        let mut src = TreeNode::new_dir("src".to_string(), PathBuf::from("src"));
+        src.add_child(TreeNode::new_file("a.rs".to_string(), PathBuf::from("src/a.rs")));
+        src.add_child(TreeNode::new_file("b.rs".to_string(), PathBuf::from("src/b.rs")));
+        tree.root.push(src);
+        app.navigator = git_lineage::navigator::NavigatorState::new(tree.clone());
+        app.navigator
+            .handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(PathBuf::from("src/b.rs")))
+            .unwrap();
+
+        let result = TaskResult::FileTreeLoaded { files: tree, revision: None };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.get_active_file(), Some(PathBuf::from("src/b.rs")));
+    }
+
     #[test]
     fn test_handle_commit_history_loaded_with_commits() {
         let mut app = create_test_app();
@@ -272,6 +331,8 @@ mod task_result_handling {
                 author: "Test Author".to_string(),
                 date: "2023-01-01".to_string(),
                 subject: "Test commit".to_string(),
+                file_path: None,
+                ..Default::default()
             },
             CommitInfo {
                 hash: "def456".to_string(),
@@ -279,6 +340,8 @@ mod task_result_handling {
                 author: "Another Author".to_string(),
                 date: "2023-01-02".to_string(),
                 subject: "Another commit".to_string(),
+                file_path: None,
+                ..Default::default()
             },
         ];
 
@@ -341,6 +404,8 @@ mod task_result_handling {
             author: "Stale Author".to_string(),
             date: "2023-01-01".to_string(),
             subject: "Stale commit from previous file".to_string(),
+            file_path: None,
+            ..Default::default()
         }];
 
         // This result is for "old_file.rs" but user has moved away from it
@@ -390,13 +455,17 @@ mod task_result_handling {
 
         let result = TaskResult::NextChangeFound {
             commit_hash: "abc123".to_string(),
+            line_number: 0,
         };
 
         git_lineage::main_lib::handle_task_result(&mut app, result);
 
         assert!(!app.ui.is_loading);
         assert_eq!(app.history.selected_commit_index, Some(0));
-        assert_eq!(app.ui.active_panel, PanelFocus::History);
+        assert_eq!(app.history.selected_commit_hash, Some("abc123".to_string()));
+        assert_eq!(app.inspector.cursor_line, 0);
+        // Focus stays where the search was started
+        assert_eq!(app.ui.active_panel, PanelFocus::Navigator);
         assert!(app.ui.status_message.contains("Found next change"));
     }
 
@@ -404,15 +473,185 @@ mod task_result_handling {
     fn test_handle_next_change_found_commit_not_in_history() {
         let mut app = create_test_app();
         app.ui.is_loading = true;
+        app.history.history_complete = true;
 
         let result = TaskResult::NextChangeFound {
             commit_hash: "nonexistent".to_string(),
+            line_number: 0,
         };
 
         git_lineage::main_lib::handle_task_result(&mut app, result);
 
         assert!(!app.ui.is_loading);
         assert!(app.ui.status_message.contains("commit not in history"));
+        assert_eq!(app.history.pending_jump, None);
+    }
+
+    #[test]
+    fn test_handle_next_change_found_while_history_streaming() {
+        let mut app = create_test_app();
+        app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+
+        let result = TaskResult::NextChangeFound {
+            commit_hash: "def456".to_string(),
+            line_number: 0,
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        // The jump waits for the commit to be streamed in
+        assert_eq!(app.history.selected_commit_index, None);
+        assert!(app.history.pending_jump.is_some());
+        assert!(app.ui.status_message.contains("waiting for history"));
+
+        let result = TaskResult::CommitFound {
+            file_path: "src/main.rs".to_string(),
+            commit: CommitInfo {
+                hash: "def456".to_string(),
+                short_hash: "def456".to_string(),
+                author: "Test Author".to_string(),
+                date: "2023-01-02".to_string(),
+                subject: "Later commit".to_string(),
+                file_path: None,
+                ..Default::default()
+            },
+            total_commits_so_far: 2,
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.history.selected_commit_index, Some(1));
+        assert_eq!(app.history.selected_commit_hash, Some("def456".to_string()));
+        assert_eq!(app.history.pending_jump, None);
+    }
+
+    #[test]
+    fn test_streamed_commit_lays_out_graph_again() {
+        let mut app = create_test_app();
+        let _ = app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.set_commits(vec![CommitInfo {
+            hash: "abc123".to_string(),
+            ..Default::default()
+        }]);
+        app.history.graph = Some(vec!["*".to_string()]);
+
+        let result = TaskResult::CommitFound {
+            file_path: "src/main.rs".to_string(),
+            commit: CommitInfo {
+                hash: "def456".to_string(),
+                graph_children: vec![("abc123".to_string(), 0)],
+                ..Default::default()
+            },
+            total_commits_so_far: 2,
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        // The graph is laid out on the next draw rather than for every frame
+        assert_eq!(app.history.graph, None);
+    }
+
+    #[test]
+    fn test_handle_line_range_commit_found() {
+        let mut app = create_test_app();
+        let _ = app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.commit_list.clear();
+        let line_range = LineRange {
+            commit_hash: "abc123".to_string(),
+            file_path: "src/main.rs".to_string(),
+            start_line: 2,
+            end_line: 4,
+        };
+        app.history.line_range = Some(line_range.clone());
+
+        let commit = CommitInfo {
+            hash: "def456".to_string(),
+            short_hash: "def456".to_string(),
+            author: "Test Author".to_string(),
+            date: "2023-01-02".to_string(),
+            subject: "Touch lines".to_string(),
+            file_path: Some("src/main.rs".to_string()),
+            ..Default::default()
+        };
+
+        // Whole-file results still in flight are ignored while a line range is shown
+        git_lineage::main_lib::handle_task_result(&mut app, TaskResult::CommitFound {
+            file_path: "src/main.rs".to_string(),
+            commit: commit.clone(),
+            total_commits_so_far: 1,
+        });
+        assert!(app.history.commit_list.is_empty());
+
+        git_lineage::main_lib::handle_task_result(&mut app, TaskResult::LineRangeCommitFound {
+            file_path: "src/main.rs".to_string(),
+            line_range: line_range.clone(),
+            commit,
+            total_commits_so_far: 1,
+        });
+        assert_eq!(app.history.commit_list.len(), 1);
+        assert_eq!(app.history.selected_commit_index, Some(0));
+        assert!(app.ui.status_message.contains("lines 3-5"));
+
+        git_lineage::main_lib::handle_task_result(&mut app, TaskResult::LineRangeHistoryComplete {
+            file_path: "src/main.rs".to_string(),
+            line_range,
+            total_commits: 1,
+        });
+        assert!(app.history.history_complete);
+        assert!(app.ui.status_message.contains("Lines 3-5 loaded (1 commits)"));
+    }
+
+    #[test]
+    fn test_handle_line_range_commit_found_for_other_range_is_ignored() {
+        let mut app = create_test_app();
+        let _ = app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.commit_list.clear();
+        app.history.line_range = Some(LineRange {
+            commit_hash: "abc123".to_string(),
+            file_path: "src/main.rs".to_string(),
+            start_line: 2,
+            end_line: 4,
+        });
+
+        git_lineage::main_lib::handle_task_result(&mut app, TaskResult::LineRangeCommitFound {
+            file_path: "src/main.rs".to_string(),
+            line_range: LineRange {
+                commit_hash: "abc123".to_string(),
+                file_path: "src/main.rs".to_string(),
+                start_line: 0,
+                end_line: 0,
+            },
+            commit: CommitInfo {
+                hash: "def456".to_string(),
+                short_hash: "def456".to_string(),
+                author: "Test Author".to_string(),
+                date: "2023-01-02".to_string(),
+                subject: "Touch lines".to_string(),
+                file_path: None,
+                ..Default::default()
+            },
+            total_commits_so_far: 1,
+        });
+
+        assert!(app.history.commit_list.is_empty());
+    }
+
+    #[test]
+    fn test_handle_previous_change_not_found() {
+        let mut app = create_test_app();
+        app.ui.is_loading = true;
+
+        git_lineage::main_lib::handle_task_result(&mut app, TaskResult::PreviousChangeNotFound);
+
+        assert!(!app.ui.is_loading);
+        assert!(app.ui.status_message.contains("No earlier changes found"));
     }
 
     #[test]
@@ -431,6 +670,246 @@ mod task_result_handling {
             .contains("No subsequent changes found"));
     }
 
+    #[test]
+    fn test_handle_blame_loaded() {
+        let mut app = create_test_app();
+        app.ui.is_loading = true;
+        app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.selected_commit_hash = Some("abc123".to_string());
+
+        let blame = vec![BlameLine {
+            commit_hash: "abc123".to_string(),
+            short_hash: "abc123".to_string(),
+            author: "Test Author".to_string(),
+            timestamp: 1672531200,
+            original_line_number: 0,
+            original_path: "src/main.rs".to_string(),
+        }];
+
+        let result = TaskResult::BlameLoaded {
+            file_path: "src/main.rs".to_string(),
+            commit_hash: "abc123".to_string(),
+            blame: blame.clone(),
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert!(!app.ui.is_loading);
+        assert_eq!(app.inspector.current_blame, Some(blame));
+        assert_eq!(app.inspector.blame_commit_hash, Some("abc123".to_string()));
+    }
+
+    #[test]
+    fn test_handle_blame_loaded_for_other_commit_is_ignored() {
+        let mut app = create_test_app();
+        app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.selected_commit_hash = Some("abc123".to_string());
+
+        let result = TaskResult::BlameLoaded {
+            file_path: "src/main.rs".to_string(),
+            commit_hash: "def456".to_string(),
+            blame: vec![],
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.inspector.current_blame, None);
+        assert!(app.ui.status_message.contains("ignored"));
+    }
+
+    #[test]
+    fn test_handle_blame_failed_clears_pending_blame() {
+        let mut app = create_test_app();
+        app.history.selected_commit_hash = Some("abc123".to_string());
+        app.inspector.show_blame = true;
+        app.inspector.blame_commit_hash = Some("abc123".to_string());
+
+        let result = TaskResult::BlameFailed {
+            file_path: "src/main.rs".to_string(),
+            commit_hash: "abc123".to_string(),
+            message: "object not found".to_string(),
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.inspector.blame_commit_hash, None);
+        assert_eq!(app.inspector.current_blame, None);
+        assert!(!app.inspector.show_blame);
+        assert!(app.ui.status_message.contains("object not found"));
+    }
+
+    #[test]
+    fn test_handle_changed_files_loaded() {
+        let mut app = create_test_app();
+        app.history.changed_files_commit = Some("abc123".to_string());
+
+        let changed_files = vec![ChangedFile {
+            path: "src/lib.rs".to_string(),
+            kind: FileChangeKind::Modified,
+        }];
+        let result = TaskResult::ChangedFilesLoaded {
+            commit_hash: "abc123".to_string(),
+            changed_files: changed_files.clone(),
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.history.changed_files, Some(changed_files));
+    }
+
+    #[test]
+    fn test_handle_changed_files_loaded_for_other_commit_is_ignored() {
+        let mut app = create_test_app();
+        app.history.changed_files_commit = Some("def456".to_string());
+
+        let result = TaskResult::ChangedFilesLoaded {
+            commit_hash: "abc123".to_string(),
+            changed_files: vec![],
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.history.changed_files, None);
+        assert!(app.ui.status_message.contains("ignored"));
+    }
+
+    fn sample_refs() -> Vec<RefInfo> {
+        vec![RefInfo {
+            name: "main".to_string(),
+            full_name: "refs/heads/main".to_string(),
+            kind: RefKind::Branch,
+            commit_hash: "abc123".to_string(),
+            commit_time: 0,
+            date: "2024-01-01 00:00".to_string(),
+        }]
+    }
+
+    #[test]
+    fn test_handle_containing_refs_loaded_for_requested_commit() {
+        let mut app = create_test_app();
+        app.history.containing_refs_commit = Some("abc123".to_string());
+        let containing_refs = ContainingRefs {
+            branches: sample_refs(),
+            tags: vec![],
+        };
+
+        let result = TaskResult::ContainingRefsLoaded {
+            commit_hash: "abc123".to_string(),
+            containing_refs: containing_refs.clone(),
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.history.containing_refs, Some(containing_refs));
+    }
+
+    #[test]
+    fn test_handle_containing_refs_loaded_for_other_commit_is_ignored() {
+        let mut app = create_test_app();
+        app.history.containing_refs_commit = Some("def456".to_string());
+
+        let result = TaskResult::ContainingRefsLoaded {
+            commit_hash: "abc123".to_string(),
+            containing_refs: ContainingRefs::default(),
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.history.containing_refs, None);
+        assert!(app.ui.status_message.contains("ignored"));
+    }
+
+    #[test]
+    fn test_handle_cancelled_containing_refs_leaves_details_alone() {
+        let mut app = create_test_app();
+        app.history.containing_refs_commit = Some("abc123".to_string());
+        app.ui.status_message = "Commit details shown".to_string();
+
+        let result = TaskResult::ContainingRefsCancelled {
+            commit_hash: "abc123".to_string(),
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.history.containing_refs, None);
+        assert_eq!(app.ui.status_message, "Commit details shown");
+    }
+
+    #[test]
+    fn test_handle_refs_loaded_fills_open_picker() {
+        let mut app = create_test_app();
+        app.ui.ref_picker = Some(RefPickerState {
+            selected: 3,
+            ..Default::default()
+        });
+
+        let result = TaskResult::RefsLoaded { refs: sample_refs() };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        let picker = app.ui.ref_picker.as_ref().unwrap();
+        assert_eq!(picker.refs, Some(sample_refs()));
+        assert_eq!(picker.selected, 0);
+    }
+
+    #[test]
+    fn test_handle_refs_loaded_without_picker_updates_decorations() {
+        let mut app = create_test_app();
+
+        let result = TaskResult::RefsLoaded { refs: sample_refs() };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert!(app.ui.ref_picker.is_none());
+        assert_eq!(app.refs_by_commit.get("abc123"), Some(&sample_refs()));
+    }
+
+    #[test]
+    fn test_handle_diff_generated_for_current_base() {
+        let mut app = create_test_app();
+        app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.selected_commit_hash = Some("abc123".to_string());
+        app.inspector.diff_base = DiffBase::Commit("def456".to_string());
+
+        let result = TaskResult::DiffGenerated {
+            file_path: "src/main.rs".to_string(),
+            current_commit: "abc123".to_string(),
+            base: DiffBase::Commit("def456".to_string()),
+            base_commit: Some("def456".to_string()),
+            diff_lines: vec![],
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.inspector.diff_lines.as_ref().map(Vec::len), Some(0));
+        assert_eq!(app.inspector.base_commit_hash, Some("def456".to_string()));
+        assert!(app.ui.status_message.contains("against def456"));
+    }
+
+    #[test]
+    fn test_handle_diff_generated_for_other_base_is_ignored() {
+        let mut app = create_test_app();
+        app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.selected_commit_hash = Some("abc123".to_string());
+        app.inspector.diff_base = DiffBase::WorkingTree;
+
+        let result = TaskResult::DiffGenerated {
+            file_path: "src/main.rs".to_string(),
+            current_commit: "abc123".to_string(),
+            base: DiffBase::Parent(0),
+            base_commit: Some("def456".to_string()),
+            diff_lines: vec![],
+        };
+
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert!(app.inspector.diff_lines.is_none());
+        assert!(app.ui.status_message.contains("ignored"));
+    }
+
     #[test]
     fn test_handle_error_result() {
         let mut app = create_test_app();
@@ -447,8 +926,518 @@ mod task_result_handling {
     }
 }
 
+mod blame_jump {
+    use super::*;
+    use git_lineage::async_task::Task;
+    use tokio::sync::mpsc;
+
+    fn create_blamed_app() -> App {
+        let mut app = create_test_app();
+        app.navigator.handle_event(git_lineage::navigator::NavigatorEvent::SelectFile(
+            std::path::PathBuf::from("src/main.rs")
+        ));
+        app.history.selected_commit_index = Some(0);
+        app.history.selected_commit_hash = Some("abc123".to_string());
+        app.inspector.current_content = vec!["fn main() {}".to_string()];
+        app.inspector.blame_commit_hash = Some("abc123".to_string());
+        app.inspector.current_blame = Some(vec![BlameLine {
+            commit_hash: "def456".to_string(),
+            short_hash: "def456".to_string(),
+            author: "Other Author".to_string(),
+            timestamp: 1672531200,
+            original_line_number: 0,
+            original_path: "src/main.rs".to_string(),
+        }]);
+        app
+    }
+
+    #[tokio::test]
+    async fn test_jump_to_blame_commit_in_history() {
+        let mut app = create_blamed_app();
+        app.history.commit_list.push(CommitInfo {
+            hash: "def456".to_string(),
+            short_hash: "def456".to_string(),
+            author: "Other Author".to_string(),
+            date: "2022-12-31".to_string(),
+            subject: "Older commit".to_string(),
+            file_path: None,
+            ..Default::default()
+        });
+        let (task_sender, _task_receiver) = mpsc::channel::<Task>(10);
+
+        git_lineage::event::jump_to_blame_commit(&mut app, &task_sender);
+
+        assert_eq!(app.history.selected_commit_index, Some(1));
+        assert_eq!(app.history.selected_commit_hash, Some("def456".to_string()));
+        assert_eq!(app.history.pending_jump, None);
+        assert!(app.ui.status_message.contains("Jumped to def456"));
+    }
+
+    #[tokio::test]
+    async fn test_jump_to_blame_commit_waits_for_history() {
+        let mut app = create_blamed_app();
+        let (task_sender, _task_receiver) = mpsc::channel::<Task>(10);
+
+        git_lineage::event::jump_to_blame_commit(&mut app, &task_sender);
+
+        assert_eq!(app.history.selected_commit_index, Some(0));
+        assert_eq!(
+            app.history.pending_jump,
+            Some(git_lineage::app::PendingJump {
+                commit_hash: "def456".to_string(),
+                line_number: Some(0),
+            })
+        );
+        assert!(app.history.is_loading_more);
+        assert!(app.ui.status_message.contains("waiting for history"));
+    }
+
+    #[tokio::test]
+    async fn test_jump_to_blame_commit_from_blamed_commit() {
+        let mut app = create_blamed_app();
+        app.history.selected_commit_hash = Some("def456".to_string());
+        app.inspector.blame_commit_hash = Some("def456".to_string());
+        let (task_sender, _task_receiver) = mpsc::channel::<Task>(10);
+
+        git_lineage::event::jump_to_blame_commit(&mut app, &task_sender);
+
+        assert_eq!(app.history.pending_jump, None);
+        assert!(app.ui.status_message.contains("last changed in the selected commit"));
+    }
+
+    #[tokio::test]
+    async fn test_jump_to_blame_commit_loads_blame_in_background() {
+        let mut app = create_blamed_app();
+        app.inspector.clear_blame();
+        app.history.commit_list = vec![
+            CommitInfo {
+                hash: "abc123".to_string(),
+                short_hash: "abc123".to_string(),
+                ..Default::default()
+            },
+            CommitInfo {
+                hash: "def456".to_string(),
+                short_hash: "def456".to_string(),
+                ..Default::default()
+            },
+        ];
+        let (task_sender, mut task_receiver) = mpsc::channel::<Task>(10);
+
+        git_lineage::event::jump_to_blame_commit(&mut app, &task_sender);
+
+        match task_receiver.recv().await {
+            Some(Task::LoadBlame { commit_hash, .. }) => assert_eq!(commit_hash, "abc123"),
+            other => panic!("Expected LoadBlame, got {:?}", other),
+        }
+        assert_eq!(app.history.selected_commit_index, Some(0));
+        assert!(app.ui.status_message.contains("Loading blame"));
+
+        let result = TaskResult::BlameLoaded {
+            file_path: "src/main.rs".to_string(),
+            commit_hash: "abc123".to_string(),
+            blame: vec![BlameLine {
+                commit_hash: "def456".to_string(),
+                short_hash: "def456".to_string(),
+                author: "Other Author".to_string(),
+                timestamp: 1672531200,
+                original_line_number: 0,
+                original_path: "src/main.rs".to_string(),
+            }],
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        assert_eq!(app.history.selected_commit_index, Some(1));
+        assert_eq!(app.inspector.pending_blame_jump, None);
+        assert!(app.ui.status_message.contains("Jumped to def456"));
+    }
+}
+
+mod ref_picker {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use git_lineage::async_task::Task;
+    use tokio::sync::mpsc;
+
+    fn press(app: &mut App, code: KeyCode, task_sender: &mpsc::Sender<Task>) {
+        let key = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        git_lineage::event::handle_event(key, app, task_sender).unwrap();
+    }
+
+    fn type_query(app: &mut App, query: &str, task_sender: &mpsc::Sender<Task>) {
+        for c in query.chars() {
+            press(app, KeyCode::Char(c), task_sender);
+        }
+    }
+
+    fn open_picker(app: &mut App) {
+        let refs = git_lineage::git_utils::list_refs(&app.repo).unwrap();
+        app.ui.ref_picker = Some(RefPickerState {
+            refs: Some(refs),
+            ..Default::default()
+        });
+    }
+
+    fn git(temp_dir: &TempDir, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_enter_prefers_a_resolving_query_over_fuzzy_matches() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_git_repo(&temp_dir).unwrap();
+        let short_hash = git(&temp_dir, &["rev-parse", "--short=7", "HEAD"]);
+        git(&temp_dir, &["tag", &format!("release-{}", short_hash)]);
+        let mut app = App::new(git_lineage::git_utils::open_repository(temp_dir.path()).unwrap());
+        let (task_sender, _task_receiver) = mpsc::channel::<Task>(10);
+
+        open_picker(&mut app);
+        type_query(&mut app, &short_hash, &task_sender);
+
+        let picker = app.ui.ref_picker.as_ref().unwrap();
+        assert!(picker.selected_ref().is_some_and(|r| r.name.starts_with("release-")));
+        assert!(picker.uses_query());
+
+        press(&mut app, KeyCode::Enter, &task_sender);
+
+        assert!(app.ui.ref_picker.is_none());
+        assert_eq!(app.revision, Some(short_hash));
+    }
+
+    #[tokio::test]
+    async fn test_picked_ref_is_switched_to_by_full_name() {
+        let temp_dir = TempDir::new().unwrap();
+        create_test_git_repo(&temp_dir).unwrap();
+        git(&temp_dir, &["branch", "v1"]);
+        git(&temp_dir, &["tag", "v1"]);
+        let mut app = App::new(git_lineage::git_utils::open_repository(temp_dir.path()).unwrap());
+        let (task_sender, _task_receiver) = mpsc::channel::<Task>(10);
+
+        open_picker(&mut app);
+        type_query(&mut app, "v1", &task_sender);
+        // Arrow keys pick from the list instead of the typed revision
+        press(&mut app, KeyCode::Down, &task_sender);
+        press(&mut app, KeyCode::Down, &task_sender);
+        assert!(!app.ui.ref_picker.as_ref().unwrap().uses_query());
+        press(&mut app, KeyCode::Enter, &task_sender);
+
+        assert_eq!(app.revision, Some("refs/tags/v1".to_string()));
+        assert_eq!(git_lineage::app::revision_label(app.revision.as_deref()), "v1");
+    }
+}
+
+mod diff_view_cursor {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use git_lineage::app::{DiffLine, DiffLineType};
+    use git_lineage::async_task::Task;
+    use tokio::sync::mpsc;
+
+    fn diff_line(line_type: DiffLineType, old: Option<usize>, new: Option<usize>, content: &str) -> DiffLine {
+        DiffLine {
+            line_type,
+            old_line_num: old,
+            new_line_num: new,
+            content: content.to_string(),
+            changed_ranges: Vec::new(),
+        }
+    }
+
+    fn press(app: &mut App, code: KeyCode, task_sender: &mpsc::Sender<Task>) {
+        let key = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        git_lineage::event::handle_event(key, app, task_sender).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_leaving_diff_view_keeps_cursor_on_file_line() {
+        let mut app = create_test_app();
+        app.ui.active_panel = PanelFocus::Inspector;
+        app.inspector.current_content = vec!["one".to_string(), "two".to_string(), "three".to_string()];
+        app.inspector.diff_lines = Some(vec![
+            diff_line(DiffLineType::Removed, Some(1), None, "zero"),
+            diff_line(DiffLineType::Removed, Some(2), None, "half"),
+            diff_line(DiffLineType::Added, None, Some(1), "one"),
+            diff_line(DiffLineType::Unchanged, Some(3), Some(2), "two"),
+            diff_line(DiffLineType::Added, None, Some(3), "three"),
+        ]);
+        let (task_sender, _task_receiver) = mpsc::channel::<Task>(10);
+
+        // Open the diff on "one" and move down to the last diff row, "three"
+        press(&mut app, KeyCode::Char('d'), &task_sender);
+        assert_eq!(app.inspector.cursor_line, 2);
+        press(&mut app, KeyCode::Down, &task_sender);
+        press(&mut app, KeyCode::Down, &task_sender);
+        assert_eq!(app.inspector.cursor_line, 4);
+
+        press(&mut app, KeyCode::Char('d'), &task_sender);
+
+        assert!(!app.inspector.show_diff_view);
+        assert_eq!(app.inspector.cursor_line, 2);
+        assert_eq!(app.inspector.current_content[app.inspector.cursor_line], "three");
+    }
+}
+
+mod commit_tree_browsing {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use git_lineage::async_task::Task;
+    use tokio::sync::mpsc;
+
+    fn press_t(app: &mut App, task_sender: &mpsc::Sender<Task>) {
+        let key = Event::Key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
+        git_lineage::event::handle_event(key, app, task_sender).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_browse_tree_of_selected_commit_and_back() {
+        let mut app = create_test_app();
+        let head = git_lineage::git_utils::resolve_revision(&app.repo, None)
+            .unwrap()
+            .to_string();
+        app.ui.active_panel = PanelFocus::History;
+        app.history.selected_commit_hash = Some(head.clone());
+        let (task_sender, mut task_receiver) = mpsc::channel::<Task>(10);
+
+        press_t(&mut app, &task_sender);
+
+        assert_eq!(app.revision, Some(head.clone()));
+        assert_eq!(app.revision_before_commit_tree, Some(None));
+        assert!(app.ui.status_message.contains(&head[..8]));
+        match task_receiver.recv().await {
+            Some(Task::LoadFileTree { revision }) => assert_eq!(revision, Some(head.clone())),
+            other => panic!("Expected LoadFileTree, got {:?}", other),
+        }
+
+        press_t(&mut app, &task_sender);
+
+        assert_eq!(app.revision, None);
+        assert_eq!(app.revision_before_commit_tree, None);
+    }
+
+    #[tokio::test]
+    async fn test_browse_tree_needs_a_selected_commit() {
+        let mut app = create_test_app();
+        app.ui.active_panel = PanelFocus::History;
+        app.history.selected_commit_hash = None;
+        let (task_sender, _task_receiver) = mpsc::channel::<Task>(10);
+
+        press_t(&mut app, &task_sender);
+
+        assert_eq!(app.revision, None);
+        assert_eq!(app.revision_before_commit_tree, None);
+    }
+}
+
+mod directory_history {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use git_lineage::async_task::Task;
+    use git_lineage::navigator::NavigatorEvent;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_selecting_a_directory_loads_its_history() {
+        let mut app = create_test_app();
+        let mut tree = FileTree::new();
+        let mut src = TreeNode::new_dir("src".to_string(), PathBuf::from("src"));
+        src.add_child(TreeNode::new_file("main.rs".to_string(), PathBuf::from("src/main.rs")));
+        src.expand();
+        tree.root.push(src);
+        app.navigator = NavigatorState::new(tree);
+        app.navigator
+            .handle_event(NavigatorEvent::SelectFile(PathBuf::from("src/main.rs")))
+            .unwrap();
+        let (task_sender, mut task_receiver) = mpsc::channel::<Task>(10);
+
+        // Moving up from the file selects the directory
+        let key = Event::Key(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        git_lineage::event::handle_event(key, &mut app, &task_sender).unwrap();
+
+        assert_eq!(app.get_active_file(), None);
+        assert_eq!(app.get_history_path(), Some(PathBuf::from("src")));
+        match task_receiver.recv().await {
+            Some(Task::LoadCommitHistoryStreaming { file_path, .. }) => assert_eq!(file_path, "src"),
+            other => panic!("Expected LoadCommitHistoryStreaming, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_open_file_changed_by_a_directory_commit() {
+        let mut app = create_test_app();
+        let mut tree = FileTree::new();
+        let mut src = TreeNode::new_dir("src".to_string(), PathBuf::from("src"));
+        src.add_child(TreeNode::new_file("main.rs".to_string(), PathBuf::from("src/main.rs")));
+        tree.root.push(src);
+        app.navigator = NavigatorState::new(tree);
+        app.navigator
+            .handle_event(NavigatorEvent::SelectFile(PathBuf::from("src")))
+            .unwrap();
+
+        let commit_hash = "0123456789abcdef0123456789abcdef01234567".to_string();
+        app.history.commit_list = vec![CommitInfo {
+            hash: commit_hash.clone(),
+            short_hash: commit_hash[..8].to_string(),
+            subject: "Change src".to_string(),
+            ..Default::default()
+        }];
+        app.history.selected_commit_index = Some(0);
+        let (task_sender, mut task_receiver) = mpsc::channel::<Task>(10);
+        git_lineage::event::update_code_inspector_for_commit(&mut app, &task_sender);
+
+        // The files changed by the commit are loaded in the background
+        match task_receiver.recv().await {
+            Some(Task::LoadChangedFiles { commit_hash: requested }) => assert_eq!(requested, commit_hash),
+            other => panic!("Expected LoadChangedFiles, got {:?}", other),
+        }
+        assert!(app.inspector.directory_changes.is_empty());
+
+        let changed_file = |path: &str, kind| ChangedFile {
+            path: path.to_string(),
+            kind,
+        };
+        let result = TaskResult::ChangedFilesLoaded {
+            commit_hash: commit_hash.clone(),
+            changed_files: vec![
+                changed_file("README.md", FileChangeKind::Modified),
+                changed_file("lib/moved.rs", FileChangeKind::Renamed("src/moved.rs".to_string())),
+                changed_file("src/main.rs", FileChangeKind::Modified),
+            ],
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        // Only the files below the directory are listed, in place of file content
+        let paths: Vec<&str> = app
+            .inspector
+            .directory_changes
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["lib/moved.rs", "src/main.rs"]);
+        assert!(app.inspector.current_content.is_empty());
+        assert_eq!(app.inspector.line_count(), 2);
+
+        app.ui.active_panel = PanelFocus::Inspector;
+        app.inspector.cursor_line = 1;
+        let key = Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        git_lineage::event::handle_event(key, &mut app, &task_sender).unwrap();
+
+        assert_eq!(app.get_active_file(), Some(PathBuf::from("src/main.rs")));
+        assert_eq!(
+            app.history.pending_jump.as_ref().map(|jump| jump.commit_hash.clone()),
+            Some(commit_hash)
+        );
+        match task_receiver.recv().await {
+            Some(Task::LoadCommitHistoryStreaming { file_path, .. }) => assert_eq!(file_path, "src/main.rs"),
+            other => panic!("Expected LoadCommitHistoryStreaming, got {:?}", other),
+        }
+    }
+}
+
+mod deleted_files {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use git_lineage::async_task::Task;
+    use git_lineage::git_utils::DeletedFile;
+    use git_lineage::navigator::NavigatorEvent;
+    use git_lineage::tree::DELETED_FILES_PATH;
+    use tokio::sync::mpsc;
+
+    fn press(app: &mut App, code: KeyCode, task_sender: &mpsc::Sender<Task>) {
+        let key = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        git_lineage::event::handle_event(key, app, task_sender).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_opening_the_folder_loads_deleted_files_once() {
+        let mut app = create_test_app();
+        let mut tree = FileTree::new();
+        tree.root.push(TreeNode::new_file("main.rs".to_string(), PathBuf::from("main.rs")));
+        tree.root.push(TreeNode::new_dir(
+            "Deleted files".to_string(),
+            PathBuf::from(DELETED_FILES_PATH),
+        ));
+        app.navigator = NavigatorState::new(tree);
+        app.navigator
+            .handle_event(NavigatorEvent::SelectFile(PathBuf::from(DELETED_FILES_PATH)))
+            .unwrap();
+        app.ui.active_panel = PanelFocus::Navigator;
+        let (task_sender, mut task_receiver) = mpsc::channel::<Task>(10);
+
+        press(&mut app, KeyCode::Right, &task_sender);
+        match task_receiver.recv().await {
+            Some(Task::LoadDeletedFiles { revision, .. }) => assert_eq!(revision, None),
+            other => panic!("Expected LoadDeletedFiles, got {:?}", other),
+        }
+
+        // Closing and opening the folder again does not walk the history again
+        press(&mut app, KeyCode::Left, &task_sender);
+        press(&mut app, KeyCode::Right, &task_sender);
+        tokio::task::yield_now().await;
+        assert!(task_receiver.try_recv().is_err());
+
+        let result = TaskResult::DeletedFilesLoaded {
+            revision: None,
+            deleted_files: vec![DeletedFile {
+                path: "src/gone.rs".to_string(),
+                commit_hash: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            }],
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+
+        let names: Vec<String> = app
+            .navigator
+            .build_view_model()
+            .items
+            .iter()
+            .map(|item| item.name.clone())
+            .collect();
+        assert_eq!(names, vec!["main.rs", "Deleted files", "src/gone.rs (deleted in 01234567)"]);
+
+        // The deleted file can be selected like any other to show its history
+        press(&mut app, KeyCode::Down, &task_sender);
+        assert_eq!(app.get_active_file(), Some(PathBuf::from("src/gone.rs")));
+    }
+
+    #[tokio::test]
+    async fn test_failed_search_is_retried_when_the_folder_is_opened_again() {
+        let mut app = create_test_app();
+        let mut tree = FileTree::new();
+        tree.root.push(TreeNode::new_file("main.rs".to_string(), PathBuf::from("main.rs")));
+        tree.root.push(TreeNode::new_dir(
+            "Deleted files".to_string(),
+            PathBuf::from(DELETED_FILES_PATH),
+        ));
+        app.navigator = NavigatorState::new(tree);
+        app.navigator
+            .handle_event(NavigatorEvent::SelectFile(PathBuf::from(DELETED_FILES_PATH)))
+            .unwrap();
+        app.ui.active_panel = PanelFocus::Navigator;
+        let (task_sender, mut task_receiver) = mpsc::channel::<Task>(10);
+
+        press(&mut app, KeyCode::Right, &task_sender);
+        assert!(matches!(task_receiver.recv().await, Some(Task::LoadDeletedFiles { .. })));
+
+        let result = TaskResult::DeletedFilesFailed {
+            revision: None,
+            message: "object not found".to_string(),
+        };
+        git_lineage::main_lib::handle_task_result(&mut app, result);
+        assert_eq!(app.deleted_files, None);
+        assert!(app.ui.status_message.contains("object not found"));
+
+        press(&mut app, KeyCode::Left, &task_sender);
+        press(&mut app, KeyCode::Right, &task_sender);
+        assert!(matches!(task_receiver.recv().await, Some(Task::LoadDeletedFiles { .. })));
+    }
+}
+
 mod command_execution {
     use super::*;
+    use git_lineage::command::Command;
 
     #[test]
     #[serial]
@@ -598,6 +1587,133 @@ mod command_execution {
         assert_ok!(&result);
         assert!(output_path.exists());
     }
+
+    fn execute_in_inspector(command: Command) -> git_lineage::executor::ExecutionResult {
+        let config = TestConfig {
+            active_panel: PanelFocus::Inspector,
+            ..TestConfig::default()
+        };
+        git_lineage::executor::Executor::execute(&config, command)
+    }
+
+    #[test]
+    fn test_change_search_is_simulated() {
+        let result = execute_in_inspector(Command::NextChange);
+        assert!(result.config.is_loading);
+        assert_eq!(result.status_message.as_deref(), Some("Searching for next change..."));
+        assert_eq!(result.config.selected_commit_index, Some(0));
+
+        let result = execute_in_inspector(Command::PreviousChange);
+        assert_eq!(result.status_message.as_deref(), Some("Searching for previous change..."));
+    }
+
+    #[test]
+    fn test_blame_jump_needs_loaded_blame() {
+        let result = execute_in_inspector(Command::JumpToBlameCommit);
+        assert_eq!(result.status_message.as_deref(), Some("No blame loaded for the selected commit"));
+        assert_eq!(result.config.selected_commit_index, Some(0));
+
+        let mut config = TestConfig {
+            active_panel: PanelFocus::Inspector,
+            cursor_line: 0,
+            ..TestConfig::default()
+        };
+        let last_commit = config.commit_list[2].clone();
+        config.current_blame = Some(vec![BlameLine {
+            commit_hash: last_commit.hash.clone(),
+            short_hash: last_commit.short_hash.clone(),
+            author: last_commit.author.clone(),
+            timestamp: 0,
+            original_line_number: 4,
+            original_path: "src/main.rs".to_string(),
+        }]);
+        let result = git_lineage::executor::Executor::execute(&config, Command::JumpToBlameCommit);
+        assert_eq!(result.config.selected_commit_index, Some(2));
+        assert_eq!(result.config.cursor_line, 4);
+    }
+
+    #[test]
+    fn test_commit_details_show_the_configured_changed_files() {
+        let mut config = TestConfig {
+            active_panel: PanelFocus::History,
+            ..TestConfig::default()
+        };
+        let result = git_lineage::executor::Executor::execute(&config, Command::ToggleCommitDetails);
+        assert!(result.config.show_commit_details);
+        assert_eq!(result.config.changed_files, None);
+        assert_eq!(result.status_message.as_deref(), Some("Loading changed files..."));
+
+        config.changed_files = Some(vec![ChangedFile {
+            path: "src/main.rs".to_string(),
+            kind: FileChangeKind::Modified,
+        }]);
+        let result = git_lineage::executor::Executor::execute(&config, Command::ToggleCommitDetails);
+        assert_eq!(result.status_message.as_deref(), Some("Commit a1b2c3d4 changed 1 file(s)"));
+        assert_eq!(result.config.changed_files, config.changed_files);
+    }
+
+    #[test]
+    fn test_cycle_history_mode_reloads_the_history() {
+        let config = TestConfig {
+            active_panel: PanelFocus::History,
+            ..TestConfig::default()
+        };
+        let result = git_lineage::executor::Executor::execute(&config, Command::CycleHistoryMode);
+        assert_eq!(result.config.history_mode, config.history_mode.next());
+        assert!(result.config.is_loading);
+        assert_eq!(
+            result.status_message,
+            Some(format!("History mode: {}", config.history_mode.next().describe()))
+        );
+    }
+
+    #[test]
+    fn test_line_range_history_uses_the_configured_selection() {
+        // Nothing is selected in the file tree by default
+        let result = execute_in_inspector(Command::LineRangeHistory);
+        assert_eq!(result.config.line_range, None);
+        assert_eq!(
+            result.status_message.as_deref(),
+            Some("No file or commit selected for line history")
+        );
+
+        let mut config = TestConfig {
+            active_panel: PanelFocus::Inspector,
+            selected_file_navigator_index: Some(3),
+            selection_anchor: Some(1),
+            cursor_line: 3,
+            ..TestConfig::default()
+        };
+        config.commit_list[0].file_path = Some("src/old_main.rs".to_string());
+        let result = git_lineage::executor::Executor::execute(&config, Command::LineRangeHistory);
+
+        let line_range = result.config.line_range.expect("Should start a line range history");
+        assert_eq!(line_range.file_path, "src/old_main.rs");
+        assert_eq!((line_range.start_line, line_range.end_line), (1, 3));
+        assert_eq!(result.config.selection_anchor, None);
+        assert!(result.config.is_loading);
+    }
+
+    #[test]
+    fn test_next_diff_parent_uses_the_configured_parents() {
+        let result = execute_in_inspector(Command::NextDiffParent);
+        assert_eq!(result.config.diff_base, DiffBase::default());
+
+        // The first parent is the default, so the next one is the second
+
+        let mut config = TestConfig {
+            active_panel: PanelFocus::Inspector,
+            ..TestConfig::default()
+        };
+        config.commit_list[0].parent_ids = vec![
+            config.commit_list[1].hash.clone(),
+            config.commit_list[2].hash.clone(),
+        ];
+        let result = git_lineage::executor::Executor::execute(&config, Command::NextDiffParent);
+        assert_eq!(result.config.diff_base, DiffBase::Parent(1));
+        let result = git_lineage::executor::Executor::execute(&result.config, Command::NextDiffParent);
+        assert_eq!(result.config.diff_base, DiffBase::Parent(0));
+    }
 }
 
 mod state_management {
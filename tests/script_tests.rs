@@ -60,7 +60,7 @@ impl ScriptTestDriver {
         });
         
         // Load initial file tree (same as interactive/headless modes)
-        if let Err(e) = task_sender.send(Task::LoadFileTree).await {
+        if let Err(e) = task_sender.send(Task::LoadFileTree { revision: None }).await {
             return Err(format!("Failed to send LoadFileTree task: {}", e).into());
         }
         
@@ -132,7 +132,7 @@ impl ScriptTestDriver {
         });
         
         // Load initial file tree (same as interactive/headless modes)
-        if let Err(e) = task_sender.send(Task::LoadFileTree).await {
+        if let Err(e) = task_sender.send(Task::LoadFileTree { revision: None }).await {
             return Err(format!("Failed to send LoadFileTree task: {}", e).into());
         }
         